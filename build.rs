@@ -1,4 +1,7 @@
 use anyhow::Result;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 use vergen::EmitBuilder;
 
 pub fn main() -> Result<()> {
@@ -9,5 +12,74 @@ pub fn main() -> Result<()> {
         .git_sha(false)
         .git_dirty(true)
         .emit()?;
+
+    generate_c_bindings()?;
+    Ok(())
+}
+
+/// Generates `ifstat_rs.h` and `ifstat_rs.pc` in OUT_DIR. If `IFSTAT_RS_OUT_DIR` is set, they are
+/// also copied to `$IFSTAT_RS_OUT_DIR/include/ifstat_rs.h` and `$IFSTAT_RS_OUT_DIR/ifstat_rs.pc`
+/// (relative to the crate directory), e.g. to install them.
+fn generate_c_bindings() -> Result<()> {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=IFSTAT_RS_PREFIX");
+    println!("cargo:rerun-if-env-changed=IFSTAT_RS_OUT_DIR");
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    let header = out_dir.join("ifstat_rs.h");
+    let pkg_config = out_dir.join("ifstat_rs.pc");
+
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    // Only the C API is parsed, so public items of other modules never end up in the header
+    let bindings = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src/ffi.rs"))
+        .generate();
+    match bindings {
+        Ok(bindings) => {
+            bindings.write_to_file(&header);
+        }
+        Err(e) => {
+            // Don't break the Rust build over the header, e.g. while ffi.rs doesn't compile
+            println!("cargo:warning=Failed to generate C header: {}", e);
+            return Ok(());
+        }
+    }
+
+    let prefix = env::var("IFSTAT_RS_PREFIX").unwrap_or_else(|_| "/usr/local".to_string());
+    fs::write(
+        &pkg_config,
+        format!(
+            "prefix={}\n\
+             libdir=${{prefix}}/lib\n\
+             includedir=${{prefix}}/include\n\
+             \n\
+             Name: ifstat_rs\n\
+             Description: {}\n\
+             Version: {}\n\
+             URL: {}\n\
+             Libs: -L${{libdir}} -lifstat_rs\n\
+             Cflags: -I${{includedir}}\n",
+            prefix,
+            env::var("CARGO_PKG_DESCRIPTION")?,
+            env::var("CARGO_PKG_VERSION")?,
+            env::var("CARGO_PKG_REPOSITORY")?,
+        ),
+    )?;
+
+    if let Ok(dir) = env::var("IFSTAT_RS_OUT_DIR") {
+        let dir = crate_dir.join(dir);
+        copy_into(&header, &dir.join("include"))?;
+        copy_into(&pkg_config, &dir)?;
+    }
+    Ok(())
+}
+
+fn copy_into(file: &Path, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::copy(file, dir.join(file.file_name().unwrap()))?;
     Ok(())
 }
@@ -0,0 +1,32 @@
+# Type stubs for the `ifstat_rs` Python extension module (src/python.rs).
+
+from typing import Dict, Iterator, List, Optional, Tuple, TypedDict
+
+__version__: str
+
+class InterfaceSample(TypedDict):
+    rx_bytes: int
+    tx_bytes: int
+    rx_bytes_per_s: float
+    tx_bytes_per_s: float
+
+class Sample(TypedDict):
+    time: float
+    interval: float
+    interfaces: Dict[str, InterfaceSample]
+
+def get_net_dev_stats() -> Dict[str, Tuple[int, int]]: ...
+def get_net_dev_counters() -> Dict[str, Dict[str, int]]: ...
+def parse_net_dev_stats(text: str) -> Dict[str, Tuple[int, int]]: ...
+def parse_net_dev_counters(text: str) -> Dict[str, Dict[str, int]]: ...
+
+class Sampler(Iterator[Sample]):
+    def __init__(
+        self,
+        interval: float = 1.0,
+        count: Optional[int] = None,
+        interfaces: Optional[List[str]] = None,
+    ) -> None: ...
+    def sample(self) -> Sample: ...
+    def __iter__(self) -> "Sampler": ...
+    def __next__(self) -> Sample: ...
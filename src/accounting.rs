@@ -0,0 +1,269 @@
+// This module keeps the persistent traffic accounting of `ifstat-rs daemon`, similar to vnStat:
+// cumulative byte totals per interface that survive reboots and counter resets, rolled up into
+// hourly, daily and monthly buckets (UTC).
+//
+// The database is a JSON file holding the counters seen last, so the traffic since then can be
+// added on the next update, even after a restart of the daemon. A counter lower than the one seen
+// last, or a changed boot ID, means the counters were reset and count from zero.
+
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::error::IfstatError;
+use crate::opts::AccountingPeriod;
+use crate::quota::QuotaAlert;
+
+/// Number of hourly buckets kept per interface.
+pub const HOURS_KEPT: usize = 48;
+/// Number of daily buckets kept per interface.
+pub const DAYS_KEPT: usize = 62;
+/// Number of monthly buckets kept per interface.
+pub const MONTHS_KEPT: usize = 24;
+
+/// Bytes received and transmitted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Traffic {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl Traffic {
+    fn add(&mut self, other: Traffic) {
+        self.rx_bytes += other.rx_bytes;
+        self.tx_bytes += other.tx_bytes;
+    }
+}
+
+/// Accounted traffic of one interface.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InterfaceAccount {
+    /// Counters (received, transmitted bytes) seen at the last update.
+    pub counters: (u64, u64),
+    /// Traffic since the interface was first seen.
+    pub total: Traffic,
+    /// Traffic per hour by `YYYY-MM-DDTHH`, oldest first.
+    pub hours: IndexMap<String, Traffic>,
+    /// Traffic per day by `YYYY-MM-DD`, oldest first.
+    pub days: IndexMap<String, Traffic>,
+    /// Traffic per month by `YYYY-MM`, oldest first.
+    pub months: IndexMap<String, Traffic>,
+}
+
+/// The accounting database.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Database {
+    /// Boot ID of the system at the last update, to detect reboots.
+    pub boot_id: Option<String>,
+    /// Seconds since the Unix epoch of the last update.
+    pub updated: Option<u64>,
+    pub interfaces: IndexMap<String, InterfaceAccount>,
+    /// Highest threshold reported per quota (see `Quota::key`) and the period it was reached in.
+    pub quota_alerts: IndexMap<String, QuotaAlert>,
+}
+
+/// Keys of the hourly, daily and monthly buckets of a time in seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketKeys {
+    pub hour: String,
+    pub day: String,
+    pub month: String,
+}
+
+impl BucketKeys {
+    pub fn at(epoch_secs: u64) -> BucketKeys {
+        let days = (epoch_secs / 86400) as i64;
+        let hour = epoch_secs % 86400 / 3600;
+        let (year, month, day) = civil_from_days(days);
+        BucketKeys {
+            hour: format!("{:04}-{:02}-{:02}T{:02}", year, month, day, hour),
+            day: format!("{:04}-{:02}-{:02}", year, month, day),
+            month: format!("{:04}-{:02}", year, month),
+        }
+    }
+}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian (year, month, day).
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Converts a proleptic Gregorian date to days since the Unix epoch.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Days from civil, see http://howardhinnant.github.io/date_algorithms.html
+    let (month, day) = (i64::from(month), i64::from(day));
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Returns the path of the database: `$XDG_DATA_HOME/ifstat-rs/accounting.json`, or
+/// `~/.local/share/ifstat-rs/accounting.json` if `XDG_DATA_HOME` is not set.
+pub fn default_database_path() -> Option<PathBuf> {
+    let data_home = match std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?)
+            .join(".local")
+            .join("share"),
+    };
+    Some(data_home.join("ifstat-rs").join("accounting.json"))
+}
+
+/// Returns the ID of the current boot of the system, if available (Linux).
+pub fn read_boot_id() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/random/boot_id")
+        .ok()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// Returns the counter difference, or `current` if the counter was reset.
+fn counter_delta(previous: u64, current: u64, reset: bool) -> u64 {
+    match reset || current < previous {
+        true => current,
+        false => current - previous,
+    }
+}
+
+/// Adds `traffic` to the bucket `key`, keeping the newest `kept` buckets.
+fn add_to_bucket(
+    buckets: &mut IndexMap<String, Traffic>,
+    key: &str,
+    traffic: Traffic,
+    kept: usize,
+) {
+    match buckets.get_mut(key) {
+        Some(bucket) => bucket.add(traffic),
+        None => {
+            buckets.insert(key.to_string(), traffic);
+            // Keys sort chronologically, also if the clock went back
+            buckets.sort_keys();
+        }
+    }
+    while buckets.len() > kept {
+        buckets.shift_remove_index(0);
+    }
+}
+
+impl Database {
+    /// Reads the database at `path`. A missing file is an empty database.
+    pub fn read(path: &Path) -> Result<Database, IfstatError> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Database::default()),
+            Err(e) => return Err(IfstatError::Io(e).context(path.display())),
+        };
+        serde_json::from_str(&text)
+            .map_err(|e| IfstatError::InvalidData(e.to_string()).context(path.display()))
+    }
+
+    /// Writes the database to `path`, creating its directory if needed. The file is replaced
+    /// atomically, so it stays intact if the process is killed.
+    pub fn write(&self, path: &Path) -> Result<(), IfstatError> {
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)?;
+            }
+            let mut temp = path.as_os_str().to_owned();
+            temp.push(".tmp");
+            std::fs::write(&temp, serde_json::to_vec(self)?)?;
+            std::fs::rename(&temp, path)
+        };
+        write().map_err(|e| IfstatError::Io(e).context(path.display()))
+    }
+
+    /// Adds the traffic since the last update to the buckets of `now` (seconds since the Unix
+    /// epoch), given the current `counters` of the accounted interfaces.
+    ///
+    /// Interfaces are counted from the first update they are seen in. Counters lower than at the
+    /// last update, or a `boot_id` different from the last one, count from zero.
+    pub fn update(
+        &mut self,
+        counters: &IndexMap<String, (u64, u64)>,
+        now: u64,
+        boot_id: Option<&str>,
+    ) {
+        let rebooted = match (&self.boot_id, boot_id) {
+            (Some(previous), Some(current)) => previous != current,
+            _ => false,
+        };
+        let keys = BucketKeys::at(now);
+        for (iface, &(rx, tx)) in counters {
+            let Some(account) = self.interfaces.get_mut(iface) else {
+                let account = InterfaceAccount {
+                    counters: (rx, tx),
+                    ..Default::default()
+                };
+                self.interfaces.insert(iface.clone(), account);
+                continue;
+            };
+            let traffic = Traffic {
+                rx_bytes: counter_delta(account.counters.0, rx, rebooted),
+                tx_bytes: counter_delta(account.counters.1, tx, rebooted),
+            };
+            account.counters = (rx, tx);
+            account.total.add(traffic);
+            add_to_bucket(&mut account.hours, &keys.hour, traffic, HOURS_KEPT);
+            add_to_bucket(&mut account.days, &keys.day, traffic, DAYS_KEPT);
+            add_to_bucket(&mut account.months, &keys.month, traffic, MONTHS_KEPT);
+        }
+        if boot_id.is_some() {
+            self.boot_id = boot_id.map(str::to_string);
+        }
+        self.updated = Some(now);
+    }
+}
+
+impl InterfaceAccount {
+    /// Returns the traffic of the hour, day and month (`BucketKeys`) containing `now`.
+    pub fn current(&self, now: u64) -> (Traffic, Traffic, Traffic) {
+        let keys = BucketKeys::at(now);
+        let get = |buckets: &IndexMap<String, Traffic>, key: &str| {
+            buckets.get(key).copied().unwrap_or_default()
+        };
+        (
+            get(&self.hours, &keys.hour),
+            get(&self.days, &keys.day),
+            get(&self.months, &keys.month),
+        )
+    }
+
+    /// Returns the traffic shown by `ifstat-rs query` for `period` at `now`: the buckets of the
+    /// period by key, or for the summary the current `hour`, `day` and `month` and the `total`.
+    pub fn rows(&self, period: AccountingPeriod, now: u64) -> IndexMap<String, Traffic> {
+        match period {
+            AccountingPeriod::Summary => {
+                let (hour, day, month) = self.current(now);
+                [
+                    ("hour", hour),
+                    ("day", day),
+                    ("month", month),
+                    ("total", self.total),
+                ]
+                .into_iter()
+                .map(|(key, traffic)| (key.to_string(), traffic))
+                .collect()
+            }
+            AccountingPeriod::Hours => self.hours.clone(),
+            AccountingPeriod::Days => self.days.clone(),
+            AccountingPeriod::Months => self.months.clone(),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+// This module provides offline analysis of saved `/proc/net/dev` dumps.
+//
+// Incident bundles often contain periodic `cat /proc/net/dev` output with a timestamp line
+// (e.g. from `date`) in between. These files are split into snapshots here, and every snapshot
+// is parsed with the same `parse_net_dev_stats` that is used for live statistics.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::Path;
+
+use indexmap::IndexMap;
+use regex::Regex;
+
+use crate::error::IfstatError;
+use crate::net_stats::parse_net_dev_stats;
+
+lazy_static::lazy_static! {
+    // 2024-08-01 12:00:00, 2024-08-01T12:00:00.123+02:00, 2024-08-01T12:00:00Z
+    static ref ISO_TIMESTAMP: Regex = Regex::new(
+        r"^(\d{4})-(\d{2})-(\d{2})[T ](\d{2}):(\d{2}):(\d{2})(\.\d+)?\s*(Z|[+-]\d{2}:?\d{2})?$"
+    )
+    .unwrap();
+    // Default `date` output: Thu Aug  1 12:00:00 UTC 2024
+    static ref DATE_TIMESTAMP: Regex = Regex::new(
+        r"^[A-Z][a-z]{2}\s+([A-Z][a-z]{2})\s+(\d{1,2})\s+(\d{2}):(\d{2}):(\d{2})(?:\s+\S+)?\s+(\d{4})$"
+    )
+    .unwrap();
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Statistics of one snapshot together with the timestamp found in front of it, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedStats {
+    /// Seconds since the Unix epoch (time zone names are ignored, only differences matter).
+    pub timestamp: Option<f64>,
+    /// Interface name to (received bytes, transmitted bytes).
+    pub stats: IndexMap<String, (u64, u64)>,
+}
+
+/// Parses a timestamp line.
+///
+/// Accepts Unix epoch seconds (`date +%s`, optionally with fraction), ISO 8601 date-times
+/// and the default output of `date`. Returns seconds since the Unix epoch.
+pub fn parse_timestamp(line: &str) -> Option<f64> {
+    let line = line.trim();
+    let epoch = line.strip_prefix('@').unwrap_or(line);
+    if !epoch.is_empty() && epoch.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return epoch.parse().ok();
+    }
+
+    if let Some(caps) = ISO_TIMESTAMP.captures(line) {
+        let num = |i: usize| caps[i].parse::<i64>().unwrap();
+        let mut seconds = civil_to_epoch(num(1), num(2), num(3), num(4), num(5), num(6))?;
+        if let Some(offset) = caps.get(8).map(|m| m.as_str()).filter(|s| *s != "Z") {
+            let digits: String = offset[1..].chars().filter(|c| *c != ':').collect();
+            let minutes =
+                digits[..2].parse::<i64>().ok()? * 60 + digits[2..].parse::<i64>().ok()?;
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            seconds -= sign * minutes * 60;
+        }
+        let fraction = caps
+            .get(7)
+            .map(|m| format!("0{}", m.as_str()).parse::<f64>().unwrap())
+            .unwrap_or(0.0);
+        return Some(seconds as f64 + fraction);
+    }
+
+    if let Some(caps) = DATE_TIMESTAMP.captures(line) {
+        let month = MONTHS.iter().position(|m| *m == &caps[1])? as i64 + 1;
+        let num = |i: usize| caps[i].parse::<i64>().unwrap();
+        return civil_to_epoch(num(6), month, num(2), num(3), num(4), num(5)).map(|s| s as f64);
+    }
+
+    None
+}
+
+/// Converts a proleptic Gregorian date and time to seconds since the Unix epoch.
+fn civil_to_epoch(year: i64, month: i64, day: i64, hour: i64, min: i64, sec: i64) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+    // Days from civil, see http://howardhinnant.github.io/date_algorithms.html
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    Some(days * 86400 + hour * 3600 + min * 60 + sec)
+}
+
+/// Splits a file of concatenated `/proc/net/dev` dumps into parsed snapshots.
+///
+/// A snapshot starts at an `Inter-|` header line and continues as long as lines contain a colon.
+/// The last timestamp line seen before a snapshot becomes its timestamp; all other lines
+/// between snapshots are ignored.
+pub fn parse_snapshot_dump<R: BufRead>(reader: R) -> Result<Vec<TimedStats>, IfstatError> {
+    let lines: Vec<_> = reader.lines().collect::<Result<_, _>>()?;
+    let mut snapshots = Vec::new();
+    let mut timestamp = None;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = &lines[index];
+        if !line.trim_start().starts_with("Inter-|") {
+            if let Some(ts) = parse_timestamp(line) {
+                timestamp = Some(ts);
+            }
+            index += 1;
+            continue;
+        }
+
+        // The two header lines, then all interface lines
+        let start = index;
+        index = (index + 2).min(lines.len());
+        while index < lines.len()
+            && lines[index].contains(':')
+            && !lines[index].trim_start().starts_with("Inter-|")
+            && parse_timestamp(&lines[index]).is_none()
+        {
+            index += 1;
+        }
+
+        let block = lines[start..index].join("\n");
+        let stats = parse_net_dev_stats(Cursor::new(block))
+            .map_err(|e| e.context(format!("Snapshot starting at line {}", start + 1)))?;
+        snapshots.push(TimedStats {
+            timestamp: timestamp.take(),
+            stats,
+        });
+    }
+
+    Ok(snapshots)
+}
+
+/// Reads and splits all snapshot dumps from the given files, in order.
+pub fn read_snapshot_files<P: AsRef<Path>>(paths: &[P]) -> Result<Vec<TimedStats>, IfstatError> {
+    let mut snapshots = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let parsed = File::open(path)
+            .map_err(IfstatError::from)
+            .and_then(|file| parse_snapshot_dump(BufReader::new(file)))
+            .map_err(|e| e.context(path.display()))?;
+        snapshots.extend(parsed);
+    }
+    Ok(snapshots)
+}
+
+/// Determines the interval in seconds between each pair of consecutive snapshots.
+///
+/// An explicitly stated `interval` is used for every pair. Otherwise both snapshots of a pair
+/// must carry a timestamp, and the timestamps must be strictly increasing.
+pub fn snapshot_intervals(
+    snapshots: &[TimedStats],
+    interval: Option<f64>,
+) -> Result<Vec<f64>, IfstatError> {
+    snapshots
+        .windows(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            if let Some(interval) = interval {
+                return Ok(interval);
+            }
+            match (pair[0].timestamp, pair[1].timestamp) {
+                (Some(prev), Some(cur)) if cur > prev => Ok(cur - prev),
+                (Some(_), Some(_)) => Err(IfstatError::InvalidData(format!(
+                    "Timestamp of snapshot {} is not after snapshot {}",
+                    i + 2,
+                    i + 1
+                ))),
+                _ => Err(IfstatError::InvalidData(format!(
+                    "Snapshot {} or {} has no timestamp, an interval must be given",
+                    i + 1,
+                    i + 2
+                ))),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,131 @@
+// This module breaks the traffic of bonding and team masters down by member, so a bond running
+// on only one leg stands out.
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::net_stats::{read_bond_info, InterfaceInfo};
+
+/// Minimum traffic of a bond in bytes per second before uneven members are flagged, so idle
+/// bonds don't flag noise.
+pub const MIN_DIVERGENCE_BYTES_PER_S: f64 = 10.0 * 1024.0;
+
+/// A bond or team master and its members.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BondGroup {
+    pub name: String,
+    /// Bonding mode, e.g. `802.3ad` or `active-backup`. `None` for teams.
+    pub mode: Option<String>,
+    pub members: Vec<String>,
+}
+
+/// Traffic of one member of a bond.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct MemberShare {
+    pub name: String,
+    pub rx_bytes_per_s: f64,
+    pub tx_bytes_per_s: f64,
+    /// Fraction of the traffic of all members (received and transmitted) on this member.
+    pub share: f64,
+    /// True if the share is far from an even split between the members.
+    pub diverging: bool,
+}
+
+/// Traffic of a bond and its members over one interval.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct BondBreakdown {
+    pub name: String,
+    pub mode: Option<String>,
+    pub rx_bytes_per_s: f64,
+    pub tx_bytes_per_s: f64,
+    pub members: Vec<MemberShare>,
+}
+
+/// Finds the bond and team masters among `interfaces` and their members.
+///
+/// Members are the interfaces whose master is the bond, or the slaves listed in
+/// `<sysfs_root>/<bond>/bonding/slaves` if the metadata has no masters.
+pub fn find_bonds(
+    interfaces: &[String],
+    infos: &IndexMap<String, InterfaceInfo>,
+    sysfs_root: &Path,
+) -> Vec<BondGroup> {
+    interfaces
+        .iter()
+        .filter(|iface| {
+            infos
+                .get(*iface)
+                .is_some_and(|info| info.kind == "bond" || info.kind == "team")
+        })
+        .map(|iface| {
+            let bond_info = read_bond_info(sysfs_root, iface);
+            let mut members: Vec<String> = infos
+                .values()
+                .filter(|info| info.master.as_ref() == Some(iface))
+                .map(|info| info.name.clone())
+                .collect();
+            if members.is_empty() {
+                members = bond_info
+                    .as_ref()
+                    .map(|(_, slaves)| slaves.clone())
+                    .unwrap_or_default();
+            }
+            BondGroup {
+                name: iface.clone(),
+                mode: bond_info.map(|(mode, _)| mode),
+                members,
+            }
+        })
+        .collect()
+}
+
+impl BondGroup {
+    /// Computes the member shares from the rates (received, transmitted bytes per second) of
+    /// the bond and its members.
+    ///
+    /// A member diverges if its share is off by more than half of an even share. Active-backup
+    /// bonds, bonds with a single member and bonds below `MIN_DIVERGENCE_BYTES_PER_S` are never
+    /// flagged.
+    pub fn breakdown(&self, rates: &IndexMap<String, (f64, f64)>) -> BondBreakdown {
+        let (rx, tx) = rates.get(&self.name).copied().unwrap_or_default();
+        let member_rates: Vec<(f64, f64)> = self
+            .members
+            .iter()
+            .map(|member| rates.get(member).copied().unwrap_or_default())
+            .collect();
+        let total: f64 = member_rates.iter().map(|(rx, tx)| rx + tx).sum();
+        let even_share = 1.0 / self.members.len().max(1) as f64;
+        let check = self.members.len() > 1
+            && self.mode.as_deref() != Some("active-backup")
+            && total >= MIN_DIVERGENCE_BYTES_PER_S;
+
+        let members = self
+            .members
+            .iter()
+            .zip(member_rates)
+            .map(|(name, (member_rx, member_tx))| {
+                let share = if total > 0.0 {
+                    (member_rx + member_tx) / total
+                } else {
+                    0.0
+                };
+                MemberShare {
+                    name: name.clone(),
+                    rx_bytes_per_s: member_rx,
+                    tx_bytes_per_s: member_tx,
+                    share,
+                    diverging: check && (share - even_share).abs() > even_share / 2.0,
+                }
+            })
+            .collect();
+        BondBreakdown {
+            name: self.name.clone(),
+            mode: self.mode.clone(),
+            rx_bytes_per_s: rx,
+            tx_bytes_per_s: tx,
+            members,
+        }
+    }
+}
@@ -0,0 +1,382 @@
+// This module reads the TOML configuration file with a default section and named profiles,
+// e.g.
+//
+//     [default]
+//     hide-zero-counters = true
+//
+//     [profiles.uplinks]
+//     interfaces = ["enp3s0f0", "enp3s0f1"]
+//     label = { enp3s0f0 = "uplink-a", enp3s0f1 = "uplink-b" }
+//     delay = 5
+//
+// The default section applies to every run, the profile selected with `-p` on top of it, and
+// options given on the command line override both.
+
+use std::path::{Path, PathBuf};
+
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::error::IfstatError;
+use crate::opts::{GraphMode, Opts, OutputFormat, ProviderKind};
+use crate::quota::parse_quota;
+
+/// Option values of the default section or a profile. Unset values keep the defaults.
+///
+/// Keys are named after the long options, e.g. `type` for `--type`. The repeatable options also
+/// accept the plural, e.g. `types`.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Profile {
+    pub interfaces: Option<Vec<String>>,
+    pub all: Option<bool>,
+    pub loopback: Option<bool>,
+    #[serde(rename = "type", alias = "types")]
+    pub types: Option<Vec<String>>,
+    #[serde(rename = "exclude-type", alias = "exclude-types")]
+    pub exclude_types: Option<Vec<String>>,
+    #[serde(rename = "state", alias = "states")]
+    pub states: Option<Vec<String>>,
+    #[serde(rename = "driver", alias = "drivers")]
+    pub drivers: Option<Vec<String>>,
+    pub group: Option<bool>,
+    /// Labels by interface name, like `--label`.
+    #[serde(rename = "label", alias = "labels")]
+    pub labels: Option<IndexMap<String, String>>,
+    pub hide_zero_counters: Option<bool>,
+    pub format: Option<OutputFormat>,
+    pub average: Option<u64>,
+    pub ewma: Option<f64>,
+    pub show_raw: Option<bool>,
+    pub peaks: Option<bool>,
+    pub header_interval: Option<u64>,
+    pub no_header_repeat: Option<bool>,
+    pub header_on_resize: Option<bool>,
+    pub single_line: Option<bool>,
+    pub graph: Option<GraphMode>,
+    pub proto: Option<bool>,
+    pub softnet: Option<bool>,
+    pub wireless: Option<bool>,
+    pub qdisc: Option<bool>,
+    pub bonds: Option<bool>,
+    /// Quotas like `--quota`, e.g. `["wwan0=50GiB/month,reset-day=15"]`.
+    #[serde(rename = "quota", alias = "quotas")]
+    pub quotas: Option<Vec<String>>,
+    pub quota_command: Option<String>,
+    pub provider: Option<ProviderKind>,
+    /// Delay between updates in seconds.
+    pub delay: Option<f64>,
+    pub count: Option<u64>,
+    pub first_measurement: Option<f64>,
+}
+
+/// Contents of the configuration file.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub default: Profile,
+    pub profiles: IndexMap<String, Profile>,
+}
+
+/// Returns the path of the configuration file: `$XDG_CONFIG_HOME/ifstat-rs/config.toml`, or
+/// `~/.config/ifstat-rs/config.toml` if `XDG_CONFIG_HOME` is not set.
+pub fn default_config_path() -> Option<PathBuf> {
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("ifstat-rs").join("config.toml"))
+}
+
+impl Profile {
+    /// Returns this profile with the values it doesn't set taken from `fallback`. Labels are
+    /// merged, with the labels of this profile taking precedence.
+    pub fn or(self, fallback: Profile) -> Profile {
+        let labels = match (fallback.labels, self.labels) {
+            (Some(mut labels), Some(overrides)) => {
+                labels.extend(overrides);
+                Some(labels)
+            }
+            (labels, overrides) => overrides.or(labels),
+        };
+        Profile {
+            interfaces: self.interfaces.or(fallback.interfaces),
+            all: self.all.or(fallback.all),
+            loopback: self.loopback.or(fallback.loopback),
+            types: self.types.or(fallback.types),
+            exclude_types: self.exclude_types.or(fallback.exclude_types),
+            states: self.states.or(fallback.states),
+            drivers: self.drivers.or(fallback.drivers),
+            group: self.group.or(fallback.group),
+            labels,
+            hide_zero_counters: self.hide_zero_counters.or(fallback.hide_zero_counters),
+            format: self.format.or(fallback.format),
+            average: self.average.or(fallback.average),
+            ewma: self.ewma.or(fallback.ewma),
+            show_raw: self.show_raw.or(fallback.show_raw),
+            peaks: self.peaks.or(fallback.peaks),
+            header_interval: self.header_interval.or(fallback.header_interval),
+            no_header_repeat: self.no_header_repeat.or(fallback.no_header_repeat),
+            header_on_resize: self.header_on_resize.or(fallback.header_on_resize),
+            single_line: self.single_line.or(fallback.single_line),
+            graph: self.graph.or(fallback.graph),
+            proto: self.proto.or(fallback.proto),
+            softnet: self.softnet.or(fallback.softnet),
+            wireless: self.wireless.or(fallback.wireless),
+            qdisc: self.qdisc.or(fallback.qdisc),
+            bonds: self.bonds.or(fallback.bonds),
+            quotas: self.quotas.or(fallback.quotas),
+            quota_command: self.quota_command.or(fallback.quota_command),
+            provider: self.provider.or(fallback.provider),
+            delay: self.delay.or(fallback.delay),
+            count: self.count.or(fallback.count),
+            first_measurement: self.first_measurement.or(fallback.first_measurement),
+        }
+    }
+
+    /// Sets the options of `opts` that were not given on the command line, according to
+    /// `matches`, to the values of this profile.
+    ///
+    /// A boolean option counts as given if its negation, e.g. `--no-peaks`, is. Values
+    /// conflicting with an option given on the command line are ignored too, e.g. `all` with
+    /// `-i`, `no-header-repeat` with `--header-interval`, `graph` with `--single-line` and the
+    /// table only options with another `--format`. Options for live statistics are ignored when
+    /// replaying a recording. Labels given on the command line are added to the labels of the
+    /// profile.
+    pub fn apply(&self, opts: &mut Opts, matches: &ArgMatches) -> Result<(), IfstatError> {
+        let unset = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+        let unset_flag = |id: &str, negation: &str| unset(id) && unset(negation);
+        let live = opts.replay.is_none();
+        let table = unset("format") || opts.format == OutputFormat::Table;
+
+        if let (true, Some(interfaces)) = (unset("interfaces"), &self.interfaces) {
+            opts.interfaces = Some(interfaces.join(","));
+        }
+        set(
+            unset_flag("monitor_all", "no_all") && unset("interfaces"),
+            &mut opts.monitor_all,
+            &self.all,
+        );
+        set(
+            unset_flag("monitor_loopback", "no_loopback"),
+            &mut opts.monitor_loopback,
+            &self.loopback,
+        );
+        set(
+            unset_flag("group", "no_group") && live,
+            &mut opts.group,
+            &self.group,
+        );
+        set(unset("types") && live, &mut opts.types, &self.types);
+        set(
+            unset("exclude_types") && live,
+            &mut opts.exclude_types,
+            &self.exclude_types,
+        );
+        set(unset("states") && live, &mut opts.states, &self.states);
+        set(unset("drivers") && live, &mut opts.drivers, &self.drivers);
+        if let Some(labels) = &self.labels {
+            let cli_labels = std::mem::take(&mut opts.labels);
+            opts.labels = labels.clone().into_iter().chain(cli_labels).collect();
+        }
+        set(
+            unset_flag("hide_zero_counters", "no_hide_zero_counters"),
+            &mut opts.hide_zero_counters,
+            &self.hide_zero_counters,
+        );
+        set(unset("format"), &mut opts.format, &self.format);
+        set(
+            unset_flag("show_raw", "no_show_raw"),
+            &mut opts.show_raw,
+            &self.show_raw,
+        );
+        set(
+            unset_flag("peaks", "no_peaks"),
+            &mut opts.peaks,
+            &self.peaks,
+        );
+        set(
+            unset_flag("no_header_repeat", "header_repeat") && unset("header_interval"),
+            &mut opts.no_header_repeat,
+            &self.no_header_repeat,
+        );
+        set(
+            unset_flag("header_on_resize", "no_header_on_resize"),
+            &mut opts.header_on_resize,
+            &self.header_on_resize,
+        );
+        set(
+            unset_flag("single_line", "no_single_line") && unset("graph") && table,
+            &mut opts.single_line,
+            &self.single_line,
+        );
+        if let (true, Some(graph)) = (unset("graph") && unset("single_line") && table, self.graph) {
+            opts.graph = Some(graph);
+        }
+        set(
+            unset_flag("proto", "no_proto") && live,
+            &mut opts.proto,
+            &self.proto,
+        );
+        set(
+            unset_flag("softnet", "no_softnet") && live,
+            &mut opts.softnet,
+            &self.softnet,
+        );
+        set(
+            unset_flag("wireless", "no_wireless") && live,
+            &mut opts.wireless,
+            &self.wireless,
+        );
+        set(
+            unset_flag("qdisc", "no_qdisc") && live,
+            &mut opts.qdisc,
+            &self.qdisc,
+        );
+        set(
+            unset_flag("bonds", "no_bonds") && live,
+            &mut opts.bonds,
+            &self.bonds,
+        );
+        if let (true, Some(quotas)) = (unset("quotas"), &self.quotas) {
+            opts.quotas = quotas
+                .iter()
+                .map(|quota| parse_quota(quota))
+                .collect::<Result<_, _>>()
+                .map_err(IfstatError::InvalidData)?;
+        }
+        if let (true, Some(command)) = (unset("quota_command"), &self.quota_command) {
+            opts.quota_command = Some(command.clone());
+        }
+        set(
+            unset("provider") && live,
+            &mut opts.provider,
+            &self.provider,
+        );
+
+        if let (true, Some(delay)) = (unset("delay"), self.delay) {
+            if delay <= 0.0 {
+                return Err(IfstatError::InvalidData(format!(
+                    "delay must be greater than 0, not {}",
+                    delay
+                )));
+            }
+            opts.delay = delay;
+        }
+        // --average and --ewma exclude each other, so either on the command line wins
+        if unset("average") && unset("ewma") {
+            match (self.average, self.ewma) {
+                (Some(0), _) => {
+                    return Err(IfstatError::InvalidData(
+                        "average must be greater than 0".to_string(),
+                    ))
+                }
+                (Some(_), Some(_)) => {
+                    return Err(IfstatError::InvalidData(
+                        "average and ewma can't be combined".to_string(),
+                    ))
+                }
+                (_, Some(alpha)) if alpha <= 0.0 || alpha > 1.0 => {
+                    return Err(IfstatError::InvalidData(format!(
+                        "ewma must be greater than 0 and at most 1, not {}",
+                        alpha
+                    )))
+                }
+                (average, ewma) => {
+                    opts.average = average.or(opts.average);
+                    opts.ewma = ewma.or(opts.ewma);
+                }
+            }
+        }
+        if let (true, Some(interval)) = (
+            unset("header_interval") && unset("no_header_repeat"),
+            self.header_interval,
+        ) {
+            if interval == 0 {
+                return Err(IfstatError::InvalidData(
+                    "header-interval must be greater than 0".to_string(),
+                ));
+            }
+            opts.header_interval = interval;
+        }
+        if let (true, Some(count)) = (unset("count"), self.count) {
+            if count == 0 {
+                return Err(IfstatError::InvalidData(
+                    "count must be greater than 0".to_string(),
+                ));
+            }
+            opts.count = Some(count);
+        }
+        if let (true, Some(first)) = (unset("first_measurement"), self.first_measurement) {
+            if first < 0.0 {
+                return Err(IfstatError::InvalidData(format!(
+                    "first-measurement must be greater than or equal to 0, not {}",
+                    first
+                )));
+            }
+            opts.first_measurement = Some(first);
+        }
+        Ok(())
+    }
+}
+
+/// Sets `target` to `value` if the option is unset and the profile has a value.
+fn set<T: Clone>(unset: bool, target: &mut T, value: &Option<T>) {
+    if let (true, Some(value)) = (unset, value) {
+        *target = value.clone();
+    }
+}
+
+impl Config {
+    /// Parses a configuration file.
+    pub fn parse(text: &str) -> Result<Config, IfstatError> {
+        toml::from_str(text).map_err(|e| IfstatError::InvalidData(e.to_string()))
+    }
+
+    /// Reads a configuration file.
+    pub fn read(path: &Path) -> Result<Config, IfstatError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| IfstatError::Io(e).context(path.display()))?;
+        Config::parse(&text).map_err(|e| e.context(path.display()))
+    }
+
+    /// Returns the default section combined with the named profile, if any.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, IfstatError> {
+        let Some(name) = name else {
+            return Ok(self.default.clone());
+        };
+        match self.profiles.get(name) {
+            Some(profile) => Ok(profile.clone().or(self.default.clone())),
+            None => Err(IfstatError::InvalidData(format!(
+                "No profile named `{}`",
+                name
+            ))),
+        }
+    }
+}
+
+/// Applies the configuration file to `opts`: the file given with `--config`, or else the default
+/// file if it exists, with the profile selected with `-p`.
+pub fn apply_config(opts: &mut Opts, matches: &ArgMatches) -> Result<(), IfstatError> {
+    let path = match &opts.config {
+        Some(path) => path.clone(),
+        None => match default_config_path() {
+            Some(path) if path.exists() => path,
+            // A profile can't be selected without a configuration file
+            _ if opts.profile.is_some() => {
+                return Err(IfstatError::InvalidData(
+                    "No configuration file found for --profile".to_string(),
+                ))
+            }
+            _ => return Ok(()),
+        },
+    };
+    let config = Config::read(&path)?;
+    config
+        .profile(opts.profile.as_deref())
+        .map_err(|e| e.context(path.display()))?
+        .apply(opts, matches)
+        .map_err(|e| e.context(path.display()))
+}
@@ -0,0 +1,134 @@
+// This file contains the error type used throughout the library.
+
+use std::fmt;
+
+/// Errors reported by the library.
+#[derive(Debug)]
+pub enum IfstatError {
+    /// Reading the statistics failed.
+    Io(std::io::Error),
+    /// A `/proc/net/dev` line has no colon separating the interface name from the counters.
+    MissingColon { line: usize, text: String },
+    /// A `/proc/net/dev` line has fewer counters than expected.
+    TooFewFields {
+        line: usize,
+        text: String,
+        interface: String,
+        fields: usize,
+    },
+    /// A counter of a `/proc/net/dev` line is not a valid number.
+    InvalidCounter {
+        line: usize,
+        text: String,
+        interface: String,
+        counter: &'static str,
+    },
+    /// Other malformed input, e.g. in recordings or snapshot dumps.
+    InvalidData(String),
+    /// The provider has no more snapshots.
+    Exhausted,
+    /// The requested functionality is not available on this platform.
+    Unsupported(&'static str),
+    /// An error with additional context, e.g. the file it occurred in.
+    Context {
+        context: String,
+        source: Box<IfstatError>,
+    },
+}
+
+impl IfstatError {
+    /// Wraps the error with additional context.
+    pub fn context<C: fmt::Display>(self, context: C) -> IfstatError {
+        IfstatError::Context {
+            context: context.to_string(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the line number for parse errors of `/proc/net/dev` lines.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            IfstatError::MissingColon { line, .. }
+            | IfstatError::TooFewFields { line, .. }
+            | IfstatError::InvalidCounter { line, .. } => Some(*line),
+            IfstatError::Context { source, .. } => source.line(),
+            _ => None,
+        }
+    }
+
+    /// Returns the interface name for parse errors of `/proc/net/dev` lines, if known.
+    pub fn interface(&self) -> Option<&str> {
+        match self {
+            IfstatError::TooFewFields { interface, .. }
+            | IfstatError::InvalidCounter { interface, .. } => Some(interface),
+            IfstatError::Context { source, .. } => source.interface(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IfstatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IfstatError::Io(e) => write!(f, "{}", e),
+            IfstatError::MissingColon { line, text } => {
+                write!(
+                    f,
+                    "Invalid line format (no colon found) at line {}: '{}'",
+                    line, text
+                )
+            }
+            IfstatError::TooFewFields {
+                line,
+                text,
+                interface,
+                fields,
+            } => write!(
+                f,
+                "Invalid line format: {} fields for {} at line {}: '{}'",
+                fields, interface, line, text
+            ),
+            IfstatError::InvalidCounter {
+                line,
+                text,
+                interface,
+                counter,
+            } => write!(
+                f,
+                "Invalid {} for {} at line {}: '{}'",
+                counter, interface, line, text
+            ),
+            IfstatError::InvalidData(message) => write!(f, "{}", message),
+            IfstatError::Exhausted => write!(f, "No more snapshots"),
+            IfstatError::Unsupported(message) => write!(f, "{}", message),
+            IfstatError::Context { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+impl std::error::Error for IfstatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IfstatError::Io(e) => Some(e),
+            IfstatError::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for IfstatError {
+    fn from(e: std::io::Error) -> Self {
+        IfstatError::Io(e)
+    }
+}
+
+impl From<IfstatError> for std::io::Error {
+    fn from(e: IfstatError) -> Self {
+        match e {
+            IfstatError::Io(e) => e,
+            IfstatError::Exhausted => std::io::Error::new(std::io::ErrorKind::UnexpectedEof, e),
+            IfstatError::Unsupported(_) => std::io::Error::new(std::io::ErrorKind::Unsupported, e),
+            _ => std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+// This module samples the optional system-wide statistics shown next to the interface rates,
+// such as the protocol counters of `--proto`, the per-CPU counters of `--softnet`, the link
+// quality of `--wireless`, the qdisc statistics of `--qdisc`, the member breakdown of `--bonds`,
+// the unsmoothed rates of `--show-raw` and the high-water marks of `--peaks`.
+//
+// An `ExtraSampler` keeps the previous snapshot of every enabled source and is polled once per
+// interface sample, so all rates cover the same interval.
+
+use indexmap::IndexMap;
+
+use crate::bond::{BondBreakdown, BondGroup};
+use crate::error::IfstatError;
+use crate::net_stats::{
+    compute_proto_rates, compute_softnet_rates, compute_tc_rates, compute_wireless_rates,
+    get_net_dev_counters, get_proto_stats, get_softnet_stat, get_tc_stats, get_wireless_stats,
+    SoftnetRates, SoftnetStat, TcRates, TcStats, WirelessRates, WirelessStats, PROTO_RATE_COUNTERS,
+};
+use crate::output::{
+    peak_column_groups, peak_column_values, proto_column_groups, proto_column_values,
+    raw_column_groups, raw_column_values, softnet_column_groups, softnet_column_values,
+    wireless_column_groups, wireless_column_values, ColumnGroup,
+};
+use crate::peaks::{InterfacePeaks, PeakTracker};
+use crate::sampler::Sample;
+
+/// Previous snapshots of the enabled extra statistics.
+#[derive(Debug, Default)]
+pub struct ExtraSampler {
+    proto: Option<IndexMap<String, u64>>,
+    softnet: Option<IndexMap<u32, SoftnetStat>>,
+    softnet_cpus: Vec<u32>,
+    wireless: Option<IndexMap<String, WirelessStats>>,
+    wireless_interfaces: Vec<String>,
+    tc: Option<Vec<TcStats>>,
+    tc_interfaces: Vec<String>,
+    bonds: Option<Vec<BondGroup>>,
+    raw_interfaces: Option<Vec<String>>,
+    peaks: Option<PeakTracker>,
+}
+
+/// Rates of the enabled extra statistics over one interval.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExtraRates {
+    /// Protocol rates by `Protocol.Counter`, if enabled.
+    pub proto: Option<IndexMap<String, f64>>,
+    /// Per-CPU softnet rates, if enabled.
+    pub softnet: Option<IndexMap<u32, SoftnetRates>>,
+    /// Link quality and rates per wireless interface, if enabled.
+    pub wireless: Option<IndexMap<String, WirelessRates>>,
+    /// Qdisc and class rates grouped by interface, if enabled. Shown as a sub-table, not as
+    /// columns.
+    pub tc: Option<Vec<TcRates>>,
+    /// Traffic of the bonds and their members, if enabled. Shown as a sub-table as well.
+    pub bonds: Option<Vec<BondBreakdown>>,
+    /// Unsmoothed rates (received, transmitted bytes per second) of the monitored interfaces, if
+    /// enabled.
+    pub raw: Option<IndexMap<String, (f64, f64)>>,
+    /// Highest rates of the monitored interfaces since the start or the last reset, if enabled.
+    pub peaks: Option<IndexMap<String, InterfacePeaks>>,
+    softnet_cpus: Vec<u32>,
+    wireless_interfaces: Vec<String>,
+    raw_interfaces: Vec<String>,
+    peak_interfaces: Vec<String>,
+    peak_packets: bool,
+}
+
+impl ExtraSampler {
+    /// Reads the baseline of every enabled source.
+    pub fn new(proto: bool, softnet: bool) -> Result<ExtraSampler, IfstatError> {
+        let proto = match proto {
+            true => Some(get_proto_stats()?),
+            false => None,
+        };
+        let softnet = match softnet {
+            true => Some(get_softnet_stat()?),
+            false => None,
+        };
+        // The table columns are fixed at start, CPUs coming online later are not shown
+        let softnet_cpus = softnet
+            .as_ref()
+            .map(|stats| stats.keys().copied().collect())
+            .unwrap_or_default();
+        Ok(ExtraSampler {
+            proto,
+            softnet,
+            softnet_cpus,
+            ..Default::default()
+        })
+    }
+
+    /// Enables the link quality columns for the `wl*` interfaces among `interfaces`.
+    pub fn with_wireless(mut self, interfaces: &[String]) -> Result<ExtraSampler, IfstatError> {
+        self.wireless = Some(get_wireless_stats()?);
+        self.wireless_interfaces = interfaces
+            .iter()
+            .filter(|iface| iface.starts_with("wl"))
+            .cloned()
+            .collect();
+        if self.wireless_interfaces.is_empty() {
+            log::warn!("No wireless (wl*) interfaces are monitored");
+        }
+        Ok(self)
+    }
+
+    /// Enables the qdisc and class statistics of `interfaces`.
+    pub fn with_tc(mut self, interfaces: &[String]) -> Result<ExtraSampler, IfstatError> {
+        self.tc = Some(get_tc_stats(interfaces)?);
+        self.tc_interfaces = interfaces.to_vec();
+        Ok(self)
+    }
+
+    /// Enables the member breakdown of the given bonds.
+    pub fn with_bonds(mut self, bonds: Vec<BondGroup>) -> ExtraSampler {
+        if bonds.is_empty() {
+            log::warn!("No bond or team interfaces are monitored");
+        }
+        self.bonds = Some(bonds);
+        self
+    }
+
+    /// Enables the unsmoothed rates of `interfaces`, shown next to the smoothed ones.
+    pub fn with_raw(mut self, interfaces: &[String]) -> ExtraSampler {
+        self.raw_interfaces = Some(interfaces.to_vec());
+        self
+    }
+
+    /// Enables the peak rates of `interfaces`, including packet rates if `packets` is set. Packet
+    /// counters are read from the system, so only set `packets` for the statistics of
+    /// `SystemProvider`.
+    pub fn with_peaks(
+        mut self,
+        interfaces: &[String],
+        packets: bool,
+    ) -> Result<ExtraSampler, IfstatError> {
+        let tracker = PeakTracker::new(interfaces);
+        self.peaks = Some(match packets {
+            true => tracker.with_packets(get_packet_counters()?),
+            false => tracker,
+        });
+        Ok(self)
+    }
+
+    /// Resets the peak rates and returns the previous ones, if enabled.
+    pub fn reset_peaks(&mut self) -> Option<IndexMap<String, InterfacePeaks>> {
+        self.peaks.as_mut().map(PeakTracker::reset)
+    }
+
+    /// Returns true if no extra statistics are enabled.
+    pub fn is_empty(&self) -> bool {
+        self.proto.is_none()
+            && self.softnet.is_none()
+            && self.wireless.is_none()
+            && self.tc.is_none()
+            && self.bonds.is_none()
+            && self.raw_interfaces.is_none()
+            && self.peaks.is_none()
+    }
+
+    /// Returns the table columns of the enabled statistics.
+    pub fn column_groups(&self) -> Vec<ColumnGroup> {
+        let mut groups = Vec::new();
+        if let Some(interfaces) = &self.raw_interfaces {
+            groups.extend(raw_column_groups(interfaces));
+        }
+        if let Some(peaks) = &self.peaks {
+            groups.extend(peak_column_groups(peaks.interfaces(), peaks.has_packets()));
+        }
+        if self.proto.is_some() {
+            groups.extend(proto_column_groups());
+        }
+        if self.softnet.is_some() {
+            groups.extend(softnet_column_groups(&self.softnet_cpus));
+        }
+        if self.wireless.is_some() {
+            groups.extend(wireless_column_groups(&self.wireless_interfaces));
+        }
+        groups
+    }
+
+    /// Reads the current counters and returns the rates over the interval of the interface
+    /// `sample`, which also provides the rates of the bond members, the unsmoothed rates and the
+    /// peak byte rates.
+    ///
+    /// A source that can't be read is logged and yields zero rates, keeping its previous snapshot.
+    pub fn sample(&mut self, sample: &Sample) -> ExtraRates {
+        let interval_secs = sample.interval.as_secs_f64();
+        let proto = self.proto.as_mut().map(|previous| {
+            let current = get_proto_stats().unwrap_or_else(|e| {
+                log::warn!("Error reading protocol statistics: {}", e);
+                previous.clone()
+            });
+            let rates = if interval_secs > 0.0 {
+                compute_proto_rates(previous, &current, &PROTO_RATE_COUNTERS, interval_secs)
+            } else {
+                IndexMap::new()
+            };
+            *previous = current;
+            rates
+        });
+        let softnet = self.softnet.as_mut().map(|previous| {
+            let current = get_softnet_stat().unwrap_or_else(|e| {
+                log::warn!("Error reading softnet statistics: {}", e);
+                previous.clone()
+            });
+            let rates = if interval_secs > 0.0 {
+                compute_softnet_rates(previous, &current, interval_secs)
+            } else {
+                IndexMap::new()
+            };
+            *previous = current;
+            rates
+        });
+        let wireless = self.wireless.as_mut().map(|previous| {
+            let current = get_wireless_stats().unwrap_or_else(|e| {
+                log::warn!("Error reading wireless statistics: {}", e);
+                previous.clone()
+            });
+            let rates = if interval_secs > 0.0 {
+                compute_wireless_rates(previous, &current, interval_secs)
+            } else {
+                IndexMap::new()
+            };
+            *previous = current;
+            rates
+        });
+        let interfaces = &self.tc_interfaces;
+        let tc = self.tc.as_mut().map(|previous| {
+            let current = get_tc_stats(interfaces).unwrap_or_else(|e| {
+                log::warn!("Error reading qdisc statistics: {}", e);
+                previous.clone()
+            });
+            let mut rates = if interval_secs > 0.0 {
+                compute_tc_rates(previous, &current, interval_secs)
+            } else {
+                Vec::new()
+            };
+            // Group by interface in the order of the monitored interfaces
+            rates.sort_by_key(|r| interfaces.iter().position(|iface| *iface == r.interface));
+            *previous = current;
+            rates
+        });
+        let bonds = self.bonds.as_ref().map(|bonds| {
+            bonds
+                .iter()
+                .map(|bond| {
+                    let mut interfaces = bond.members.clone();
+                    interfaces.push(bond.name.clone());
+                    bond.breakdown(&sample.rates(&interfaces))
+                })
+                .collect()
+        });
+        let raw = self
+            .raw_interfaces
+            .as_ref()
+            .map(|interfaces| sample.rates(interfaces));
+        let peaks = self.peaks.as_mut().map(|tracker| {
+            let packets = tracker.has_packets().then(|| {
+                get_packet_counters().unwrap_or_else(|e| {
+                    log::warn!("Error reading packet counters: {}", e);
+                    IndexMap::new()
+                })
+            });
+            tracker.update(sample, packets.filter(|counters| !counters.is_empty()));
+            tracker.peaks().clone()
+        });
+        ExtraRates {
+            proto,
+            softnet,
+            wireless,
+            tc,
+            bonds,
+            raw,
+            peaks,
+            softnet_cpus: self.softnet_cpus.clone(),
+            wireless_interfaces: self.wireless_interfaces.clone(),
+            raw_interfaces: self.raw_interfaces.clone().unwrap_or_default(),
+            peak_interfaces: self
+                .peaks
+                .as_ref()
+                .map(|tracker| tracker.interfaces().to_vec())
+                .unwrap_or_default(),
+            peak_packets: self.peaks.as_ref().is_some_and(PeakTracker::has_packets),
+        }
+    }
+}
+
+impl ExtraRates {
+    /// Returns the values for the columns of `ExtraSampler::column_groups`.
+    pub fn column_values(&self) -> Vec<f64> {
+        let mut values = Vec::new();
+        if let Some(raw) = &self.raw {
+            values.extend(raw_column_values(&self.raw_interfaces, raw));
+        }
+        if let Some(peaks) = &self.peaks {
+            values.extend(peak_column_values(
+                &self.peak_interfaces,
+                self.peak_packets,
+                peaks,
+            ));
+        }
+        if let Some(proto) = &self.proto {
+            values.extend(proto_column_values(proto));
+        }
+        if let Some(softnet) = &self.softnet {
+            values.extend(softnet_column_values(&self.softnet_cpus, softnet));
+        }
+        if let Some(wireless) = &self.wireless {
+            values.extend(wireless_column_values(&self.wireless_interfaces, wireless));
+        }
+        values
+    }
+
+    /// Returns the JSON members of the enabled statistics: `raw`, `peaks`, `protocols`,
+    /// `softnet`, `wireless`, `qdiscs` and `bonds`.
+    pub fn json(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut members = serde_json::Map::new();
+        if let Some(raw) = &self.raw {
+            let interfaces: serde_json::Map<String, serde_json::Value> = raw
+                .iter()
+                .map(|(iface, (rx_rate, tx_rate))| {
+                    let rates =
+                        serde_json::json!({ "rx_bytes_per_s": rx_rate, "tx_bytes_per_s": tx_rate });
+                    (iface.clone(), rates)
+                })
+                .collect();
+            members.insert("raw".to_string(), serde_json::Value::Object(interfaces));
+        }
+        if let Some(peaks) = &self.peaks {
+            members.insert("peaks".to_string(), serde_json::json!(peaks));
+        }
+        if let Some(proto) = &self.proto {
+            members.insert("protocols".to_string(), serde_json::json!(proto));
+        }
+        if let Some(softnet) = &self.softnet {
+            let cpus: serde_json::Map<String, serde_json::Value> = softnet
+                .iter()
+                .map(|(cpu, rates)| (cpu.to_string(), serde_json::json!(rates)))
+                .collect();
+            members.insert("softnet".to_string(), serde_json::Value::Object(cpus));
+        }
+        if let Some(wireless) = &self.wireless {
+            let interfaces: serde_json::Map<String, serde_json::Value> = self
+                .wireless_interfaces
+                .iter()
+                .filter_map(|iface| Some((iface.clone(), serde_json::json!(wireless.get(iface)?))))
+                .collect();
+            members.insert(
+                "wireless".to_string(),
+                serde_json::Value::Object(interfaces),
+            );
+        }
+        if let Some(tc) = &self.tc {
+            members.insert("qdiscs".to_string(), serde_json::json!(tc));
+        }
+        if let Some(bonds) = &self.bonds {
+            members.insert("bonds".to_string(), serde_json::json!(bonds));
+        }
+        members
+    }
+}
+
+/// Reads the packet counters (received, transmitted) of all interfaces.
+fn get_packet_counters() -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+    Ok(get_net_dev_counters()?
+        .into_iter()
+        .map(|(iface, c)| (iface, (c.rx_packets, c.tx_packets)))
+        .collect())
+}
@@ -0,0 +1,441 @@
+// This module contains the C ABI of the library.
+//
+// `GetNetDevStats`/`FreeCString` are the original string based interface used by LabVIEW.
+// The `ifstat_*_v1` functions return typed records instead; their layout never changes; a new
+// layout gets a new version suffix. The `ifstat_sampler_*` functions keep the previous snapshot
+// behind an opaque handle and return rates. No function lets a Rust panic unwind into the caller.
+
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use libc::c_char;
+
+use crate::error::IfstatError;
+use crate::net_stats::{get_net_dev_counters, get_net_dev_stats, NetDevCounters};
+use crate::sampler::{Sample, Sampler};
+
+/// Version of the typed C ABI, as returned by `ifstat_abi_version`.
+pub const IFSTAT_ABI_VERSION: u32 = 1;
+
+/// The call succeeded.
+pub const IFSTAT_OK: i32 = 0;
+/// A required pointer argument was null.
+pub const IFSTAT_ERR_INVALID_ARGUMENT: i32 = 1;
+/// Reading the statistics failed.
+pub const IFSTAT_ERR_IO: i32 = 2;
+/// The statistics could not be parsed.
+pub const IFSTAT_ERR_INVALID_DATA: i32 = 3;
+/// The functionality is not available on this platform.
+pub const IFSTAT_ERR_UNSUPPORTED: i32 = 4;
+/// An internal error occurred.
+pub const IFSTAT_ERR_INTERNAL: i32 = 5;
+/// The sampler has no more snapshots.
+pub const IFSTAT_ERR_EXHAUSTED: i32 = 6;
+
+/// One network interface with all its counters.
+///
+/// `name` is a NUL-terminated UTF-8 string owned by the array it is part of.
+#[repr(C)]
+#[derive(Debug)]
+pub struct IfstatInterfaceV1 {
+    pub name: *mut c_char,
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub rx_dropped: u64,
+    pub rx_fifo: u64,
+    pub rx_frame: u64,
+    pub rx_compressed: u64,
+    pub rx_multicast: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+    pub tx_dropped: u64,
+    pub tx_fifo: u64,
+    pub tx_collisions: u64,
+    pub tx_carrier: u64,
+    pub tx_compressed: u64,
+}
+
+impl IfstatInterfaceV1 {
+    fn new(name: &str, c: &NetDevCounters) -> Self {
+        IfstatInterfaceV1 {
+            name: to_c_string(name).into_raw(),
+            rx_bytes: c.rx_bytes,
+            rx_packets: c.rx_packets,
+            rx_errors: c.rx_errors,
+            rx_dropped: c.rx_dropped,
+            rx_fifo: c.rx_fifo,
+            rx_frame: c.rx_frame,
+            rx_compressed: c.rx_compressed,
+            rx_multicast: c.rx_multicast,
+            tx_bytes: c.tx_bytes,
+            tx_packets: c.tx_packets,
+            tx_errors: c.tx_errors,
+            tx_dropped: c.tx_dropped,
+            tx_fifo: c.tx_fifo,
+            tx_collisions: c.tx_collisions,
+            tx_carrier: c.tx_carrier,
+            tx_compressed: c.tx_compressed,
+        }
+    }
+}
+
+/// Maps an error to one of the `IFSTAT_ERR_*` codes.
+pub fn error_code(e: &IfstatError) -> i32 {
+    match e {
+        IfstatError::Io(_) => IFSTAT_ERR_IO,
+        IfstatError::Unsupported(_) => IFSTAT_ERR_UNSUPPORTED,
+        IfstatError::Exhausted => IFSTAT_ERR_EXHAUSTED,
+        IfstatError::Context { source, .. } => error_code(source),
+        _ => IFSTAT_ERR_INVALID_DATA,
+    }
+}
+
+/// Converts a Rust string to a C string, replacing interior NUL bytes instead of failing.
+pub fn to_c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "\u{FFFD}")).expect("NUL bytes were replaced")
+}
+
+// Converts the statistics into a format that can be returned to LabVIEW
+fn convert_stats_to_c(stats: IndexMap<String, (u64, u64)>) -> *mut c_char {
+    let mut output = String::new();
+    for (device, (received, transmitted)) in stats {
+        output.push_str(&format!("{},{},{}\n", device, received, transmitted));
+    }
+    to_c_string(&output).into_raw()
+}
+
+/// Stores `value` in `out` unless it is null.
+fn set_out<T>(out: *mut T, value: T) {
+    if !out.is_null() {
+        unsafe { out.write(value) };
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn GetNetDevStats() -> *mut c_char {
+    // Call the platform-specific implementation of `get_net_dev_stats` and convert the stats to
+    // a C string. On error or panic, return a null pointer.
+    catch_unwind(|| match get_net_dev_stats() {
+        Ok(stats_map) => convert_stats_to_c(stats_map),
+        Err(_) => ptr::null_mut(),
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+// The signature is kept as is for existing callers of the string based interface
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn FreeCString(s: *mut c_char) {
+    if !s.is_null() {
+        unsafe {
+            drop(CString::from_raw(s));
+        }
+    }
+}
+
+/// Returns the version of the typed C ABI (`IFSTAT_ABI_VERSION`).
+#[no_mangle]
+pub extern "C" fn ifstat_abi_version() -> u32 {
+    IFSTAT_ABI_VERSION
+}
+
+/// Returns all network interfaces with their counters.
+///
+/// The number of records is stored in `out_len`, the `IFSTAT_*` result code in `out_error`
+/// (which may be null). Null is returned exactly when `out_len` is 0: on error, and with
+/// `IFSTAT_OK` if there are no interfaces. The array (or null) must be released with
+/// `ifstat_free_interfaces_v1`.
+///
+/// # Safety
+///
+/// `out_len` must be a valid pointer to a `size_t`, `out_error` must be null or a valid pointer
+/// to an `int32_t`.
+#[no_mangle]
+pub unsafe extern "C" fn ifstat_get_interfaces_v1(
+    out_len: *mut usize,
+    out_error: *mut i32,
+) -> *mut IfstatInterfaceV1 {
+    if out_len.is_null() {
+        set_out(out_error, IFSTAT_ERR_INVALID_ARGUMENT);
+        return ptr::null_mut();
+    }
+    set_out(out_len, 0);
+
+    let result = catch_unwind(|| get_net_dev_counters().map(|counters| interfaces_to_c(&counters)));
+    match result {
+        Ok(Ok((records, len))) => {
+            set_out(out_len, len);
+            set_out(out_error, IFSTAT_OK);
+            records
+        }
+        Ok(Err(e)) => {
+            set_out(out_error, error_code(&e));
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_out(out_error, IFSTAT_ERR_INTERNAL);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Converts counters into a heap array of records, returning the pointer and length. No
+/// counters are returned as null and 0.
+pub fn interfaces_to_c(
+    counters: &IndexMap<String, NetDevCounters>,
+) -> (*mut IfstatInterfaceV1, usize) {
+    if counters.is_empty() {
+        return (ptr::null_mut(), 0);
+    }
+    let records: Box<[IfstatInterfaceV1]> = counters
+        .iter()
+        .map(|(name, c)| IfstatInterfaceV1::new(name, c))
+        .collect();
+    let len = records.len();
+    (Box::into_raw(records) as *mut IfstatInterfaceV1, len)
+}
+
+/// Releases an array returned by `ifstat_get_interfaces_v1`, including all names.
+///
+/// # Safety
+///
+/// `records` must be null or a pointer returned by `ifstat_get_interfaces_v1` together with the
+/// length it reported, and must not have been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ifstat_free_interfaces_v1(records: *mut IfstatInterfaceV1, len: usize) {
+    if records.is_null() {
+        return;
+    }
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let records = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(records, len)) };
+        for record in records.iter() {
+            if !record.name.is_null() {
+                drop(unsafe { CString::from_raw(record.name) });
+            }
+        }
+    }));
+}
+
+/// Rates of one network interface since the previous poll of an `IfstatSampler`.
+///
+/// `name` is a NUL-terminated UTF-8 string owned by the array it is part of.
+#[repr(C)]
+#[derive(Debug)]
+pub struct IfstatRateV1 {
+    pub name: *mut c_char,
+    /// Received bytes counter at the time of the poll.
+    pub rx_bytes: u64,
+    /// Transmitted bytes counter at the time of the poll.
+    pub tx_bytes: u64,
+    pub rx_bytes_per_s: f64,
+    pub tx_bytes_per_s: f64,
+}
+
+/// Opaque sampler handle for C callers.
+///
+/// All functions lock the handle, so one handle can be shared between threads.
+pub struct IfstatSampler {
+    state: Mutex<SamplerState>,
+}
+
+struct SamplerState {
+    sampler: Sampler,
+    /// Interfaces to report, in this order. Empty reports all interfaces.
+    filter: Vec<String>,
+}
+
+impl IfstatSampler {
+    /// Wraps a sampler, e.g. one with a custom provider. Use `Box::into_raw` to hand it to C.
+    pub fn new(sampler: Sampler) -> Box<IfstatSampler> {
+        Box::new(IfstatSampler {
+            state: Mutex::new(SamplerState {
+                sampler,
+                filter: Vec::new(),
+            }),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SamplerState> {
+        // A panic while holding the lock leaves the state consistent, keep going.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Converts the rates of a sample into a heap array of records, of the interfaces in `filter` in
+/// its order if it isn't empty. Filtered interfaces missing from the sample are left out.
+fn rates_to_c(sample: &Sample, filter: &[String]) -> (*mut IfstatRateV1, usize) {
+    let selected: Vec<_> = match filter.is_empty() {
+        true => sample.per_iface.iter().collect(),
+        false => filter
+            .iter()
+            .filter_map(|iface| sample.per_iface.get_key_value(iface))
+            .collect(),
+    };
+    let records: Box<[IfstatRateV1]> = selected
+        .into_iter()
+        .map(|(iface, s)| IfstatRateV1 {
+            name: to_c_string(iface).into_raw(),
+            rx_bytes: s.rx_bytes,
+            tx_bytes: s.tx_bytes,
+            rx_bytes_per_s: s.rx_bytes_per_s,
+            tx_bytes_per_s: s.tx_bytes_per_s,
+        })
+        .collect();
+    if records.is_empty() {
+        return (ptr::null_mut(), 0);
+    }
+    let len = records.len();
+    (Box::into_raw(records) as *mut IfstatRateV1, len)
+}
+
+/// Creates a sampler of the system statistics. The current counters become the baseline of the
+/// first poll.
+///
+/// Returns null on error, with the `IFSTAT_*` result code stored in `out_error` (which may be
+/// null). The handle must be released with `ifstat_sampler_destroy`.
+///
+/// # Safety
+///
+/// `out_error` must be null or a valid pointer to an `int32_t`.
+#[no_mangle]
+pub unsafe extern "C" fn ifstat_sampler_create(out_error: *mut i32) -> *mut IfstatSampler {
+    match catch_unwind(|| Sampler::new(Duration::from_secs(1))) {
+        Ok(Ok(sampler)) => {
+            set_out(out_error, IFSTAT_OK);
+            Box::into_raw(IfstatSampler::new(sampler))
+        }
+        Ok(Err(e)) => {
+            set_out(out_error, error_code(&e));
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_out(out_error, IFSTAT_ERR_INTERNAL);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Restricts the interfaces returned by `ifstat_sampler_poll_v1` to `names`, which are then
+/// returned in this order. A `count` of 0 removes the filter. Returns an `IFSTAT_*` result code.
+///
+/// # Safety
+///
+/// `sampler` must be a live handle from `ifstat_sampler_create`, `names` must point to `count`
+/// NUL-terminated strings (or may be null if `count` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn ifstat_sampler_set_filter(
+    sampler: *mut IfstatSampler,
+    names: *const *const c_char,
+    count: usize,
+) -> i32 {
+    if sampler.is_null() || (names.is_null() && count > 0) {
+        return IFSTAT_ERR_INVALID_ARGUMENT;
+    }
+    let mut filter = Vec::with_capacity(count);
+    for i in 0..count {
+        let name = unsafe { *names.add(i) };
+        if name.is_null() {
+            return IFSTAT_ERR_INVALID_ARGUMENT;
+        }
+        filter.push(
+            unsafe { CStr::from_ptr(name) }
+                .to_string_lossy()
+                .into_owned(),
+        );
+    }
+    let sampler = unsafe { &*sampler };
+    match catch_unwind(AssertUnwindSafe(|| sampler.lock().filter = filter)) {
+        Ok(()) => IFSTAT_OK,
+        Err(_) => IFSTAT_ERR_INTERNAL,
+    }
+}
+
+/// Takes a new snapshot and returns the rates of all (filtered) interfaces since the previous
+/// poll, or since creation for the first poll.
+///
+/// Length and result code are reported like in `ifstat_get_interfaces_v1`. The array must be
+/// released with `ifstat_free_rates_v1`. On error the baseline is kept, so the next successful
+/// poll covers the whole time since the last successful one.
+///
+/// # Safety
+///
+/// `sampler` must be a live handle from `ifstat_sampler_create`, `out_len` must be a valid
+/// pointer to a `size_t`, `out_error` must be null or a valid pointer to an `int32_t`.
+#[no_mangle]
+pub unsafe extern "C" fn ifstat_sampler_poll_v1(
+    sampler: *mut IfstatSampler,
+    out_len: *mut usize,
+    out_error: *mut i32,
+) -> *mut IfstatRateV1 {
+    if sampler.is_null() || out_len.is_null() {
+        set_out(out_error, IFSTAT_ERR_INVALID_ARGUMENT);
+        return ptr::null_mut();
+    }
+    set_out(out_len, 0);
+
+    let sampler = unsafe { &*sampler };
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut state = sampler.lock();
+        let sample = state
+            .sampler
+            .sample_now()
+            .unwrap_or(Err(IfstatError::Exhausted))?;
+        Ok::<_, IfstatError>(rates_to_c(&sample, &state.filter))
+    }));
+    match result {
+        Ok(Ok((records, len))) => {
+            set_out(out_len, len);
+            set_out(out_error, IFSTAT_OK);
+            records
+        }
+        Ok(Err(e)) => {
+            set_out(out_error, error_code(&e));
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_out(out_error, IFSTAT_ERR_INTERNAL);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases an array returned by `ifstat_sampler_poll_v1`, including all names.
+///
+/// # Safety
+///
+/// `records` must be null or a pointer returned by `ifstat_sampler_poll_v1` together with the
+/// length it reported, and must not have been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ifstat_free_rates_v1(records: *mut IfstatRateV1, len: usize) {
+    if records.is_null() {
+        return;
+    }
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        let records = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(records, len)) };
+        for record in records.iter() {
+            if !record.name.is_null() {
+                drop(unsafe { CString::from_raw(record.name) });
+            }
+        }
+    }));
+}
+
+/// Releases a sampler handle. Null is ignored.
+///
+/// # Safety
+///
+/// `sampler` must be null or a handle from `ifstat_sampler_create` that is not used by any other
+/// thread anymore.
+#[no_mangle]
+pub unsafe extern "C" fn ifstat_sampler_destroy(sampler: *mut IfstatSampler) {
+    if !sampler.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(unsafe { Box::from_raw(sampler) })));
+    }
+}
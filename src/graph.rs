@@ -0,0 +1,128 @@
+// This module provides the scaling and the bars of `--graph`, which draws the rates as
+// horizontal bars per interface (`bars`) or as a chart scrolling down one line per update
+// (`chart`), using only plain lines so it works over SSH without a full-screen interface.
+//
+// Bars are scaled to the link speed of an interface if known, otherwise to the highest rate seen
+// so far, rounded up to a power of two so the scale of the chart rarely changes.
+
+use indexmap::IndexMap;
+
+use crate::net_stats::InterfaceInfo;
+use crate::opts::GraphMode;
+
+/// Lowest full scale in bytes per second, so idle interfaces don't scale up noise.
+pub const MIN_SCALE_BYTES_PER_S: f64 = 1024.0;
+
+/// Characters of bars with a resolution of eighths of a character.
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Scales of the monitored interfaces for `--graph`.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    pub mode: GraphMode,
+    /// Link speeds in bytes per second.
+    link_speeds: IndexMap<String, f64>,
+    /// Highest rates (received or transmitted) seen per interface.
+    peaks: IndexMap<String, f64>,
+    /// Whether the chart header is due, at the start and whenever a scale changed.
+    header_due: bool,
+}
+
+impl Graph {
+    pub fn new(mode: GraphMode) -> Graph {
+        Graph {
+            mode,
+            link_speeds: IndexMap::new(),
+            peaks: IndexMap::new(),
+            header_due: true,
+        }
+    }
+
+    /// Scales the interfaces with a known link speed to that speed.
+    pub fn with_link_speeds(mut self, infos: &IndexMap<String, InterfaceInfo>) -> Graph {
+        self.link_speeds = infos
+            .iter()
+            .filter_map(|(iface, info)| {
+                let speed = info.speed_mbps.filter(|speed| *speed > 0)?;
+                Some((iface.clone(), speed as f64 * 1_000_000.0 / 8.0))
+            })
+            .collect();
+        self
+    }
+
+    /// Returns the full scale of the interface in bytes per second.
+    pub fn scale(&self, iface: &str) -> f64 {
+        if let Some(speed) = self.link_speeds.get(iface) {
+            return *speed;
+        }
+        let peak = self.peaks.get(iface).copied().unwrap_or_default();
+        let mut scale = MIN_SCALE_BYTES_PER_S;
+        while scale < peak {
+            scale *= 2.0;
+        }
+        scale
+    }
+
+    /// Adds the rates (received, transmitted bytes per second) to the highest rates seen,
+    /// making the chart header due if a scale changed.
+    pub fn update(&mut self, rates: &IndexMap<String, (f64, f64)>) {
+        for (iface, (rx, tx)) in rates {
+            let before = self.scale(iface);
+            let peak = self.peaks.entry(iface.clone()).or_default();
+            *peak = peak.max(rx.max(*tx));
+            self.header_due |= self.scale(iface) != before;
+        }
+    }
+
+    /// Returns true if the chart header is due because of the start or a new scale, clearing
+    /// the flag.
+    pub fn take_header_due(&mut self) -> bool {
+        std::mem::take(&mut self.header_due)
+    }
+}
+
+/// Returns a bar of `#` filling `fraction` (0 to 1) of `width` characters. Nonzero fractions
+/// show at least one character.
+pub fn ascii_bar(fraction: f64, width: usize) -> String {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let mut length = (fraction * width as f64).round() as usize;
+    if fraction > 0.0 {
+        length = length.max(1);
+    }
+    "#".repeat(length.min(width))
+}
+
+/// Returns a bar of block characters filling `fraction` (0 to 1) of `width` characters in
+/// eighths, padded with spaces to `width`. Nonzero fractions show at least one eighth.
+pub fn block_bar(fraction: f64, width: usize) -> String {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let mut eighths = (fraction * width as f64 * 8.0).round() as usize;
+    if fraction > 0.0 {
+        eighths = eighths.max(1);
+    }
+    let mut bar = "█".repeat(eighths / 8);
+    let partial = EIGHTHS[eighths % 8];
+    if partial != ' ' {
+        bar.push(partial);
+    }
+    let padding = width.saturating_sub(bar.chars().count());
+    bar + &" ".repeat(padding)
+}
+
+/// Returns the width of the terminal: of stdout if it is a terminal, else `$COLUMNS`, else 80.
+pub fn terminal_width() -> usize {
+    #[cfg(unix)]
+    {
+        // SAFETY: TIOCGWINSZ only writes the winsize struct passed to it
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+        if result == 0 && size.ws_col > 0 {
+            return size.ws_col as usize;
+        }
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|columns| *columns > 0)
+        .unwrap_or(80)
+}
@@ -0,0 +1,77 @@
+// This module orders interfaces by their topology for `--group`: bridge, bond and team ports
+// below their master, and VLAN and macvlan interfaces below their lower device, so the traffic
+// of stacked interfaces is shown next to the uplink it ends up on.
+
+use indexmap::IndexMap;
+
+use crate::net_stats::InterfaceInfo;
+use crate::output::shorten_name;
+
+/// Returns the device an interface is grouped under: its master, or else its lower device.
+pub fn parent_of(info: &InterfaceInfo) -> Option<&str> {
+    info.master.as_deref().or(info.link.as_deref())
+}
+
+/// Orders `interfaces` so every interface follows its parent, returning each with its depth.
+///
+/// Interfaces whose parent is not among `interfaces` or has no metadata are roots, which keep
+/// their order. Children follow in their order in `interfaces`.
+pub fn group_interfaces(
+    interfaces: &[String],
+    infos: &IndexMap<String, InterfaceInfo>,
+) -> Vec<(String, usize)> {
+    let parent = |iface: &String| {
+        infos
+            .get(iface)
+            .and_then(parent_of)
+            .filter(|parent| *parent != iface && interfaces.iter().any(|i| i == parent))
+    };
+
+    let mut grouped = Vec::with_capacity(interfaces.len());
+    // Depth-first from the roots; a stack of (interface, depth) in reverse order
+    let mut stack: Vec<(&String, usize)> = interfaces
+        .iter()
+        .rev()
+        .filter(|iface| parent(iface).is_none())
+        .map(|iface| (iface, 0))
+        .collect();
+    while let Some((iface, depth)) = stack.pop() {
+        if grouped.iter().any(|(name, _)| name == iface) {
+            continue;
+        }
+        grouped.push((iface.clone(), depth));
+        stack.extend(
+            interfaces
+                .iter()
+                .rev()
+                .filter(|child| parent(child) == Some(iface.as_str()))
+                .map(|child| (child, depth + 1)),
+        );
+    }
+    // Interfaces in a loop of parents (which the kernel doesn't allow) are appended as roots
+    for iface in interfaces {
+        if !grouped.iter().any(|(name, _)| name == iface) {
+            grouped.push((iface.clone(), 0));
+        }
+    }
+    grouped
+}
+
+/// Returns the table header labels of grouped interfaces: their alias if they have one, and
+/// children prefixed with `└`.
+///
+/// Roots without alias have no label and are shown by name.
+pub fn group_labels(
+    grouped: &[(String, usize)],
+    aliases: &IndexMap<String, String>,
+) -> IndexMap<String, String> {
+    grouped
+        .iter()
+        .filter(|(iface, depth)| *depth > 0 || aliases.contains_key(iface))
+        .map(|(iface, depth)| {
+            let name = shorten_name(aliases.get(iface).unwrap_or(iface));
+            let prefix = if *depth > 0 { "└" } else { "" };
+            (iface.clone(), format!("{}{}", prefix, name))
+        })
+        .collect()
+}
@@ -1,3 +1,20 @@
+pub mod accounting;
+pub mod analyze;
+pub mod bond;
+pub mod config;
+pub mod error;
+pub mod extras;
+pub mod ffi;
+pub mod graph;
+pub mod hierarchy;
 pub mod net_stats;
 pub mod opts;
 pub mod output;
+pub mod peaks;
+#[cfg(feature = "python")]
+mod python;
+pub mod quota;
+pub mod record;
+pub mod sampler;
+pub mod select;
+pub mod smoothing;
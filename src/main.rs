@@ -1,78 +1,850 @@
-mod net_stats;
-mod opts;
-mod output;
+use clap::{CommandFactory, FromArgMatches};
+use ifstat_rs::accounting::{default_database_path, read_boot_id, Database};
+use ifstat_rs::analyze::{read_snapshot_files, snapshot_intervals};
+use ifstat_rs::bond::find_bonds;
+use ifstat_rs::config::apply_config;
+use ifstat_rs::error::IfstatError;
+use ifstat_rs::extras::{ExtraRates, ExtraSampler};
+use ifstat_rs::graph::{terminal_width, Graph};
+use ifstat_rs::hierarchy::{group_interfaces, group_labels};
+use ifstat_rs::net_stats::{
+    get_device_string_to_name_map, get_interface_info, InterfaceInfo, ProcNetDevProvider,
+    ReplayProvider, StatsProvider, SysfsProvider, SystemProvider,
+};
+use ifstat_rs::opts::{
+    AccountingPeriod, AnalyzeOpts, Command, DaemonOpts, GraphMode, Opts, OutputFormat,
+    ProviderKind, QueryOpts,
+};
+use ifstat_rs::output::{
+    filter_zero_counters, format_quota_status, print_accounting_csv, print_accounting_table,
+    print_bond_table, print_chart_header, print_chart_line, print_csv_header, print_graph_bars,
+    print_headers_with_labels, print_interface_info_json, print_net_devices, print_peak_table,
+    print_quotas_csv, print_rates_csv, print_rates_json_with_extra, print_rates_with_columns,
+    print_tc_table, ColumnGroup, HeaderRepeat,
+};
+use ifstat_rs::quota::QuotaStatus;
+use ifstat_rs::record::{read_recording, Recorder};
+use ifstat_rs::sampler::{Sample, Sampler};
+use ifstat_rs::select::InterfaceSelector;
+use ifstat_rs::smoothing::{Smoother, Smoothing};
+use indexmap::IndexMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use clap::Parser;
-use net_stats::get_net_dev_stats;
-use opts::Opts;
-use output::{print_headers, print_net_devices, print_stats};
-use tokio::time::{interval, sleep, Duration};
+/// Determines which interfaces to monitor based on the options and the available interfaces.
+///
+/// `infos` is the metadata of the interfaces for live statistics, needed by the type, state
+/// and driver filters.
+fn select_interfaces(
+    opts: &Opts,
+    stats: &IndexMap<String, (u64, u64)>,
+    infos: Option<&IndexMap<String, InterfaceInfo>>,
+) -> Result<Vec<String>, IfstatError> {
+    InterfaceSelector::from_opts(opts).select(stats, infos)
+}
+
+/// The monitored interfaces in display order, with their table header labels and the aliases
+/// carried by the JSON and CSV output.
+struct Monitored {
+    interfaces: Vec<String>,
+    headers: IndexMap<String, String>,
+    aliases: IndexMap<String, String>,
+}
+
+/// Returns the aliases of the interfaces: the friendly names of this system for live statistics
+/// (e.g. `ifalias` on Linux), overridden by `--label`.
+fn interface_aliases(opts: &Opts, live: bool) -> IndexMap<String, String> {
+    let mut aliases = match live {
+        true => get_device_string_to_name_map(),
+        false => IndexMap::new(),
+    };
+    aliases.extend(opts.labels.iter().cloned());
+    aliases
+}
+
+/// Orders the interfaces by topology for `--group` and labels them with their aliases.
+///
+/// Without `--group` the order is kept.
+fn arrange_interfaces(
+    opts: &Opts,
+    interfaces: Vec<String>,
+    infos: Option<&IndexMap<String, InterfaceInfo>>,
+    aliases: IndexMap<String, String>,
+) -> Result<Monitored, IfstatError> {
+    let grouped = match (opts.group, infos) {
+        (false, _) => interfaces.into_iter().map(|iface| (iface, 0)).collect(),
+        (true, Some(infos)) => group_interfaces(&interfaces, infos),
+        (true, None) => {
+            return Err(IfstatError::Unsupported(
+                "Grouping interfaces needs interface metadata, which is only available for live statistics on Linux",
+            ))
+        }
+    };
+    Ok(Monitored {
+        headers: group_labels(&grouped, &aliases),
+        interfaces: grouped.into_iter().map(|(iface, _)| iface).collect(),
+        aliases,
+    })
+}
+
+/// Prints the initial headers for the selected output format.
+fn print_initial_headers(
+    opts: &Opts,
+    monitored: &Monitored,
+    stats: &IndexMap<String, (u64, u64)>,
+    extra_columns: &[ColumnGroup],
+) {
+    match opts.format {
+        // The chart header is printed with the first line, the bars have none
+        OutputFormat::Table if opts.graph.is_some() => {}
+        OutputFormat::Table => print_headers_with_labels(
+            &monitored.interfaces,
+            &monitored.headers,
+            &mut std::io::stdout(),
+            opts.hide_zero_counters,
+            stats,
+            extra_columns,
+        )
+        .unwrap(),
+        OutputFormat::Csv => print_csv_header(&mut std::io::stdout()).unwrap(),
+        OutputFormat::Json => {}
+    }
+}
+
+/// Returns when to repeat the table headers according to `--header-interval`, `-n` and
+/// `--header-on-resize`.
+fn header_repeat(opts: &Opts) -> HeaderRepeat {
+    let interval = (!opts.no_header_repeat).then_some(opts.header_interval as usize);
+    let headers = HeaderRepeat::new(interval);
+    match opts.header_on_resize {
+        true => headers.with_resize_flag(watch_resize()),
+        false => headers,
+    }
+}
+
+/// Returns a flag that is set whenever the terminal is resized.
+#[cfg(unix)]
+fn watch_resize() -> Arc<AtomicBool> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let resized = Arc::new(AtomicBool::new(false));
+    match signal(SignalKind::window_change()) {
+        Ok(mut window_change) => {
+            let resized = resized.clone();
+            tokio::spawn(async move {
+                while window_change.recv().await.is_some() {
+                    resized.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+        Err(e) => eprintln!("Error watching terminal resizes: {}", e),
+    }
+    resized
+}
+
+/// Returns a flag that is set whenever the terminal is resized (Unix only).
+#[cfg(not(unix))]
+fn watch_resize() -> Arc<AtomicBool> {
+    eprintln!("--header-on-resize is only supported on Unix");
+    Arc::new(AtomicBool::new(false))
+}
+
+/// Returns a flag that is set when the peaks of `--peaks` should be reset: on SIGUSR1 (Unix) or
+/// when Enter is pressed in the terminal.
+fn watch_peak_reset() -> Arc<AtomicBool> {
+    use std::io::{BufRead, IsTerminal};
+
+    let reset = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::user_defined1()) {
+            Ok(mut user_defined1) => {
+                let reset = reset.clone();
+                tokio::spawn(async move {
+                    while user_defined1.recv().await.is_some() {
+                        reset.store(true, Ordering::Relaxed);
+                    }
+                });
+            }
+            Err(e) => eprintln!("Error watching SIGUSR1: {}", e),
+        }
+    }
+    if std::io::stdin().is_terminal() {
+        // A plain thread, as a blocking read would keep the runtime from shutting down
+        let reset = reset.clone();
+        std::thread::spawn(move || {
+            for _ in std::io::stdin().lock().lines().map_while(Result::ok) {
+                reset.store(true, Ordering::Relaxed);
+            }
+        });
+    }
+    reset
+}
+
+/// Resets the peaks of `--peaks` if `reset` is set, first printing them below the table.
+fn reset_peaks(
+    opts: &Opts,
+    extras: &mut ExtraSampler,
+    reset: &AtomicBool,
+    headers: &mut HeaderRepeat,
+) {
+    if !reset.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    if let Some(peaks) = extras.reset_peaks() {
+        if opts.format == OutputFormat::Table && !opts.single_line {
+            print_peak_table(&peaks, &mut std::io::stdout()).unwrap();
+            headers.force();
+        }
+    }
+}
+
+/// Prints the peaks of `--peaks` with the time each occurred at the end of a table.
+fn print_final_peaks(opts: &Opts, extras: &mut ExtraSampler) {
+    if opts.format != OutputFormat::Table || opts.single_line {
+        return;
+    }
+    if let Some(peaks) = extras.reset_peaks() {
+        print_peak_table(&peaks, &mut std::io::stdout()).unwrap();
+    }
+}
+
+/// Prints one sample, repeating the table headers as decided by `headers`.
+///
+/// With `--single-line`, the table line replaces the previous one. With `--graph`, `graph`
+/// draws the rates instead of the table.
+///
+/// `extra` holds the rates of `--proto`, `--softnet`, `--wireless`, `--qdisc` and `--bonds` over
+/// the same interval, with the matching table columns.
+fn print_update(
+    opts: &Opts,
+    monitored: &Monitored,
+    sample: &Sample,
+    extra: (&[ColumnGroup], &ExtraRates),
+    headers: &mut HeaderRepeat,
+    graph: Option<&mut Graph>,
+) {
+    let counters = sample.counters();
+    let interfaces = if opts.hide_zero_counters {
+        filter_zero_counters(&counters, &monitored.interfaces)
+    } else {
+        monitored.interfaces.clone()
+    };
+    let rates = sample.rates(&interfaces);
+    let time = Some(sample.at.as_secs_f64());
+    let mut stdout = std::io::stdout();
+    let (extra_columns, extra_rates) = extra;
+
+    if let Some(graph) = graph {
+        print_graph(monitored, &rates, headers, graph, &mut stdout);
+        return;
+    }
+    match opts.format {
+        OutputFormat::Table if opts.single_line => {
+            let mut line = Vec::new();
+            print_rates_with_columns(&rates, &extra_rates.column_values(), &mut line).unwrap();
+            let line = String::from_utf8_lossy(&line);
+            write!(stdout, "\r{}", line.trim_end_matches('\n')).unwrap();
+            stdout.flush().unwrap();
+        }
+        OutputFormat::Table => {
+            if headers.due() {
+                print_headers_with_labels(
+                    &monitored.interfaces,
+                    &monitored.headers,
+                    &mut stdout,
+                    opts.hide_zero_counters,
+                    &counters,
+                    extra_columns,
+                )
+                .unwrap();
+            }
+            print_rates_with_columns(&rates, &extra_rates.column_values(), &mut stdout).unwrap();
+            headers.line_printed();
+            if let Some(tc) = &extra_rates.tc {
+                print_tc_table(tc, &mut stdout).unwrap();
+            }
+            if let Some(bonds) = &extra_rates.bonds {
+                print_bond_table(bonds, &mut stdout).unwrap();
+            }
+            let has_tc = extra_rates.tc.as_ref().is_some_and(|tc| !tc.is_empty());
+            let has_bonds = extra_rates.bonds.as_ref().is_some_and(|b| !b.is_empty());
+            if has_tc || has_bonds {
+                // Label the next line again below the sub-tables
+                headers.force();
+            }
+        }
+        OutputFormat::Json => print_rates_json_with_extra(
+            time,
+            &rates,
+            &monitored.aliases,
+            extra_rates.json(),
+            &mut stdout,
+        )
+        .unwrap(),
+        OutputFormat::Csv => {
+            print_rates_csv(time, &rates, &monitored.aliases, &mut stdout).unwrap()
+        }
+    }
+}
+
+/// Prints the rates of one sample as bars or as a chart line of `--graph`, sized to the
+/// current terminal width. The chart header is repeated like the table headers, and whenever
+/// a scale changed.
+fn print_graph(
+    monitored: &Monitored,
+    rates: &IndexMap<String, (f64, f64)>,
+    headers: &mut HeaderRepeat,
+    graph: &mut Graph,
+    writer: &mut dyn Write,
+) {
+    let width = terminal_width();
+    graph.update(rates);
+    match graph.mode {
+        GraphMode::Bars => {
+            print_graph_bars(rates, &monitored.headers, graph, width, writer).unwrap()
+        }
+        GraphMode::Chart => {
+            // Both are called so each clears its flag
+            let due = headers.due();
+            if graph.take_header_due() || due {
+                let interfaces: Vec<String> = rates.keys().cloned().collect();
+                print_chart_header(&interfaces, &monitored.headers, graph, width, writer).unwrap();
+            }
+            print_chart_line(rates, graph, width, writer).unwrap();
+            headers.line_printed();
+        }
+    }
+}
+
+/// Returns the smoother for `--average` or `--ewma`, if either is given.
+fn create_smoother(opts: &Opts) -> Option<Smoother> {
+    match (opts.average, opts.ewma) {
+        (Some(samples), _) => Some(Smoother::new(Smoothing::MovingAverage(samples as usize))),
+        (None, Some(alpha)) => Some(Smoother::new(Smoothing::Ewma(alpha))),
+        (None, None) => None,
+    }
+}
+
+/// Ends the line rewritten by `--single-line`.
+fn end_single_line(opts: &Opts) {
+    if opts.single_line && opts.format == OutputFormat::Table {
+        println!();
+    }
+}
+
+/// Prints rates computed from saved `/proc/net/dev` dumps.
+fn run_analyze(opts: &Opts, analyze_opts: &AnalyzeOpts) -> Result<(), IfstatError> {
+    let snapshots = read_snapshot_files(&analyze_opts.files)?;
+    if snapshots.len() < 2 {
+        return Err(IfstatError::InvalidData(format!(
+            "At least two snapshots are needed, found {}",
+            snapshots.len()
+        )));
+    }
+    let intervals = snapshot_intervals(&snapshots, analyze_opts.interval)?;
+
+    let monitor_interfaces = select_interfaces(opts, &snapshots[0].stats, None)?;
+    let aliases = interface_aliases(opts, false);
+    let monitored = arrange_interfaces(opts, monitor_interfaces, None, aliases)?;
+    let mut extras = match opts.show_raw {
+        true => ExtraSampler::default().with_raw(&monitored.interfaces),
+        false => ExtraSampler::default(),
+    };
+    if opts.peaks {
+        extras = extras.with_peaks(&monitored.interfaces, false)?;
+    }
+    let extra_columns = extras.column_groups();
+    print_initial_headers(opts, &monitored, &snapshots[0].stats, &extra_columns);
+
+    let mut headers = header_repeat(opts);
+    let mut smoother = create_smoother(opts);
+    // Recorded snapshots have no link speeds, so the bars scale to the rates seen
+    let mut graph = opts.graph.map(Graph::new);
+    let mut elapsed = 0.0;
+    for (pair, interval_secs) in snapshots.windows(2).zip(intervals) {
+        elapsed += interval_secs;
+        // Report file timestamps if present, otherwise the time since the first snapshot
+        let time = match analyze_opts.interval {
+            Some(_) => elapsed,
+            None => pair[1].timestamp.unwrap_or(elapsed),
+        };
+        let mut sample = Sample::between(
+            &pair[0].stats,
+            &pair[1].stats,
+            Duration::from_secs_f64(time),
+            Duration::from_secs_f64(interval_secs),
+        );
+        let extra_rates = extras.sample(&sample);
+        if let Some(smoother) = smoother.as_mut() {
+            sample = smoother.smooth(&sample);
+        }
+        print_update(
+            opts,
+            &monitored,
+            &sample,
+            (&extra_columns, &extra_rates),
+            &mut headers,
+            graph.as_mut(),
+        );
+    }
+    end_single_line(opts);
+    print_final_peaks(opts, &mut extras);
+    Ok(())
+}
+
+/// Returns the accounting database given with `--db`, or else the default one.
+fn database_path(db: &Option<PathBuf>) -> Result<PathBuf, IfstatError> {
+    match db {
+        Some(path) => Ok(path.clone()),
+        None => default_database_path().ok_or_else(|| {
+            IfstatError::InvalidData("No default accounting database, use --db".to_string())
+        }),
+    }
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+fn epoch_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+/// Waits for Ctrl-C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Adds the traffic of the selected interfaces since the last update to the database.
+fn account_traffic(
+    provider: &mut dyn StatsProvider,
+    selector: &InterfaceSelector,
+    database: &mut Database,
+    now: u64,
+) -> Result<(), IfstatError> {
+    let stats = provider.get_net_dev_stats()?;
+    let infos = match selector.needs_metadata() {
+        true => Some(get_interface_info()?),
+        false => None,
+    };
+    // Interfaces given with -i may not exist (yet), e.g. a modem that is not connected
+    let counters = selector
+        .select(&stats, infos.as_ref())?
+        .into_iter()
+        .filter_map(|iface| Some((iface.clone(), *stats.get(&iface)?)))
+        .collect();
+    database.update(&counters, now, read_boot_id().as_deref());
+    Ok(())
+}
+
+/// Reports a quota that reached `threshold` percent on stdout and runs `--quota-command`.
+fn report_quota(opts: &Opts, status: &QuotaStatus, threshold: u32) {
+    match opts.format {
+        OutputFormat::Json => {
+            let mut event = serde_json::json!({ "event": "quota", "threshold": threshold });
+            if let (Some(event), serde_json::Value::Object(status)) =
+                (event.as_object_mut(), serde_json::json!(status))
+            {
+                event.extend(status);
+            }
+            println!("{}", event);
+        }
+        _ => println!(
+            "Quota reached {}%: {}",
+            threshold,
+            format_quota_status(status)
+        ),
+    }
+
+    let Some(command) = &opts.quota_command else {
+        return;
+    };
+    let mut shell = match cfg!(windows) {
+        true => tokio::process::Command::new("cmd"),
+        false => tokio::process::Command::new("sh"),
+    };
+    shell
+        .arg(if cfg!(windows) { "/C" } else { "-c" })
+        .arg(command)
+        .env("IFSTAT_INTERFACE", &status.interface)
+        .env("IFSTAT_THRESHOLD", threshold.to_string())
+        .env("IFSTAT_USED_BYTES", status.used_bytes.to_string())
+        .env("IFSTAT_LIMIT_BYTES", status.limit_bytes.to_string());
+    match shell.spawn() {
+        // Wait in the background, so slow commands don't delay the accounting
+        Ok(mut child) => {
+            tokio::spawn(async move { child.wait().await });
+        }
+        Err(e) => eprintln!("Error running quota command: {}", e),
+    }
+}
+
+/// Accounts the traffic of the selected interfaces in the database every interval until stopped,
+/// and once more when stopped. Quotas reaching a threshold are reported after each update.
+async fn run_daemon(opts: &Opts, daemon_opts: &DaemonOpts) -> Result<(), IfstatError> {
+    if opts.replay.is_some() {
+        return Err(IfstatError::InvalidData(
+            "The daemon accounts live statistics, it can't replay a recording".to_string(),
+        ));
+    }
+    let path = database_path(&daemon_opts.db)?;
+    let mut database = Database::read(&path)?;
+    let mut provider = create_provider(opts)?;
+    let selector = InterfaceSelector::from_opts(opts);
+
+    let mut ticks = tokio::time::interval(Duration::from_secs_f64(daemon_opts.interval));
+    let mut shutdown = std::pin::pin!(shutdown_signal());
+    loop {
+        let stopping = tokio::select! {
+            _ = ticks.tick() => false,
+            _ = &mut shutdown => true,
+        };
+        let now = epoch_now();
+        if let Err(e) = account_traffic(&mut *provider, &selector, &mut database, now) {
+            eprintln!("Error reading network statistics: {}", e);
+        }
+        for quota in &opts.quotas {
+            if let Some((status, threshold)) = quota.check(&mut database, now) {
+                report_quota(opts, &status, threshold);
+            }
+        }
+        database.write(&path)?;
+        if stopping {
+            return Ok(());
+        }
+    }
+}
+
+/// Prints the traffic accounted by the daemon.
+fn run_query(opts: &Opts, query_opts: &QueryOpts) -> Result<(), IfstatError> {
+    let path = database_path(&query_opts.db)?;
+    if !path.exists() {
+        return Err(IfstatError::InvalidData(format!(
+            "No accounting database at {}, start `ifstat-rs daemon` first",
+            path.display()
+        )));
+    }
+    let database = Database::read(&path)?;
+    let now = epoch_now();
+    let quotas: Vec<QuotaStatus> = opts
+        .quotas
+        .iter()
+        .map(|quota| quota.status(database.interfaces.get(&quota.interface), now))
+        .collect();
+    if query_opts.show_quotas {
+        if quotas.is_empty() {
+            return Err(IfstatError::InvalidData(
+                "No quotas given, use --quota".to_string(),
+            ));
+        }
+        let mut stdout = std::io::stdout();
+        match opts.format {
+            OutputFormat::Table => {
+                for status in &quotas {
+                    writeln!(stdout, "{}", format_quota_status(status))?;
+                }
+            }
+            OutputFormat::Json => {
+                serde_json::to_writer(&mut stdout, &quotas)
+                    .map_err(|e| IfstatError::Io(e.into()))?;
+                writeln!(stdout)?;
+            }
+            OutputFormat::Csv => print_quotas_csv(&quotas, &mut stdout)?,
+        }
+        return Ok(());
+    }
+    let interfaces: Vec<String> = match InterfaceSelector::from_opts(opts).names {
+        Some(names) => names,
+        None => database.interfaces.keys().cloned().collect(),
+    };
+    let rows = interfaces
+        .into_iter()
+        .filter_map(|iface| {
+            let rows = database
+                .interfaces
+                .get(&iface)?
+                .rows(query_opts.period, now);
+            Some((iface, rows))
+        })
+        .collect();
+
+    let mut stdout = std::io::stdout();
+    match opts.format {
+        OutputFormat::Table => {
+            print_accounting_table(&rows, &mut stdout)?;
+            if query_opts.period == AccountingPeriod::Summary {
+                for status in &quotas {
+                    writeln!(stdout, "{}", format_quota_status(status))?;
+                }
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer(&mut stdout, &rows).map_err(|e| IfstatError::Io(e.into()))?;
+            writeln!(stdout)?;
+        }
+        OutputFormat::Csv => print_accounting_csv(&rows, &mut stdout)?,
+    }
+    Ok(())
+}
+
+/// Creates the statistics provider selected by the options.
+fn create_provider(opts: &Opts) -> Result<Box<dyn StatsProvider>, IfstatError> {
+    if let Some(path) = &opts.replay {
+        let snapshots = read_recording(path)?;
+        return Ok(Box::new(ReplayProvider::new(snapshots)));
+    }
+
+    Ok(match (opts.provider, &opts.provider_path) {
+        (ProviderKind::Proc, Some(path)) => Box::new(ProcNetDevProvider::new(path)),
+        (ProviderKind::Sysfs, Some(path)) => Box::new(SysfsProvider::new(path)),
+        (_, Some(_)) => {
+            return Err(IfstatError::Unsupported(
+                "--provider-path is only supported with --provider proc or sysfs",
+            ))
+        }
+        (ProviderKind::System, None) => Box::new(SystemProvider),
+        (ProviderKind::Proc, None) => Box::new(ProcNetDevProvider::default()),
+        (ProviderKind::Sysfs, None) => Box::new(SysfsProvider::default()),
+        #[cfg(target_os = "linux")]
+        (ProviderKind::Netlink, None) => Box::new(ifstat_rs::net_stats::NetlinkProvider),
+        #[cfg(not(target_os = "linux"))]
+        (ProviderKind::Netlink, None) => {
+            return Err(IfstatError::Unsupported(
+                "The netlink provider is only available on Linux",
+            ))
+        }
+    })
+}
+
+/// Creates the sampler for live statistics or a replayed recording.
+fn create_sampler(opts: &Opts) -> Result<Sampler, IfstatError> {
+    let provider = create_provider(opts)?;
+
+    // Use first_measurement delay if provided, otherwise use delay
+    let delay = Duration::from_secs_f64(opts.delay);
+    let first_delay = opts
+        .first_measurement
+        .map(Duration::from_secs_f64)
+        .unwrap_or(delay);
+    Ok(Sampler::with_provider(provider, delay)?
+        .with_first_delay(first_delay)
+        .with_speed(opts.speed))
+}
 
 #[tokio::main]
 async fn main() {
-    // Parse command-line options
-    let opts: Opts = Opts::parse();
+    // Show warnings, e.g. about skipped malformed lines, unless configured otherwise via RUST_LOG
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    // Parse command-line options, filling in the others from the configuration file
+    let matches = Opts::command().get_matches();
+    let mut opts = Opts::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let Err(e) = apply_config(&mut opts, &matches) {
+        eprintln!("Error reading configuration: {}", e);
+        std::process::exit(1);
+    }
 
     if opts.list_interfaces {
         // List interface names and exit.
-        match get_net_dev_stats() {
-            Ok(stats) => print_net_devices(&stats),
+        let infos = get_interface_info().unwrap_or_default();
+        match create_provider(&opts).and_then(|mut provider| provider.get_net_dev_stats()) {
+            Ok(stats) if opts.format == OutputFormat::Json => {
+                // Order by topology; the master and link members carry the hierarchy
+                let stats = match opts.group {
+                    true => {
+                        let interfaces: Vec<String> = stats.keys().cloned().collect();
+                        group_interfaces(&interfaces, &infos)
+                            .into_iter()
+                            .filter_map(|(iface, _)| Some((iface.clone(), *stats.get(&iface)?)))
+                            .collect()
+                    }
+                    false => stats,
+                };
+                print_interface_info_json(&stats, &infos, &mut std::io::stdout()).unwrap();
+            }
+            Ok(stats) => print_net_devices(&stats, &infos, opts.group),
             Err(e) => eprintln!("Error listing network interfaces: {}", e),
         }
         return;
     }
 
-    let interfaces: Vec<String> = opts
-        .interfaces
-        .clone()
-        .unwrap_or_default()
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .collect();
+    if opts.single_line && (opts.format != OutputFormat::Table || opts.qdisc || opts.bonds) {
+        eprintln!(
+            "--single-line is only supported with --format table, without --qdisc and --bonds"
+        );
+        std::process::exit(1);
+    }
 
-    // Get initial network statistics
-    let mut previous_stats = get_net_dev_stats().expect("Failed reading network interface stats.");
+    let has_extras = opts.proto
+        || opts.softnet
+        || opts.wireless
+        || opts.qdisc
+        || opts.bonds
+        || opts.show_raw
+        || opts.peaks;
+    if opts.graph.is_some()
+        && (opts.format != OutputFormat::Table || opts.single_line || has_extras)
+    {
+        eprintln!("--graph is only supported with --format table, without --single-line, --proto, --softnet, --wireless, --qdisc, --bonds, --show-raw and --peaks");
+        std::process::exit(1);
+    }
 
-    // Determine which interfaces to monitor
-    let monitor_interfaces: Vec<String> = if opts.monitor_all {
-        previous_stats.keys().cloned().collect()
-    } else if opts.interfaces.is_some() {
-        interfaces
-    } else if opts.monitor_loopback {
-        previous_stats.keys().cloned().collect()
-    } else {
-        previous_stats
-            .keys()
-            .filter(|iface| !iface.starts_with("lo"))
-            .cloned()
-            .collect()
+    if opts.show_raw && opts.average.is_none() && opts.ewma.is_none() {
+        eprintln!("--show-raw needs --average or --ewma");
+        std::process::exit(1);
+    }
+
+    if let Some(Command::Analyze(analyze_opts)) = &opts.command {
+        if let Err(e) = run_analyze(&opts, analyze_opts) {
+            eprintln!("Error analyzing snapshots: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Daemon(daemon_opts)) = &opts.command {
+        if let Err(e) = run_daemon(&opts, daemon_opts).await {
+            eprintln!("Error accounting traffic: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Query(query_opts)) = &opts.command {
+        if let Err(e) = run_query(&opts, query_opts) {
+            eprintln!("Error querying traffic: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut recorder = match &opts.record {
+        Some(path) => match Recorder::create(path) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                eprintln!("Error opening recording {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
     };
 
-    // Print headers based on specified or available interfaces
-    let header_repeat_interval = 20;
-    print_headers(
-        &monitor_interfaces,
-        &mut std::io::stdout(),
-        opts.hide_zero_counters,
-        &previous_stats,
-    )
-    .unwrap();
+    // Get initial network statistics
+    let sampler = match create_sampler(&opts) {
+        Ok(sampler) => sampler,
+        Err(e) => {
+            eprintln!("Failed reading network interface stats: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let baseline = sampler.baseline().clone();
+    if let Some(recorder) = recorder.as_mut() {
+        if let Err(e) = recorder.record(&baseline) {
+            eprintln!("Error writing recording: {}", e);
+        }
+    }
 
-    // Use first_measurement delay if provided, otherwise use delay
-    let first_delay = opts.first_measurement.unwrap_or(opts.delay);
+    // Metadata of the interfaces of this system, not of a replayed recording or a fixture
+    let system = opts.replay.is_none() && opts.provider_path.is_none();
+    let infos = match system {
+        true => get_interface_info().ok(),
+        false => None,
+    };
 
-    // Ensure that regular_delay is non-zero, default to 1 ms
-    let regular_delay = if opts.delay == 0.0 { 0.001 } else { opts.delay };
+    // Determine which interfaces to monitor
+    let aliases = interface_aliases(&opts, system);
+    let monitored = select_interfaces(&opts, &baseline, infos.as_ref())
+        .and_then(|interfaces| arrange_interfaces(&opts, interfaces, infos.as_ref(), aliases));
+    let monitored = match monitored {
+        Ok(monitored) => monitored,
+        Err(e) => {
+            eprintln!("Error selecting interfaces: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let monitor_interfaces = &monitored.interfaces;
 
-    if first_delay != regular_delay {
-        // Sleep for the first delay
-        sleep(Duration::from_secs_f64(first_delay)).await;
+    // Protocol, softnet, wireless and qdisc counters are sampled alongside the interface counters
+    let extras = ExtraSampler::new(opts.proto, opts.softnet)
+        .map(|extras| match opts.bonds {
+            true => {
+                let infos = infos.clone().unwrap_or_default();
+                let sysfs = Path::new("/sys/class/net");
+                extras.with_bonds(find_bonds(monitor_interfaces, &infos, sysfs))
+            }
+            false => extras,
+        })
+        .and_then(|extras| match opts.wireless {
+            true => extras.with_wireless(monitor_interfaces),
+            false => Ok(extras),
+        })
+        .and_then(|extras| match opts.qdisc {
+            true => extras.with_tc(monitor_interfaces),
+            false => Ok(extras),
+        })
+        .map(|extras| match opts.show_raw {
+            true => extras.with_raw(monitor_interfaces),
+            false => extras,
+        })
+        .and_then(|extras| match opts.peaks {
+            // Packet counters are read from the system, so they would not match the byte counters
+            // of another provider, a fixture or a recording
+            true => {
+                let packets = system && opts.provider == ProviderKind::System;
+                if !packets && opts.replay.is_none() {
+                    eprintln!("--peaks only tracks packet rates with --provider system");
+                }
+                extras.with_peaks(monitor_interfaces, packets)
+            }
+            false => Ok(extras),
+        });
+    let mut extras = match extras {
+        Ok(extras) => extras,
+        Err(e) => {
+            eprintln!("Failed reading system stats: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if !extras.is_empty() && opts.format == OutputFormat::Csv {
+        eprintln!("--proto, --softnet, --wireless, --qdisc, --bonds, --show-raw and --peaks are not supported with --format csv");
+        std::process::exit(1);
     }
+    let extra_columns = extras.column_groups();
+
+    // Print headers based on specified or available interfaces
+    print_initial_headers(&opts, &monitored, &baseline, &extra_columns);
 
     let mut updates = 0;
-    let mut lines_since_last_header = 0;
-    let mut interval = interval(Duration::from_secs_f64(regular_delay));
-    interval.tick().await; // First tick fires immediately.
+    let mut headers = header_repeat(&opts);
+    let mut smoother = create_smoother(&opts);
+    let mut graph = opts
+        .graph
+        .map(|mode| Graph::new(mode).with_link_speeds(&infos.clone().unwrap_or_default()));
+    let peak_reset = match opts.peaks {
+        true => watch_peak_reset(),
+        false => Arc::new(AtomicBool::new(false)),
+    };
+    let mut samples = sampler.into_stream();
+    // Stopping with Ctrl-C still ends the single line and prints the peaks
+    let mut shutdown = std::pin::pin!(shutdown_signal());
 
     loop {
         // Check if the number of updates has reached the specified count
@@ -82,40 +854,41 @@ async fn main() {
             }
         }
 
-        // Get current network statistics
-        match get_net_dev_stats() {
-            Ok(current_stats) => {
-                // Print headers again if enough lines have been printed
-                if lines_since_last_header >= header_repeat_interval {
-                    print_headers(
-                        &monitor_interfaces,
-                        &mut std::io::stdout(),
-                        opts.hide_zero_counters,
-                        &current_stats,
-                    )
-                    .unwrap();
-                    lines_since_last_header = 0;
+        // Wait for the next sample
+        let next = tokio::select! {
+            next = samples.next() => next,
+            _ = &mut shutdown => break,
+        };
+        match next {
+            Some(Ok(sample)) => {
+                if let Some(recorder) = recorder.as_mut() {
+                    if let Err(e) = recorder.record(&sample.counters()) {
+                        eprintln!("Error writing recording: {}", e);
+                    }
                 }
 
-                // Print stats for the monitored interfaces
-                print_stats(
-                    &previous_stats,
-                    &current_stats,
-                    &monitor_interfaces,
-                    &mut std::io::stdout(),
-                    opts.hide_zero_counters,
-                )
-                .unwrap();
-                previous_stats = current_stats;
+                reset_peaks(&opts, &mut extras, &peak_reset, &mut headers);
+                let extra_rates = extras.sample(&sample);
+                let sample = match smoother.as_mut() {
+                    Some(smoother) => smoother.smooth(&sample),
+                    None => sample,
+                };
 
-                lines_since_last_header += 1;
+                print_update(
+                    &opts,
+                    &monitored,
+                    &sample,
+                    (&extra_columns, &extra_rates),
+                    &mut headers,
+                    graph.as_mut(),
+                );
             }
-            Err(e) => eprintln!("Error reading network statistics: {}", e),
+            Some(Err(e)) => eprintln!("Error reading network statistics: {}", e),
+            None => break,
         }
 
         updates += 1;
-
-        // Wait for the next tick
-        interval.tick().await;
     }
+    end_single_line(&opts);
+    print_final_peaks(&opts, &mut extras);
 }
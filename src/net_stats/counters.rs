@@ -0,0 +1,62 @@
+// counters.rs
+// This module defines the full set of per-interface counters, as found in `/proc/net/dev`.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// All counters of a network interface.
+///
+/// The fields follow the columns of `/proc/net/dev`. Platforms that don't provide a counter
+/// report it as zero.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetDevCounters {
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub rx_dropped: u64,
+    pub rx_fifo: u64,
+    pub rx_frame: u64,
+    pub rx_compressed: u64,
+    pub rx_multicast: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+    pub tx_dropped: u64,
+    pub tx_fifo: u64,
+    pub tx_collisions: u64,
+    pub tx_carrier: u64,
+    pub tx_compressed: u64,
+}
+
+impl NetDevCounters {
+    /// Creates counters from the 16 columns of a `/proc/net/dev` line. Missing columns are zero.
+    pub fn from_columns(columns: &[u64]) -> NetDevCounters {
+        let col = |i: usize| columns.get(i).copied().unwrap_or(0);
+        NetDevCounters {
+            rx_bytes: col(0),
+            rx_packets: col(1),
+            rx_errors: col(2),
+            rx_dropped: col(3),
+            rx_fifo: col(4),
+            rx_frame: col(5),
+            rx_compressed: col(6),
+            rx_multicast: col(7),
+            tx_bytes: col(8),
+            tx_packets: col(9),
+            tx_errors: col(10),
+            tx_dropped: col(11),
+            tx_fifo: col(12),
+            tx_collisions: col(13),
+            tx_carrier: col(14),
+            tx_compressed: col(15),
+        }
+    }
+}
+
+/// Reduces full counters to (received bytes, transmitted bytes) as returned by `get_net_dev_stats`.
+pub fn to_byte_stats(counters: &IndexMap<String, NetDevCounters>) -> IndexMap<String, (u64, u64)> {
+    counters
+        .iter()
+        .map(|(iface, c)| (iface.clone(), (c.rx_bytes, c.tx_bytes)))
+        .collect()
+}
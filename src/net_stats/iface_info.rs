@@ -0,0 +1,194 @@
+// iface_info.rs
+// This module collects interface metadata for `--list-interfaces`: index, state, MAC address,
+// MTU, link speed, driver, kind and IP addresses. On Linux it combines rtnetlink link and
+// address dumps with the speed and driver from sysfs.
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::error::IfstatError;
+
+/// Metadata of one interface. Fields the platform doesn't provide are `None` or empty.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct InterfaceInfo {
+    pub name: String,
+    pub ifindex: u32,
+    /// Operational state as in `/sys/class/net/*/operstate`, e.g. `up`, `down` or `unknown`.
+    pub operstate: String,
+    pub mac: Option<String>,
+    pub mtu: Option<u32>,
+    /// Link speed in Mbit/s, `None` if unknown (e.g. for virtual interfaces or without carrier).
+    pub speed_mbps: Option<u64>,
+    /// Kernel driver of the underlying device.
+    pub driver: Option<String>,
+    /// `physical`, `loopback`, or the link kind of a virtual interface such as `veth`, `bridge`,
+    /// `bond`, `vlan` or `tun`. Other virtual interfaces without a kind are `virtual`.
+    pub kind: String,
+    /// IP addresses with prefix length, e.g. `192.168.1.2/24`.
+    pub addresses: Vec<String>,
+    /// Upper device this interface is enslaved to, e.g. its bond, team or bridge.
+    pub master: Option<String>,
+    /// Lower device a VLAN, macvlan or similar interface is stacked on.
+    pub link: Option<String>,
+    /// Description set with `ip link set <name> alias <alias>`.
+    pub alias: Option<String>,
+    /// Alternative names, e.g. the predictable names udev adds to renamed interfaces.
+    pub altnames: Vec<String>,
+}
+
+impl InterfaceInfo {
+    /// Returns the friendly name of the interface: its alias. Alternative names are not used, as
+    /// udev adds the predictable name of the interface as one.
+    pub fn friendly_name(&self) -> Option<&str> {
+        self.alias.as_deref()
+    }
+}
+
+/// Formats a hardware address as colon-separated hex bytes.
+pub fn format_mac(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Returns the name of an `IF_OPER_*` state like the kernel shows it in sysfs.
+pub fn operstate_name(state: u8) -> &'static str {
+    match state {
+        1 => "notpresent",
+        2 => "down",
+        3 => "lowerlayerdown",
+        4 => "testing",
+        5 => "dormant",
+        6 => "up",
+        _ => "unknown",
+    }
+}
+
+/// Reads the link speed and the driver of an interface from a directory in `/sys/class/net`
+/// layout, and whether it is backed by a device (i.e. is physical).
+pub fn read_sysfs_link_info(root: &Path, name: &str) -> (Option<u64>, Option<String>, bool) {
+    let dir = root.join(name);
+    // Reading the speed fails with EINVAL for interfaces without carrier, and is -1 if unknown
+    let speed = std::fs::read_to_string(dir.join("speed"))
+        .ok()
+        .and_then(|s| s.trim().parse::<i64>().ok())
+        .filter(|&speed| speed > 0)
+        .map(|speed| speed as u64);
+    let driver = std::fs::read_link(dir.join("device").join("driver"))
+        .ok()
+        .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()));
+    let physical = dir.join("device").exists();
+    (speed, driver, physical)
+}
+
+/// Reads the alias of an interface from a directory in `/sys/class/net` layout. Returns `None` if
+/// it has no alias.
+pub fn read_ifalias(root: &Path, name: &str) -> Option<String> {
+    let alias = std::fs::read_to_string(root.join(name).join("ifalias")).ok()?;
+    Some(alias.trim_end_matches('\n').to_string()).filter(|alias| !alias.is_empty())
+}
+
+/// Reads the mode (e.g. `active-backup`) and the slaves of a bonding master from a directory in
+/// `/sys/class/net` layout. Returns `None` if `name` is not a bond.
+pub fn read_bond_info(root: &Path, name: &str) -> Option<(String, Vec<String>)> {
+    let dir = root.join(name).join("bonding");
+    // The mode file contains the name and the number, e.g. "active-backup 1"
+    let mode = std::fs::read_to_string(dir.join("mode")).ok()?;
+    let mode = mode
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let slaves = std::fs::read_to_string(dir.join("slaves"))
+        .map(|slaves| slaves.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default();
+    Some((mode, slaves))
+}
+
+/// Retrieves the metadata of all interfaces, in ifindex order.
+#[cfg(target_os = "linux")]
+pub fn get_interface_info() -> Result<IndexMap<String, InterfaceInfo>, IfstatError> {
+    use super::netlink::{attr_str, dump_addresses, dump_links, find_attr, parse_attrs, read_u32};
+
+    const ARPHRD_LOOPBACK: u16 = 772;
+
+    let mut addresses = dump_addresses()?;
+    let links = dump_links()?;
+    let names: IndexMap<u32, String> = links
+        .iter()
+        .filter_map(|link| Some((link.index(), link.name()?)))
+        .collect();
+    let mut infos = IndexMap::new();
+    for link in links {
+        let Some(name) = link.name() else {
+            continue;
+        };
+        let attrs = link.attrs();
+        let sysfs = Path::new("/sys/class/net");
+        let (speed_mbps, driver, physical) = read_sysfs_link_info(sysfs, &name);
+        let link_kind = find_attr(&attrs, libc::IFLA_LINKINFO)
+            .and_then(|info| find_attr(&parse_attrs(info), libc::IFLA_INFO_KIND).map(attr_str));
+        let kind = match link_kind {
+            Some(kind) => kind,
+            None if link.link_type() == ARPHRD_LOOPBACK => "loopback".to_string(),
+            None if physical => "physical".to_string(),
+            None => "virtual".to_string(),
+        };
+
+        // The link of a veth is its peer, and links into other namespaces can't be resolved here
+        let lower = find_attr(&attrs, libc::IFLA_LINK)
+            .filter(|link| link.len() >= 4)
+            .map(|link| read_u32(link, 0))
+            .filter(|&index| index != 0 && index != link.index())
+            .filter(|_| kind != "veth" && find_attr(&attrs, libc::IFLA_LINK_NETNSID).is_none())
+            .and_then(|index| names.get(&index).cloned());
+
+        let info = InterfaceInfo {
+            ifindex: link.index(),
+            operstate: find_attr(&attrs, libc::IFLA_OPERSTATE)
+                .and_then(|state| state.first())
+                .map(|&state| operstate_name(state))
+                .unwrap_or("unknown")
+                .to_string(),
+            mac: find_attr(&attrs, libc::IFLA_ADDRESS)
+                .filter(|mac| !mac.is_empty())
+                .map(format_mac),
+            mtu: find_attr(&attrs, libc::IFLA_MTU)
+                .filter(|mtu| mtu.len() >= 4)
+                .map(|mtu| read_u32(mtu, 0)),
+            speed_mbps,
+            driver,
+            kind,
+            addresses: addresses.shift_remove(&link.index()).unwrap_or_default(),
+            master: find_attr(&attrs, libc::IFLA_MASTER)
+                .filter(|master| master.len() >= 4)
+                .and_then(|master| names.get(&read_u32(master, 0)).cloned()),
+            link: lower,
+            alias: read_ifalias(sysfs, &name),
+            altnames: find_attr(&attrs, libc::IFLA_PROP_LIST)
+                .map(|props| {
+                    parse_attrs(props)
+                        .into_iter()
+                        .filter(|(attr_type, _)| *attr_type == libc::IFLA_ALT_IFNAME)
+                        .map(|(_, altname)| attr_str(altname))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            name: name.clone(),
+        };
+        infos.insert(name, info);
+    }
+    Ok(infos)
+}
+
+/// Retrieves the metadata of all interfaces (Linux only).
+#[cfg(not(target_os = "linux"))]
+pub fn get_interface_info() -> Result<IndexMap<String, InterfaceInfo>, IfstatError> {
+    Err(IfstatError::Unsupported(
+        "Interface metadata is only available on Linux",
+    ))
+}
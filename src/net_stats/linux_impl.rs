@@ -2,88 +2,90 @@
 // This module provides utility functions to retrieve network device statistics
 // and map device strings to friendly names on a Unix-based system.
 
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufReader;
+use std::mem::{discriminant, Discriminant};
+use std::sync::Mutex;
 
 use indexmap::IndexMap;
 
-use crate::test_debug;
+use super::{
+    get_interface_info, parse_net_dev_counters_lenient, parse_net_dev_stats_lenient, NetDevCounters,
+};
+use crate::error::IfstatError;
+
+/// Most malformed lines to remember, so a long run with many broken devices stays bounded.
+const MAX_WARNED_LINES: usize = 256;
+
+lazy_static::lazy_static! {
+    // Interfaces and kinds of errors we already warned about, so a broken device doesn't flood
+    // the log. The lines themselves contain counters that change on every read.
+    static ref WARNED_LINES: Mutex<HashSet<(String, Discriminant<IfstatError>)>> =
+        Mutex::new(HashSet::new());
+}
 
 /// Retrieves network device statistics from the `/proc/net/dev` file.
 ///
+/// Malformed lines are skipped so that one odd device doesn't hide all others, and each kind of
+/// error is logged once per interface as a warning. Live statistics are always parsed like this;
+/// `ProcNetDevProvider` (`--provider proc`) reads `/proc/net/dev` strictly instead, failing on
+/// the first malformed line.
+///
 /// # Returns
 ///
 /// A result containing an IndexMap where the keys are the device names and the values are tuples of (received bytes, transmitted bytes).
-/// In case of an error, returns an io::Error.
-pub fn get_net_dev_stats() -> Result<IndexMap<String, (u64, u64)>, std::io::Error> {
+/// In case of an error, returns an IfstatError.
+pub fn get_net_dev_stats() -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+    let file = File::open("/proc/net/dev")?;
+    let parsed = parse_net_dev_stats_lenient(BufReader::new(file))?;
+    warn_skipped_lines(parsed.warnings);
+    Ok(parsed.stats)
+}
+
+/// Retrieves all counters of all network devices from the `/proc/net/dev` file.
+///
+/// Malformed lines are handled like in `get_net_dev_stats`, and here also include lines with any
+/// column that is not a number.
+pub fn get_net_dev_counters() -> Result<IndexMap<String, NetDevCounters>, IfstatError> {
     // Open the `/proc/net/dev` file for reading
     let file = File::open("/proc/net/dev")?;
     let reader = BufReader::new(file);
     // Parse the network device statistics from the file
-    parse_net_dev_stats(reader)
+    let parsed = parse_net_dev_counters_lenient(reader)?;
+    warn_skipped_lines(parsed.warnings);
+    Ok(parsed.stats)
 }
 
-/// Parses network device statistics from a given reader.
-///
-/// # Arguments
-///
-/// * `reader` - A reader that provides lines of network device statistics.
-///
-/// # Returns
-///
-/// A result containing an IndexMap where the keys are the device names and the values are tuples of (received bytes, transmitted bytes).
-/// In case of an error, returns an io::Error.
-pub fn parse_net_dev_stats<R: BufRead>(
-    reader: R,
-) -> Result<IndexMap<String, (u64, u64)>, std::io::Error> {
-    let mut stats = IndexMap::new();
-    let lines: Vec<_> = reader.lines().collect::<Result<_, _>>()?;
-    test_debug!("Parsing {} lines", lines.len());
-
-    // Skip the first two lines as they are headers
-    for (_index, line) in lines.into_iter().enumerate().skip(2) {
-        test_debug!("Parsing line: {}", line);
-        // Split the line into interface name and the rest of the statistics
-        if let Some((iface, rest)) = line.split_once(':') {
-            let fields: Vec<&str> = rest.split_whitespace().collect();
-            if fields.len() >= 9 {
-                // Parse the received and transmitted bytes
-                let rx_bytes: u64 = fields[0].parse().map_err(|_| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid RX bytes")
-                })?;
-                let tx_bytes: u64 = fields[8].parse().map_err(|_| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid TX bytes")
-                })?;
-                stats.insert(iface.trim().to_string(), (rx_bytes, tx_bytes));
-            } else {
-                test_debug!(
-                    "Invalid line format: '{}' ({} fields: {:?})",
-                    line,
-                    fields.len(),
-                    fields
-                );
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Invalid line format: {} fields", fields.len()),
-                ));
-            }
-        } else {
-            test_debug!("Invalid line format: '{}' (no colon found)", line);
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Invalid line format (no colon found)",
-            ));
+/// Logs the errors of skipped `/proc/net/dev` lines, each kind once per interface.
+fn warn_skipped_lines(warnings: Vec<IfstatError>) {
+    if warnings.is_empty() {
+        return;
+    }
+    let mut warned = WARNED_LINES.lock().unwrap();
+    for warning in warnings {
+        let key = (
+            warning.interface().unwrap_or_default().to_string(),
+            discriminant(&warning),
+        );
+        if warned.len() < MAX_WARNED_LINES && warned.insert(key) {
+            log::warn!("Skipping line of /proc/net/dev: {}", warning);
         }
     }
-    Ok(stats)
 }
 
 /// Retrieves a map of device strings to friendly names.
 ///
+/// On Linux the device strings are the interface names, and the friendly names their aliases
+/// (`/sys/class/net/*/ifalias`).
+///
 /// # Returns
 ///
-/// A HashMap where the keys are device strings and the values are friendly names.
-pub fn get_device_string_to_name_map() -> HashMap<String, String> {
-    HashMap::new() // This isn't really crucial on linux but we *could* implement it.
+/// An IndexMap where the keys are device strings and the values are friendly names.
+pub fn get_device_string_to_name_map() -> IndexMap<String, String> {
+    get_interface_info()
+        .unwrap_or_default()
+        .into_values()
+        .filter_map(|info| Some((info.name.clone(), info.friendly_name()?.to_string())))
+        .collect()
 }
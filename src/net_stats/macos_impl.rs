@@ -3,11 +3,21 @@ use libc::{c_int, c_void, sysctl, CTL_NET, NET_RT_IFLIST2, PF_ROUTE};
 use std::io::Error;
 use std::ptr::null_mut;
 
+use super::{to_byte_stats, NetDevCounters};
+use crate::error::IfstatError;
+
 /// Fetch network device statistics for each network interface.
 ///
 /// Returns an `IndexMap` where the key is the interface name and the value is a tuple containing
 /// bytes in and bytes out.
-pub fn get_net_dev_stats() -> Result<IndexMap<String, (u64, u64)>, Error> {
+pub fn get_net_dev_stats() -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+    get_net_dev_counters().map(|counters| to_byte_stats(&counters))
+}
+
+/// Fetch all counters that `if_data64` provides for each network interface.
+///
+/// FIFO, frame, compressed and carrier counters are not available and reported as zero.
+pub fn get_net_dev_counters() -> Result<IndexMap<String, NetDevCounters>, IfstatError> {
     unsafe {
         // Define the MIB (Management Information Base) array for fetching network interface data
         let mut mib: [c_int; 6] = [CTL_NET, PF_ROUTE, 0, 0, NET_RT_IFLIST2, 0];
@@ -23,7 +33,7 @@ pub fn get_net_dev_stats() -> Result<IndexMap<String, (u64, u64)>, Error> {
             0,
         ) < 0
         {
-            return Err(Error::last_os_error());
+            return Err(Error::last_os_error().into());
         }
 
         // Allocate a buffer with the required length
@@ -38,7 +48,7 @@ pub fn get_net_dev_stats() -> Result<IndexMap<String, (u64, u64)>, Error> {
             0,
         ) < 0
         {
-            return Err(Error::last_os_error());
+            return Err(Error::last_os_error().into());
         }
 
         // Get a map of interface indices to names
@@ -60,11 +70,21 @@ pub fn get_net_dev_stats() -> Result<IndexMap<String, (u64, u64)>, Error> {
 
                 // Get the interface name by its index
                 if let Some(name) = iface_names.get(&(if2.ifm_index as u32)) {
-                    let bytes_in = data.ifi_ibytes;
-                    let bytes_out = data.ifi_obytes;
+                    let counters = NetDevCounters {
+                        rx_bytes: data.ifi_ibytes,
+                        rx_packets: data.ifi_ipackets,
+                        rx_errors: data.ifi_ierrors,
+                        rx_dropped: data.ifi_iqdrops,
+                        rx_multicast: data.ifi_imcasts,
+                        tx_bytes: data.ifi_obytes,
+                        tx_packets: data.ifi_opackets,
+                        tx_errors: data.ifi_oerrors,
+                        tx_collisions: data.ifi_collisions,
+                        ..Default::default()
+                    };
 
                     // Insert the interface name and its statistics into the index map
-                    index_map.insert(name.clone(), (bytes_in, bytes_out));
+                    index_map.insert(name.clone(), counters);
                 }
             }
         }
@@ -0,0 +1,273 @@
+// netlink.rs
+// This module provides a minimal rtnetlink client on top of libc: it sends dump requests
+// (e.g. RTM_GETLINK) and splits the replies into messages and attributes.
+
+use std::io;
+use std::mem::size_of;
+use std::net::IpAddr;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use indexmap::IndexMap;
+
+/// Size of `struct nlmsghdr`.
+const NLMSG_HDRLEN: usize = 16;
+/// Size of `struct ifaddrmsg`.
+const IFADDRMSG_LEN: usize = 8;
+/// Size of `struct ifinfomsg`.
+pub(crate) const IFINFOMSG_LEN: usize = 16;
+/// Mask removing the NLA_F_NESTED and NLA_F_NET_BYTEORDER flags from an attribute type.
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+/// Rounds `len` up to the 4 byte netlink alignment.
+pub(crate) fn nl_align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// A reply message of a netlink dump: its type and its payload after the netlink header.
+pub(crate) struct NetlinkMessage {
+    pub msg_type: u16,
+    pub payload: Vec<u8>,
+}
+
+/// A NETLINK_ROUTE socket.
+pub(crate) struct NetlinkSocket {
+    fd: OwnedFd,
+    seq: u32,
+}
+
+impl NetlinkSocket {
+    /// Opens and binds a NETLINK_ROUTE socket.
+    pub fn route() -> io::Result<NetlinkSocket> {
+        unsafe {
+            let fd = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_ROUTE,
+            );
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let fd = OwnedFd::from_raw_fd(fd);
+
+            let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            if libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            ) < 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(NetlinkSocket { fd, seq: 0 })
+        }
+    }
+
+    /// Sends a dump request of type `msg_type` with the given payload (e.g. an `ifinfomsg`)
+    /// and collects all reply messages until NLMSG_DONE.
+    pub fn dump(&mut self, msg_type: u16, payload: &[u8]) -> io::Result<Vec<NetlinkMessage>> {
+        self.seq = self.seq.wrapping_add(1);
+        let len = NLMSG_HDRLEN + payload.len();
+        let flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+
+        let mut request = Vec::with_capacity(nl_align(len));
+        request.extend_from_slice(&(len as u32).to_ne_bytes());
+        request.extend_from_slice(&msg_type.to_ne_bytes());
+        request.extend_from_slice(&flags.to_ne_bytes());
+        request.extend_from_slice(&self.seq.to_ne_bytes());
+        request.extend_from_slice(&0u32.to_ne_bytes()); // pid, 0 addresses the kernel
+        request.extend_from_slice(payload);
+        request.resize(nl_align(len), 0);
+
+        let sent = unsafe {
+            libc::send(
+                self.fd.as_raw_fd(),
+                request.as_ptr() as *const libc::c_void,
+                request.len(),
+                0,
+            )
+        };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut messages = Vec::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let received = unsafe {
+                libc::recv(
+                    self.fd.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    0,
+                )
+            };
+            if received < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+
+            let data = &buf[..received as usize];
+            let mut offset = 0;
+            while offset + NLMSG_HDRLEN <= data.len() {
+                let msg_len = read_u32(data, offset) as usize;
+                let msg_type = read_u16(data, offset + 4);
+                let msg_seq = read_u32(data, offset + 8);
+                if msg_len < NLMSG_HDRLEN || offset + msg_len > data.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Truncated netlink message",
+                    ));
+                }
+                let body = &data[offset + NLMSG_HDRLEN..offset + msg_len];
+                offset += nl_align(msg_len);
+
+                if msg_seq != self.seq {
+                    continue;
+                }
+                match msg_type as libc::c_int {
+                    libc::NLMSG_DONE => return Ok(messages),
+                    libc::NLMSG_ERROR => {
+                        let errno = if body.len() >= 4 {
+                            read_i32(body, 0)
+                        } else {
+                            0
+                        };
+                        if errno != 0 {
+                            return Err(io::Error::from_raw_os_error(-errno));
+                        }
+                    }
+                    _ => messages.push(NetlinkMessage {
+                        msg_type,
+                        payload: body.to_vec(),
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// Splits a buffer of netlink attributes into (type, payload) pairs.
+pub(crate) fn parse_attrs(mut data: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut attrs = Vec::new();
+    while data.len() >= 4 {
+        let len = read_u16(data, 0) as usize;
+        let attr_type = read_u16(data, 2) & NLA_TYPE_MASK;
+        if len < 4 || len > data.len() {
+            break;
+        }
+        attrs.push((attr_type, &data[4..len]));
+        data = &data[nl_align(len).min(data.len())..];
+    }
+    attrs
+}
+
+/// Returns the payload of the first attribute of the given type.
+pub(crate) fn find_attr<'a>(attrs: &[(u16, &'a [u8])], attr_type: u16) -> Option<&'a [u8]> {
+    attrs
+        .iter()
+        .find(|(t, _)| *t == attr_type)
+        .map(|(_, payload)| *payload)
+}
+
+/// Interprets an attribute payload as a NUL-terminated string.
+pub(crate) fn attr_str(payload: &[u8]) -> String {
+    let end = payload
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(payload.len());
+    String::from_utf8_lossy(&payload[..end]).into_owned()
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes([data[offset], data[offset + 1]])
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+pub(crate) fn read_i32(data: &[u8], offset: usize) -> i32 {
+    i32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_ne_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// A link from an RTM_GETLINK dump with its attributes.
+pub(crate) struct Link {
+    pub payload: Vec<u8>,
+}
+
+impl Link {
+    /// Returns the attributes following the `ifinfomsg` header.
+    pub fn attrs(&self) -> Vec<(u16, &[u8])> {
+        parse_attrs(&self.payload[nl_align(IFINFOMSG_LEN).min(self.payload.len())..])
+    }
+
+    /// Returns the ARPHRD_* device type (`ifi_type`).
+    pub fn link_type(&self) -> u16 {
+        read_u16(&self.payload, 2)
+    }
+
+    /// Returns the interface index (`ifi_index`).
+    pub fn index(&self) -> u32 {
+        read_u32(&self.payload, 4)
+    }
+
+    /// Returns the interface name (IFLA_IFNAME).
+    pub fn name(&self) -> Option<String> {
+        find_attr(&self.attrs(), libc::IFLA_IFNAME).map(attr_str)
+    }
+}
+
+/// Dumps all links via RTM_GETLINK.
+pub(crate) fn dump_links() -> io::Result<Vec<Link>> {
+    let mut socket = NetlinkSocket::route()?;
+    let request = [0u8; IFINFOMSG_LEN]; // ifi_family = AF_UNSPEC
+    let messages = socket.dump(libc::RTM_GETLINK, &request)?;
+
+    Ok(messages
+        .into_iter()
+        .filter(|m| m.msg_type == libc::RTM_NEWLINK && m.payload.len() >= IFINFOMSG_LEN)
+        .map(|m| Link { payload: m.payload })
+        .collect())
+}
+
+/// Dumps all IP addresses via RTM_GETADDR, as `address/prefix` strings by interface index.
+pub(crate) fn dump_addresses() -> io::Result<IndexMap<u32, Vec<String>>> {
+    let mut socket = NetlinkSocket::route()?;
+    let request = [0u8; IFADDRMSG_LEN]; // ifa_family = AF_UNSPEC
+    let messages = socket.dump(libc::RTM_GETADDR, &request)?;
+
+    let mut addresses: IndexMap<u32, Vec<String>> = IndexMap::new();
+    for m in messages {
+        if m.msg_type != libc::RTM_NEWADDR || m.payload.len() < IFADDRMSG_LEN {
+            continue;
+        }
+        let prefix_len = m.payload[1];
+        let index = read_u32(&m.payload, 4);
+        let attrs = parse_attrs(&m.payload[IFADDRMSG_LEN..]);
+        // IFA_LOCAL is the own address, IFA_ADDRESS the peer's on point-to-point links
+        let Some(address) =
+            find_attr(&attrs, libc::IFA_LOCAL).or_else(|| find_attr(&attrs, libc::IFA_ADDRESS))
+        else {
+            continue;
+        };
+        let address = match address.len() {
+            4 => IpAddr::from(<[u8; 4]>::try_from(address).unwrap()),
+            16 => IpAddr::from(<[u8; 16]>::try_from(address).unwrap()),
+            _ => continue,
+        };
+        addresses
+            .entry(index)
+            .or_default()
+            .push(format!("{}/{}", address, prefix_len));
+    }
+    Ok(addresses)
+}
@@ -0,0 +1,184 @@
+// proc_net_dev.rs
+// This module parses the `/proc/net/dev` format. It is available on all platforms so that
+// saved dumps from Linux machines can be analyzed anywhere.
+
+use std::io::BufRead;
+
+use indexmap::IndexMap;
+
+use super::counters::{to_byte_stats, NetDevCounters};
+use crate::error::IfstatError;
+use crate::test_debug;
+
+/// Result of the lenient parsers: the statistics of all valid lines and the errors of the
+/// skipped ones.
+#[derive(Debug, Default)]
+pub struct LenientStats<T = IndexMap<String, (u64, u64)>> {
+    pub stats: T,
+    pub warnings: Vec<IfstatError>,
+}
+
+/// Indices of the received and transmitted bytes, the only columns the byte statistics read.
+const BYTE_COLUMNS: [usize; 2] = [0, 8];
+
+/// Names of the `/proc/net/dev` columns, used in error messages.
+const COLUMN_NAMES: [&str; 16] = [
+    "RX bytes",
+    "RX packets",
+    "RX errs",
+    "RX drop",
+    "RX fifo",
+    "RX frame",
+    "RX compressed",
+    "RX multicast",
+    "TX bytes",
+    "TX packets",
+    "TX errs",
+    "TX drop",
+    "TX fifo",
+    "TX colls",
+    "TX carrier",
+    "TX compressed",
+];
+
+/// Parses network device statistics from a given reader.
+///
+/// # Arguments
+///
+/// * `reader` - A reader that provides lines of network device statistics.
+///
+/// # Returns
+///
+/// A result containing an IndexMap where the keys are the device names and the values are tuples of (received bytes, transmitted bytes).
+/// The first malformed line aborts parsing with an `IfstatError` describing it. Only the byte
+/// columns must be numbers, the other columns are not read.
+pub fn parse_net_dev_stats<R: BufRead>(
+    reader: R,
+) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+    parse_counters(reader, false).map(|counters| to_byte_stats(&counters))
+}
+
+/// Parses network device statistics from a given reader, skipping malformed lines.
+///
+/// # Returns
+///
+/// The statistics of all valid lines together with one warning per skipped line.
+/// Only errors reading from `reader` are returned as `Err`.
+pub fn parse_net_dev_stats_lenient<R: BufRead>(reader: R) -> Result<LenientStats, IfstatError> {
+    let parsed = parse_counters_lenient(reader, false)?;
+    Ok(LenientStats {
+        stats: to_byte_stats(&parsed.stats),
+        warnings: parsed.warnings,
+    })
+}
+
+/// Parses all counters of all devices from a given reader, like `parse_net_dev_stats`. All
+/// columns present must be numbers.
+pub fn parse_net_dev_counters<R: BufRead>(
+    reader: R,
+) -> Result<IndexMap<String, NetDevCounters>, IfstatError> {
+    parse_counters(reader, true)
+}
+
+/// Parses all counters of all devices from a given reader, like `parse_net_dev_stats_lenient`.
+/// Lines with a column that is not a number are skipped.
+pub fn parse_net_dev_counters_lenient<R: BufRead>(
+    reader: R,
+) -> Result<LenientStats<IndexMap<String, NetDevCounters>>, IfstatError> {
+    parse_counters_lenient(reader, true)
+}
+
+/// Parses the counters of all lines, failing on the first malformed one. Unless `all_columns`
+/// is set, only the byte columns are validated and other columns that are not numbers are 0.
+fn parse_counters<R: BufRead>(
+    reader: R,
+    all_columns: bool,
+) -> Result<IndexMap<String, NetDevCounters>, IfstatError> {
+    let mut stats = IndexMap::new();
+    for parsed in parse_lines(reader, all_columns)? {
+        let (iface, counters) = parsed?;
+        stats.insert(iface, counters);
+    }
+    Ok(stats)
+}
+
+/// Parses the counters of all lines, skipping malformed ones. `all_columns` is as in
+/// `parse_counters`.
+fn parse_counters_lenient<R: BufRead>(
+    reader: R,
+    all_columns: bool,
+) -> Result<LenientStats<IndexMap<String, NetDevCounters>>, IfstatError> {
+    let mut result = LenientStats::<IndexMap<String, NetDevCounters>>::default();
+    for parsed in parse_lines(reader, all_columns)? {
+        match parsed {
+            Ok((iface, counters)) => {
+                result.stats.insert(iface, counters);
+            }
+            Err(e) => result.warnings.push(e),
+        }
+    }
+    Ok(result)
+}
+
+type ParsedLine = Result<(String, NetDevCounters), IfstatError>;
+
+/// Parses every line after the two header lines.
+fn parse_lines<R: BufRead>(reader: R, all_columns: bool) -> Result<Vec<ParsedLine>, IfstatError> {
+    let lines: Vec<_> = reader.lines().collect::<Result<_, _>>()?;
+    test_debug!("Parsing {} lines", lines.len());
+
+    // Skip the first two lines as they are headers
+    Ok(lines
+        .into_iter()
+        .enumerate()
+        .skip(2)
+        .map(|(index, line)| parse_line(index + 1, line, all_columns))
+        .collect())
+}
+
+/// Parses a single interface line, `line` is its 1-based line number. `all_columns` is as in
+/// `parse_counters`.
+fn parse_line(line: usize, text: String, all_columns: bool) -> ParsedLine {
+    test_debug!("Parsing line: {}", text);
+    // Split the line into interface name and the rest of the statistics
+    let Some((iface, rest)) = text.split_once(':') else {
+        test_debug!("Invalid line format: '{}' (no colon found)", text);
+        return Err(IfstatError::MissingColon { line, text });
+    };
+    let interface = iface.trim().to_string();
+
+    // At least everything up to the transmitted bytes must be present
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    if fields.len() < 9 {
+        test_debug!(
+            "Invalid line format: '{}' ({} fields: {:?})",
+            text,
+            fields.len(),
+            fields
+        );
+        let fields = fields.len();
+        return Err(IfstatError::TooFewFields {
+            line,
+            text,
+            interface,
+            fields,
+        });
+    }
+
+    let mut columns = Vec::with_capacity(COLUMN_NAMES.len());
+    for (index, (field, counter)) in fields.iter().zip(COLUMN_NAMES).enumerate() {
+        match field.parse::<u64>() {
+            Ok(value) => columns.push(value),
+            Err(_) if !all_columns && !BYTE_COLUMNS.contains(&index) => columns.push(0),
+            Err(_) => {
+                return Err(IfstatError::InvalidCounter {
+                    line,
+                    text: text.clone(),
+                    interface,
+                    counter,
+                })
+            }
+        }
+    }
+    Ok((interface, NetDevCounters::from_columns(&columns)))
+}
@@ -0,0 +1,150 @@
+// proto_stats.rs
+// This module parses the per-protocol counters of `/proc/net/snmp` and `/proc/net/netstat`.
+// Like `/proc/net/dev`, the parser is available on all platforms so saved dumps can be tested
+// and analyzed anywhere.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use crate::error::IfstatError;
+
+/// Counters reported as rates by `--proto`, as `Protocol.Counter` keys.
+pub const PROTO_RATE_COUNTERS: [&str; 11] = [
+    "Ip.InReceives",
+    "Ip.OutRequests",
+    "Tcp.InSegs",
+    "Tcp.OutSegs",
+    "Tcp.RetransSegs",
+    "TcpExt.ListenDrops",
+    "Udp.InDatagrams",
+    "Udp.OutDatagrams",
+    "Udp.RcvbufErrors",
+    "Icmp.InMsgs",
+    "Icmp.OutMsgs",
+];
+
+/// Parses protocol counters from a given reader in the format of `/proc/net/snmp`.
+///
+/// Every protocol has a line with counter names followed by a line with their values, both
+/// prefixed with `Protocol:`. The result maps `Protocol.Counter` to its value. Negative values
+/// (which are settings like `Tcp.MaxConn`, not counters) are skipped.
+pub fn parse_proto_stats<R: BufRead>(reader: R) -> Result<IndexMap<String, u64>, IfstatError> {
+    let lines: Vec<_> = reader.lines().collect::<Result<_, _>>()?;
+    let mut stats = IndexMap::new();
+
+    let mut index = 0;
+    while index < lines.len() {
+        let names_line = &lines[index];
+        if names_line.trim().is_empty() {
+            index += 1;
+            continue;
+        }
+        let invalid = |line: usize, message: &str| {
+            IfstatError::InvalidData(format!(
+                "{} at line {}: '{}'",
+                message,
+                line,
+                lines[line - 1]
+            ))
+        };
+
+        let Some((protocol, names)) = names_line.split_once(':') else {
+            return Err(invalid(index + 1, "Missing protocol name"));
+        };
+        let Some(values_line) = lines.get(index + 1) else {
+            return Err(invalid(index + 1, "Missing values line"));
+        };
+        let Some((values_protocol, values)) = values_line.split_once(':') else {
+            return Err(invalid(index + 2, "Missing protocol name"));
+        };
+        if values_protocol != protocol {
+            return Err(invalid(
+                index + 2,
+                "Protocol doesn't match the previous line",
+            ));
+        }
+
+        let names: Vec<&str> = names.split_whitespace().collect();
+        let values: Vec<&str> = values.split_whitespace().collect();
+        if names.len() != values.len() {
+            return Err(invalid(
+                index + 2,
+                &format!("{} values for {} counters", values.len(), names.len()),
+            ));
+        }
+
+        for (name, value) in names.iter().zip(values) {
+            match value.parse::<u64>() {
+                Ok(value) => {
+                    stats.insert(format!("{}.{}", protocol, name), value);
+                }
+                Err(_) if value.parse::<i64>().is_ok() => {}
+                Err(_) => {
+                    return Err(invalid(
+                        index + 2,
+                        &format!("Invalid {}.{}", protocol, name),
+                    ));
+                }
+            }
+        }
+        index += 2;
+    }
+
+    Ok(stats)
+}
+
+/// Reads and merges the protocol counters of an snmp and an optional netstat file.
+pub fn read_proto_stats(
+    snmp: &Path,
+    netstat: Option<&Path>,
+) -> Result<IndexMap<String, u64>, IfstatError> {
+    let mut stats = IndexMap::new();
+    for path in std::iter::once(snmp).chain(netstat) {
+        let parsed = File::open(path)
+            .map_err(IfstatError::from)
+            .and_then(|file| parse_proto_stats(BufReader::new(file)))
+            .map_err(|e| e.context(path.display()))?;
+        stats.extend(parsed);
+    }
+    Ok(stats)
+}
+
+/// Retrieves the protocol counters of the system from `/proc/net/snmp` and `/proc/net/netstat`.
+#[cfg(target_os = "linux")]
+pub fn get_proto_stats() -> Result<IndexMap<String, u64>, IfstatError> {
+    read_proto_stats(
+        Path::new("/proc/net/snmp"),
+        Some(Path::new("/proc/net/netstat")),
+    )
+}
+
+/// Retrieves the protocol counters of the system (Linux only).
+#[cfg(not(target_os = "linux"))]
+pub fn get_proto_stats() -> Result<IndexMap<String, u64>, IfstatError> {
+    Err(IfstatError::Unsupported(
+        "Protocol statistics are only available on Linux",
+    ))
+}
+
+/// Computes per-second rates of the given counters.
+///
+/// Counters missing from either snapshot are skipped, counters that went backwards yield zero.
+pub fn compute_proto_rates(
+    previous: &IndexMap<String, u64>,
+    current: &IndexMap<String, u64>,
+    counters: &[&str],
+    interval_secs: f64,
+) -> IndexMap<String, f64> {
+    counters
+        .iter()
+        .filter_map(|&counter| {
+            let prev = previous.get(counter)?;
+            let cur = current.get(counter)?;
+            let rate = cur.saturating_sub(*prev) as f64 / interval_secs;
+            Some((counter.to_string(), rate))
+        })
+        .collect()
+}
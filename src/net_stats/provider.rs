@@ -0,0 +1,278 @@
+// provider.rs
+// This module defines the `StatsProvider` trait, which abstracts over where network device
+// statistics come from, along with its implementations: the platform default, /proc, sysfs,
+// netlink, recorded files and in-memory test doubles.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+
+use super::{get_net_dev_stats, parse_net_dev_stats};
+use crate::error::IfstatError;
+use crate::record::Snapshot;
+
+/// Describes when the next snapshot of a provider is available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextSnapshot {
+    /// Live statistics, the snapshot reflects the moment it is taken.
+    Live,
+    /// Recorded statistics taken at the given time since the start of the recording.
+    Recorded(Duration),
+    /// No more snapshots are available.
+    Exhausted,
+}
+
+/// A source of network device statistics.
+///
+/// Closures returning snapshots implement this trait as well.
+pub trait StatsProvider: Send {
+    /// Returns the next snapshot, mapping interface names to (received bytes, transmitted bytes).
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError>;
+
+    /// Returns when the next snapshot is available. Live providers don't need to override this.
+    fn next_snapshot(&mut self) -> NextSnapshot {
+        NextSnapshot::Live
+    }
+}
+
+impl<F> StatsProvider for F
+where
+    F: FnMut() -> Result<IndexMap<String, (u64, u64)>, IfstatError> + Send,
+{
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+        self()
+    }
+}
+
+impl StatsProvider for Box<dyn StatsProvider> {
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+        (**self).get_net_dev_stats()
+    }
+
+    fn next_snapshot(&mut self) -> NextSnapshot {
+        (**self).next_snapshot()
+    }
+}
+
+/// The platform's default source: `/proc/net/dev` on Linux, sysctl on macOS, GetIfTable on Windows.
+///
+/// Malformed lines of `/proc/net/dev` are skipped with a warning, see `get_net_dev_stats`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemProvider;
+
+impl StatsProvider for SystemProvider {
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+        get_net_dev_stats()
+    }
+}
+
+/// Reads a file in `/proc/net/dev` format, by default `/proc/net/dev` itself.
+///
+/// Parsing is strict: the first malformed line is an error.
+#[derive(Debug, Clone)]
+pub struct ProcNetDevProvider {
+    path: PathBuf,
+}
+
+impl ProcNetDevProvider {
+    /// Creates a provider reading the given file.
+    pub fn new<P: Into<PathBuf>>(path: P) -> ProcNetDevProvider {
+        ProcNetDevProvider { path: path.into() }
+    }
+}
+
+impl Default for ProcNetDevProvider {
+    fn default() -> Self {
+        ProcNetDevProvider::new("/proc/net/dev")
+    }
+}
+
+impl StatsProvider for ProcNetDevProvider {
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+        let file = File::open(&self.path)?;
+        parse_net_dev_stats(BufReader::new(file))
+    }
+}
+
+/// Reads `statistics/rx_bytes` and `statistics/tx_bytes` of every device in `/sys/class/net`.
+#[derive(Debug, Clone)]
+pub struct SysfsProvider {
+    path: PathBuf,
+}
+
+impl SysfsProvider {
+    /// Creates a provider reading the given directory in `/sys/class/net` layout.
+    pub fn new<P: Into<PathBuf>>(path: P) -> SysfsProvider {
+        SysfsProvider { path: path.into() }
+    }
+}
+
+impl Default for SysfsProvider {
+    fn default() -> Self {
+        SysfsProvider::new("/sys/class/net")
+    }
+}
+
+impl StatsProvider for SysfsProvider {
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+        let read_counter = |iface: &str, name: &str| -> Result<u64, IfstatError> {
+            let path = self.path.join(iface).join("statistics").join(name);
+            let content = std::fs::read_to_string(&path)?;
+            content.trim().parse().map_err(|_| {
+                IfstatError::InvalidData(format!("Invalid counter in {}", path.display()))
+            })
+        };
+
+        // Sort by ifindex to get the same order as /proc/net/dev
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.path)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            // Not every entry is a device, e.g. bonding_masters
+            if !self.path.join(&name).join("statistics").is_dir() {
+                continue;
+            }
+            let ifindex = std::fs::read_to_string(self.path.join(&name).join("ifindex"))
+                .ok()
+                .and_then(|s| s.trim().parse::<u32>().ok())
+                .unwrap_or(u32::MAX);
+            names.push((ifindex, name));
+        }
+        names.sort();
+
+        let mut stats = IndexMap::new();
+        for (_, iface) in names {
+            match (
+                read_counter(&iface, "rx_bytes"),
+                read_counter(&iface, "tx_bytes"),
+            ) {
+                (Ok(rx), Ok(tx)) => {
+                    stats.insert(iface, (rx, tx));
+                }
+                // Devices may disappear while we're reading
+                (Err(IfstatError::Io(e)), _) | (_, Err(IfstatError::Io(e)))
+                    if e.kind() == std::io::ErrorKind::NotFound => {}
+                (Err(e), _) | (_, Err(e)) => return Err(e),
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Dumps all links via rtnetlink (RTM_GETLINK) and reads their 64 bit counters (IFLA_STATS64).
+#[cfg(target_os = "linux")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NetlinkProvider;
+
+#[cfg(target_os = "linux")]
+impl StatsProvider for NetlinkProvider {
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+        Ok(super::netlink::dump_links()?
+            .iter()
+            .filter_map(|link| parse_link_message(&link.payload))
+            .collect())
+    }
+}
+
+/// Parses an RTM_NEWLINK message payload (`struct ifinfomsg` and attributes) into the interface
+/// name and its (received bytes, transmitted bytes) from IFLA_STATS64.
+///
+/// Returns `None` for truncated messages and links without a name or 64 bit counters.
+#[cfg(target_os = "linux")]
+pub fn parse_link_message(payload: &[u8]) -> Option<(String, (u64, u64))> {
+    use super::netlink::{attr_str, find_attr, nl_align, parse_attrs, read_u64, IFINFOMSG_LEN};
+
+    if payload.len() < IFINFOMSG_LEN {
+        return None;
+    }
+    let attrs = parse_attrs(&payload[nl_align(IFINFOMSG_LEN)..]);
+    let name = find_attr(&attrs, libc::IFLA_IFNAME).map(attr_str)?;
+    let stats64 = find_attr(&attrs, libc::IFLA_STATS64)?;
+    // struct rtnl_link_stats64 starts with rx_packets, tx_packets, rx_bytes, tx_bytes
+    if stats64.len() < 32 {
+        return None;
+    }
+    Some((name, (read_u64(stats64, 16), read_u64(stats64, 24))))
+}
+
+/// Replays snapshots written by `record::Recorder`.
+#[derive(Debug, Clone)]
+pub struct ReplayProvider {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl ReplayProvider {
+    /// Creates a provider returning the given snapshots in order.
+    pub fn new(snapshots: Vec<Snapshot>) -> ReplayProvider {
+        ReplayProvider {
+            snapshots: snapshots.into(),
+        }
+    }
+}
+
+impl StatsProvider for ReplayProvider {
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+        self.snapshots
+            .pop_front()
+            .map(|snapshot| snapshot.stats)
+            .ok_or(IfstatError::Exhausted)
+    }
+
+    fn next_snapshot(&mut self) -> NextSnapshot {
+        match self.snapshots.front() {
+            Some(snapshot) => NextSnapshot::Recorded(snapshot.elapsed()),
+            None => NextSnapshot::Exhausted,
+        }
+    }
+}
+
+/// In-memory provider for tests: returns the given snapshots in order.
+///
+/// By default the snapshots are live data. With `with_interval` they are treated as recorded
+/// at a fixed interval, which makes the resulting rates deterministic.
+#[derive(Debug, Clone, Default)]
+pub struct MockProvider {
+    snapshots: VecDeque<IndexMap<String, (u64, u64)>>,
+    interval: Option<Duration>,
+    taken: u32,
+}
+
+impl MockProvider {
+    /// Creates a provider returning the given snapshots in order, then ending.
+    pub fn new(snapshots: Vec<IndexMap<String, (u64, u64)>>) -> MockProvider {
+        MockProvider {
+            snapshots: snapshots.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Treats the snapshots as recorded `interval` apart.
+    pub fn with_interval(mut self, interval: Duration) -> MockProvider {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Appends a snapshot.
+    pub fn push(&mut self, snapshot: IndexMap<String, (u64, u64)>) {
+        self.snapshots.push_back(snapshot);
+    }
+}
+
+impl StatsProvider for MockProvider {
+    fn get_net_dev_stats(&mut self) -> Result<IndexMap<String, (u64, u64)>, IfstatError> {
+        let snapshot = self.snapshots.pop_front().ok_or(IfstatError::Exhausted)?;
+        self.taken += 1;
+        Ok(snapshot)
+    }
+
+    fn next_snapshot(&mut self) -> NextSnapshot {
+        match (self.snapshots.is_empty(), self.interval) {
+            (true, _) => NextSnapshot::Exhausted,
+            (false, Some(interval)) => NextSnapshot::Recorded(interval * self.taken),
+            (false, None) => NextSnapshot::Live,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+// qdisc.rs
+// This module reads traffic-control statistics of qdiscs and classes via rtnetlink
+// (RTM_GETQDISC and RTM_GETTCLASS), showing where shaping drops or delays packets.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::error::IfstatError;
+
+/// Size of `struct tcmsg`.
+#[cfg(target_os = "linux")]
+const TCMSG_LEN: usize = 20;
+/// TCA_STATS2 nested attribute with `struct gnet_stats_basic`.
+#[cfg(target_os = "linux")]
+const TCA_STATS_BASIC: u16 = 1;
+/// TCA_STATS2 nested attribute with `struct gnet_stats_queue`.
+#[cfg(target_os = "linux")]
+const TCA_STATS_QUEUE: u16 = 3;
+/// Handle of the root qdisc of an interface.
+const TC_H_ROOT: u32 = 0xffff_ffff;
+
+/// Counters of one qdisc or class.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TcStats {
+    /// Interface index the qdisc or class is attached to.
+    pub ifindex: u32,
+    /// Interface name, empty if unknown.
+    pub interface: String,
+    /// True for a class, false for a qdisc.
+    pub class: bool,
+    /// Type of the qdisc, e.g. `fq_codel` or `htb`.
+    pub kind: String,
+    pub handle: u32,
+    pub parent: u32,
+    pub bytes: u64,
+    pub packets: u64,
+    pub drops: u64,
+    pub overlimits: u64,
+    pub requeues: u64,
+    /// Bytes currently queued.
+    pub backlog: u64,
+    /// Packets currently queued.
+    pub qlen: u64,
+}
+
+impl TcStats {
+    /// Returns the key identifying the same qdisc or class in consecutive snapshots.
+    fn key(&self) -> (u32, bool, u32, u32) {
+        (self.ifindex, self.class, self.handle, self.parent)
+    }
+}
+
+/// Rates of one qdisc or class over an interval, with the current queue.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct TcRates {
+    pub interface: String,
+    pub class: bool,
+    pub kind: String,
+    /// Handle as shown by `tc`, e.g. `1:10`.
+    pub handle: String,
+    /// Parent handle as shown by `tc`, e.g. `root`.
+    pub parent: String,
+    pub bytes_per_s: f64,
+    pub packets_per_s: f64,
+    pub drops_per_s: f64,
+    pub overlimits_per_s: f64,
+    pub requeues_per_s: f64,
+    pub backlog: u64,
+    pub qlen: u64,
+}
+
+/// Formats a traffic-control handle like `tc`: `major:minor` in hex, `major:` or `root`.
+pub fn format_tc_handle(handle: u32) -> String {
+    match handle {
+        TC_H_ROOT => "root".to_string(),
+        _ if handle & 0xffff == 0 => format!("{:x}:", handle >> 16),
+        _ => format!("{:x}:{:x}", handle >> 16, handle & 0xffff),
+    }
+}
+
+/// Parses an RTM_NEWQDISC or RTM_NEWTCLASS message payload (`struct tcmsg` and attributes).
+///
+/// Returns `None` for other message types or truncated messages. The interface name is left
+/// empty.
+#[cfg(target_os = "linux")]
+pub fn parse_tc_message(msg_type: u16, payload: &[u8]) -> Option<TcStats> {
+    use super::netlink::{attr_str, find_attr, nl_align, parse_attrs, read_u32, read_u64};
+
+    let class = match msg_type {
+        libc::RTM_NEWQDISC => false,
+        libc::RTM_NEWTCLASS => true,
+        _ => return None,
+    };
+    if payload.len() < TCMSG_LEN {
+        return None;
+    }
+    let attrs = parse_attrs(&payload[nl_align(TCMSG_LEN)..]);
+    let mut stats = TcStats {
+        ifindex: read_u32(payload, 4),
+        class,
+        kind: find_attr(&attrs, libc::TCA_KIND)
+            .map(attr_str)
+            .unwrap_or_default(),
+        handle: read_u32(payload, 8),
+        parent: read_u32(payload, 12),
+        ..Default::default()
+    };
+
+    if let Some(stats2) = find_attr(&attrs, libc::TCA_STATS2) {
+        let nested = parse_attrs(stats2);
+        // struct gnet_stats_basic { __u64 bytes; __u32 packets; }
+        if let Some(basic) = find_attr(&nested, TCA_STATS_BASIC).filter(|b| b.len() >= 12) {
+            stats.bytes = read_u64(basic, 0);
+            stats.packets = read_u32(basic, 8) as u64;
+        }
+        // struct gnet_stats_queue { qlen, backlog, drops, requeues, overlimits }
+        if let Some(queue) = find_attr(&nested, TCA_STATS_QUEUE).filter(|q| q.len() >= 20) {
+            stats.qlen = read_u32(queue, 0) as u64;
+            stats.backlog = read_u32(queue, 4) as u64;
+            stats.drops = read_u32(queue, 8) as u64;
+            stats.requeues = read_u32(queue, 12) as u64;
+            stats.overlimits = read_u32(queue, 16) as u64;
+        }
+    }
+    Some(stats)
+}
+
+/// Dumps the qdiscs and classes of the given interfaces, or of all interfaces if `interfaces` is
+/// empty.
+#[cfg(target_os = "linux")]
+pub fn get_tc_stats(interfaces: &[String]) -> Result<Vec<TcStats>, IfstatError> {
+    use super::netlink::{dump_links, NetlinkSocket};
+
+    let names: IndexMap<u32, String> = dump_links()?
+        .iter()
+        .filter_map(|link| Some((link.index(), link.name()?)))
+        .filter(|(_, name)| interfaces.is_empty() || interfaces.contains(name))
+        .collect();
+
+    let mut socket = NetlinkSocket::route()?;
+    let mut messages = socket.dump(libc::RTM_GETQDISC, &[0u8; TCMSG_LEN])?;
+    // Classes can only be dumped per interface
+    for ifindex in names.keys() {
+        let mut request = [0u8; TCMSG_LEN];
+        request[4..8].copy_from_slice(&ifindex.to_ne_bytes());
+        messages.extend(socket.dump(libc::RTM_GETTCLASS, &request)?);
+    }
+
+    Ok(messages
+        .iter()
+        .filter_map(|m| parse_tc_message(m.msg_type, &m.payload))
+        .filter_map(|mut stats| {
+            stats.interface = names.get(&stats.ifindex)?.clone();
+            Some(stats)
+        })
+        .collect())
+}
+
+/// Dumps the qdiscs and classes of the given interfaces (Linux only).
+#[cfg(not(target_os = "linux"))]
+pub fn get_tc_stats(_interfaces: &[String]) -> Result<Vec<TcStats>, IfstatError> {
+    Err(IfstatError::Unsupported(
+        "Traffic-control statistics are only available on Linux",
+    ))
+}
+
+/// Computes per-second rates for every qdisc and class in `current`, in its order.
+///
+/// Entries missing from `previous` and counters that went backwards yield zero rates.
+pub fn compute_tc_rates(
+    previous: &[TcStats],
+    current: &[TcStats],
+    interval_secs: f64,
+) -> Vec<TcRates> {
+    let previous: IndexMap<_, &TcStats> = previous.iter().map(|s| (s.key(), s)).collect();
+    current
+        .iter()
+        .map(|cur| {
+            let prev = previous.get(&cur.key()).copied().unwrap_or(cur);
+            let rate = |prev: u64, cur: u64| cur.saturating_sub(prev) as f64 / interval_secs;
+            TcRates {
+                interface: cur.interface.clone(),
+                class: cur.class,
+                kind: cur.kind.clone(),
+                handle: format_tc_handle(cur.handle),
+                parent: format_tc_handle(cur.parent),
+                bytes_per_s: rate(prev.bytes, cur.bytes),
+                packets_per_s: rate(prev.packets, cur.packets),
+                drops_per_s: rate(prev.drops, cur.drops),
+                overlimits_per_s: rate(prev.overlimits, cur.overlimits),
+                requeues_per_s: rate(prev.requeues, cur.requeues),
+                backlog: cur.backlog,
+                qlen: cur.qlen,
+            }
+        })
+        .collect()
+}
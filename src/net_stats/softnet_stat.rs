@@ -0,0 +1,121 @@
+// softnet_stat.rs
+// This module parses the per-CPU packet processing counters of `/proc/net/softnet_stat`.
+
+use std::io::BufRead;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::error::IfstatError;
+
+/// Packet processing counters of one CPU.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SoftnetStat {
+    /// Packets processed by the network stack.
+    pub processed: u64,
+    /// Packets dropped because the backlog queue was full.
+    pub dropped: u64,
+    /// Times the NET_RX softirq ran out of budget or time with work remaining.
+    pub time_squeeze: u64,
+}
+
+/// Per-second rates of the counters of one CPU.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct SoftnetRates {
+    pub processed_per_s: f64,
+    pub dropped_per_s: f64,
+    pub time_squeeze_per_s: f64,
+}
+
+/// Parses per-CPU counters from a given reader in the format of `/proc/net/softnet_stat`.
+///
+/// Every line holds the hexadecimal counters of one online CPU. Since Linux 5.10 the 13th column
+/// is the CPU number; for older kernels the line number is used, which is only correct as long
+/// as no CPU is offline.
+pub fn parse_softnet_stat<R: BufRead>(
+    reader: R,
+) -> Result<IndexMap<u32, SoftnetStat>, IfstatError> {
+    let mut stats = IndexMap::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = || {
+            IfstatError::InvalidData(format!(
+                "Invalid softnet_stat line {}: '{}'",
+                index + 1,
+                line
+            ))
+        };
+
+        let columns = line
+            .split_whitespace()
+            .map(|column| u64::from_str_radix(column, 16))
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| invalid())?;
+        if columns.len() < 3 {
+            return Err(invalid());
+        }
+        let cpu = match columns.get(12) {
+            Some(&cpu) => u32::try_from(cpu).map_err(|_| invalid())?,
+            None => index as u32,
+        };
+        stats.insert(
+            cpu,
+            SoftnetStat {
+                processed: columns[0],
+                dropped: columns[1],
+                time_squeeze: columns[2],
+            },
+        );
+    }
+    Ok(stats)
+}
+
+/// Retrieves the per-CPU counters of the system from `/proc/net/softnet_stat`.
+#[cfg(target_os = "linux")]
+pub fn get_softnet_stat() -> Result<IndexMap<u32, SoftnetStat>, IfstatError> {
+    let file = std::fs::File::open("/proc/net/softnet_stat")?;
+    parse_softnet_stat(std::io::BufReader::new(file))
+        .map_err(|e| e.context("/proc/net/softnet_stat"))
+}
+
+/// Retrieves the per-CPU counters of the system (Linux only).
+#[cfg(not(target_os = "linux"))]
+pub fn get_softnet_stat() -> Result<IndexMap<u32, SoftnetStat>, IfstatError> {
+    Err(IfstatError::Unsupported(
+        "softnet statistics are only available on Linux",
+    ))
+}
+
+/// Computes per-second rates for every CPU present in both snapshots.
+///
+/// The counters are 32 bit in the kernel, so a counter that went backwards is assumed to have
+/// wrapped around.
+pub fn compute_softnet_rates(
+    previous: &IndexMap<u32, SoftnetStat>,
+    current: &IndexMap<u32, SoftnetStat>,
+    interval_secs: f64,
+) -> IndexMap<u32, SoftnetRates> {
+    let delta = |prev: u64, cur: u64| {
+        let diff = if cur >= prev {
+            cur - prev
+        } else {
+            (cur + (1 << 32)).saturating_sub(prev)
+        };
+        diff as f64 / interval_secs
+    };
+    current
+        .iter()
+        .filter_map(|(cpu, cur)| {
+            let prev = previous.get(cpu)?;
+            let rates = SoftnetRates {
+                processed_per_s: delta(prev.processed, cur.processed),
+                dropped_per_s: delta(prev.dropped, cur.dropped),
+                time_squeeze_per_s: delta(prev.time_squeeze, cur.time_squeeze),
+            };
+            Some((*cpu, rates))
+        })
+        .collect()
+}
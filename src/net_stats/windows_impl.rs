@@ -17,6 +17,9 @@ use windows::Win32::{
     Networking::WinSock::AF_UNSPEC,
 };
 
+use super::{to_byte_stats, NetDevCounters};
+use crate::error::IfstatError;
+
 #[derive(Debug)]
 struct SomeError;
 
@@ -37,8 +40,16 @@ impl From<std::ffi::FromBytesWithNulError> for SomeError {
 /// # Returns
 ///
 /// A result containing an IndexMap where the keys are the device names and the values are tuples of (received bytes$
-/// In case of an error, returns an io::Error.
-pub fn get_net_dev_stats() -> std::result::Result<IndexMap<String, (u64, u64)>, std::io::Error> {
+/// In case of an error, returns an IfstatError.
+pub fn get_net_dev_stats() -> std::result::Result<IndexMap<String, (u64, u64)>, IfstatError> {
+    get_net_dev_counters().map(|counters| to_byte_stats(&counters))
+}
+
+/// Retrieves all counters of all network devices that the interface table provides.
+///
+/// FIFO, frame, compressed and carrier counters are not available and reported as zero.
+pub fn get_net_dev_counters() -> std::result::Result<IndexMap<String, NetDevCounters>, IfstatError>
+{
     let mut size = 0;
 
     unsafe {
@@ -48,7 +59,8 @@ pub fn get_net_dev_stats() -> std::result::Result<IndexMap<String, (u64, u64)>,
             return Err(io::Error::new(
                 io::ErrorKind::Other,
                 "Failed to get buffer size for network interface table",
-            ));
+            )
+            .into());
         }
     }
 
@@ -63,7 +75,8 @@ pub fn get_net_dev_stats() -> std::result::Result<IndexMap<String, (u64, u64)>,
             return Err(io::Error::new(
                 io::ErrorKind::Other,
                 "Failed to get network interface table",
-            ));
+            )
+            .into());
         }
 
         let table_ref = &*table;
@@ -84,11 +97,19 @@ pub fn get_net_dev_stats() -> std::result::Result<IndexMap<String, (u64, u64)>,
                 .trim()
                 .to_string();
 
-            // Retrieve received and transmitted bytes
-            let rx_bytes = row.dwInOctets as u64;
-            let tx_bytes = row.dwOutOctets as u64;
-
-            stats.insert(iface_name, (rx_bytes, tx_bytes));
+            let counters = NetDevCounters {
+                rx_bytes: row.dwInOctets as u64,
+                rx_packets: row.dwInUcastPkts as u64 + row.dwInNUcastPkts as u64,
+                rx_errors: row.dwInErrors as u64,
+                rx_dropped: row.dwInDiscards as u64,
+                tx_bytes: row.dwOutOctets as u64,
+                tx_packets: row.dwOutUcastPkts as u64 + row.dwOutNUcastPkts as u64,
+                tx_errors: row.dwOutErrors as u64,
+                tx_dropped: row.dwOutDiscards as u64,
+                ..Default::default()
+            };
+
+            stats.insert(iface_name, counters);
         }
 
         Ok(stats)
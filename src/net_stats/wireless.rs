@@ -0,0 +1,148 @@
+// wireless.rs
+// This module parses the link quality and discarded-packet counters of `/proc/net/wireless`.
+
+use std::io::BufRead;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::error::IfstatError;
+
+/// Link quality and counters of one wireless interface.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WirelessStats {
+    /// Device-dependent status word.
+    pub status: u16,
+    /// Link quality, on a driver-specific scale (often 0 to 70).
+    pub link: f64,
+    /// Signal level, usually in dBm.
+    pub level: f64,
+    /// Noise level, usually in dBm. -256 means unknown.
+    pub noise: f64,
+    /// Packets discarded because of a wrong network id.
+    pub discarded_nwid: u64,
+    /// Packets that could not be decrypted.
+    pub discarded_crypt: u64,
+    /// Packets that could not be reassembled.
+    pub discarded_frag: u64,
+    /// Packets discarded after too many retries.
+    pub discarded_retry: u64,
+    /// Packets discarded for other reasons.
+    pub discarded_misc: u64,
+    /// Missed beacons.
+    pub missed_beacon: u64,
+}
+
+impl WirelessStats {
+    /// Returns the sum of all discarded-packet counters.
+    pub fn discarded(&self) -> u64 {
+        self.discarded_nwid
+            + self.discarded_crypt
+            + self.discarded_frag
+            + self.discarded_retry
+            + self.discarded_misc
+    }
+}
+
+/// Link quality and discarded packets per second of one wireless interface.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct WirelessRates {
+    pub link: f64,
+    pub level: f64,
+    pub noise: f64,
+    pub discarded_per_s: f64,
+    pub missed_beacon_per_s: f64,
+}
+
+/// Parses wireless interface statistics from a given reader in the format of `/proc/net/wireless`.
+///
+/// The two header lines are skipped. Quality values may carry a trailing `.`, which the kernel
+/// uses to mark values updated since the last read.
+pub fn parse_wireless_stats<R: BufRead>(
+    reader: R,
+) -> Result<IndexMap<String, WirelessStats>, IfstatError> {
+    let mut stats = IndexMap::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.contains('|') || line.trim().is_empty() {
+            continue;
+        }
+        let invalid =
+            || IfstatError::InvalidData(format!("Invalid wireless line {}: '{}'", index + 1, line));
+
+        let (name, values) = line.split_once(':').ok_or_else(invalid)?;
+        let fields: Vec<&str> = values.split_whitespace().collect();
+        if fields.len() < 10 {
+            return Err(invalid());
+        }
+        let parsed = parse_fields(&fields).ok_or_else(invalid)?;
+        stats.insert(name.trim().to_string(), parsed);
+    }
+    Ok(stats)
+}
+
+/// Parses the values after the interface name, `None` if any is invalid.
+fn parse_fields(fields: &[&str]) -> Option<WirelessStats> {
+    let quality = |field: &str| field.trim_end_matches('.').parse::<f64>().ok();
+    let counter = |field: &str| field.parse::<u64>().ok();
+    Some(WirelessStats {
+        status: u16::from_str_radix(fields[0], 16).ok()?,
+        link: quality(fields[1])?,
+        level: quality(fields[2])?,
+        noise: quality(fields[3])?,
+        discarded_nwid: counter(fields[4])?,
+        discarded_crypt: counter(fields[5])?,
+        discarded_frag: counter(fields[6])?,
+        discarded_retry: counter(fields[7])?,
+        discarded_misc: counter(fields[8])?,
+        missed_beacon: counter(fields[9])?,
+    })
+}
+
+/// Retrieves the wireless interface statistics of the system from `/proc/net/wireless`.
+///
+/// Returns an empty map if the file doesn't exist, i.e. there are no wireless interfaces.
+#[cfg(target_os = "linux")]
+pub fn get_wireless_stats() -> Result<IndexMap<String, WirelessStats>, IfstatError> {
+    match std::fs::File::open("/proc/net/wireless") {
+        Ok(file) => parse_wireless_stats(std::io::BufReader::new(file))
+            .map_err(|e| e.context("/proc/net/wireless")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(IndexMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Retrieves the wireless interface statistics of the system (Linux only).
+#[cfg(not(target_os = "linux"))]
+pub fn get_wireless_stats() -> Result<IndexMap<String, WirelessStats>, IfstatError> {
+    Err(IfstatError::Unsupported(
+        "Wireless statistics are only available on Linux",
+    ))
+}
+
+/// Combines the current quality values with the discarded-packet rates since `previous`.
+///
+/// Interfaces missing from `previous` (e.g. after reconnecting) and counters that went backwards
+/// yield zero rates.
+pub fn compute_wireless_rates(
+    previous: &IndexMap<String, WirelessStats>,
+    current: &IndexMap<String, WirelessStats>,
+    interval_secs: f64,
+) -> IndexMap<String, WirelessRates> {
+    current
+        .iter()
+        .map(|(iface, cur)| {
+            let prev = previous.get(iface).unwrap_or(cur);
+            let rates = WirelessRates {
+                link: cur.link,
+                level: cur.level,
+                noise: cur.noise,
+                discarded_per_s: cur.discarded().saturating_sub(prev.discarded()) as f64
+                    / interval_secs,
+                missed_beacon_per_s: cur.missed_beacon.saturating_sub(prev.missed_beacon) as f64
+                    / interval_secs,
+            };
+            (iface.clone(), rates)
+        })
+        .collect()
+}
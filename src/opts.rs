@@ -1,5 +1,9 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
 use std::env;
+use std::path::PathBuf;
+
+use crate::quota::{parse_quota, Quota};
 
 const AUTHOR: &str = env!("CARGO_PKG_AUTHORS");
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -40,33 +44,221 @@ lazy_static::lazy_static! {
 #[derive(Parser)]
 #[clap(version = VERSION, author = AUTHOR, long_version = LONG_VERSION.as_str())]
 pub struct Opts {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Profile of the configuration file to use, on top of its default section
+    #[arg(short = 'p', long, global = true)]
+    pub profile: Option<String>,
+
+    /// Configuration file [default: $XDG_CONFIG_HOME/ifstat-rs/config.toml]
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
     /// Interfaces to monitor, separated by commas (e.g., "eth0,lo")
-    #[clap(short, long)]
+    #[clap(short, long, global = true)]
     pub interfaces: Option<String>,
 
     /// Enables monitoring of all interfaces found for which statistics are available.
-    #[clap(short = 'a')]
+    #[clap(short = 'a', long = "all", global = true)]
     pub monitor_all: bool,
 
     /// Enables monitoring of loopback interfaces for which statistics are available.
-    #[clap(short = 'l')]
+    #[clap(short = 'l', long = "loopback", global = true)]
     pub monitor_loopback: bool,
 
+    /// Only monitor interfaces of these kinds, separated by commas: physical, loopback, veth, bridge, bond, vlan, tun, ... (Linux)
+    #[arg(long = "type", value_delimiter = ',', conflicts_with = "replay")]
+    pub types: Vec<String>,
+
+    /// Don't monitor interfaces of these kinds, separated by commas (Linux)
+    #[arg(
+        long = "exclude-type",
+        value_delimiter = ',',
+        conflicts_with = "replay"
+    )]
+    pub exclude_types: Vec<String>,
+
+    /// Only monitor interfaces in these operational states, separated by commas: up, down, unknown, ... (Linux)
+    #[arg(long = "state", value_delimiter = ',', conflicts_with = "replay")]
+    pub states: Vec<String>,
+
+    /// Only monitor interfaces using these drivers, separated by commas (Linux)
+    #[arg(long = "driver", value_delimiter = ',', conflicts_with = "replay")]
+    pub drivers: Vec<String>,
+
+    /// Show an interface under a label, e.g. enp3s0f0=uplink-a (repeatable). Overrides the alias of the interface
+    #[arg(
+        long = "label",
+        value_name = "NAME=LABEL",
+        value_parser = parse_label,
+        global = true
+    )]
+    pub labels: Vec<(String, String)>,
+
+    /// Group interfaces by topology: ports after their bridge, bond or team, and VLAN or macvlan interfaces after their lower device (Linux)
+    #[arg(long, conflicts_with = "replay")]
+    pub group: bool,
+
     /// Hides interfaces with zero counters (default false on Linux/Mac, true in Windows).
     #[clap(
         short = 'z',
+        long,
+        global = true,
         default_value_if("cfg(target_os = \"windows\")", "false", "true")
     )]
     pub hide_zero_counters: bool,
 
-    /// List all available network interfaces and exit
-    #[clap(long)]
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, global = true)]
+    pub format: OutputFormat,
+
+    /// Show the mean rates of the last N samples
+    #[arg(long, value_name = "N", value_parser = parse_positive_u64, conflicts_with = "ewma", global = true)]
+    pub average: Option<u64>,
+
+    /// Show exponentially weighted moving averages of the rates, weighting the newest sample with ALPHA (0 < ALPHA <= 1)
+    #[arg(long, value_name = "ALPHA", value_parser = parse_alpha, global = true)]
+    pub ewma: Option<f64>,
+
+    /// Also show the unsmoothed rates with --average or --ewma (not with csv)
+    #[arg(long, global = true)]
+    pub show_raw: bool,
+
+    /// Also show the highest rates of every interface (bytes, and packets for live statistics); reset them with Enter or SIGUSR1 (not with csv)
+    #[arg(long, global = true)]
+    pub peaks: bool,
+
+    /// Repeat the table headers every N lines (must be > 0)
+    #[arg(long, value_name = "N", default_value = "20", value_parser = parse_positive_u64, global = true)]
+    pub header_interval: u64,
+
+    /// Print the table headers only once
+    #[arg(short = 'n', long, conflicts_with = "header_interval", global = true)]
+    pub no_header_repeat: bool,
+
+    /// Also repeat the table headers when the terminal is resized (Unix)
+    #[arg(long, global = true)]
+    pub header_on_resize: bool,
+
+    /// Keep rewriting one table line instead of scrolling, e.g. for status panes (table only, not with --qdisc or --bonds)
+    #[arg(short = 'S', long, global = true)]
+    pub single_line: bool,
+
+    /// Draw the rates as bars per interface or as a chart scrolling one line per update, scaled to the link speed or else the highest rate seen (table only, not with --single-line)
+    #[arg(long, value_name = "MODE", global = true)]
+    pub graph: Option<GraphMode>,
+
+    /// List all available network interfaces with their metadata and exit (as an array with --format json). The metadata is that of this system, so not with --replay or --provider-path
+    #[clap(long, conflicts_with_all = ["replay", "provider_path"])]
     pub list_interfaces: bool,
 
+    /// Also show IP/TCP/UDP/ICMP rates from /proc/net/snmp and /proc/net/netstat (Linux, not with csv)
+    #[arg(long, conflicts_with = "replay")]
+    pub proto: bool,
+
+    /// Also show per-CPU processed, dropped and time_squeeze rates from /proc/net/softnet_stat (Linux, not with csv)
+    #[arg(long, conflicts_with = "replay")]
+    pub softnet: bool,
+
+    /// Also show link quality, signal, noise and discarded packets of wl* interfaces from /proc/net/wireless (Linux, not with csv)
+    #[arg(long, conflicts_with = "replay")]
+    pub wireless: bool,
+
+    /// Also show qdisc and class statistics of the monitored interfaces via rtnetlink as a sub-table (Linux, not with csv)
+    #[arg(long, conflicts_with = "replay")]
+    pub qdisc: bool,
+
+    /// Also show the traffic of monitored bond and team interfaces per member as a sub-table, flagging uneven members (Linux, not with csv)
+    #[arg(long, conflicts_with = "replay")]
+    pub bonds: bool,
+
+    /// Data quota of an interface for the daemon and query, e.g. wwan0=50GiB/month,reset-day=15 (repeatable). Periods are day or month
+    #[arg(
+        long = "quota",
+        value_name = "IFACE=SIZE/PERIOD",
+        value_parser = parse_quota,
+        global = true
+    )]
+    pub quotas: Vec<Quota>,
+
+    /// Command the daemon runs via the shell when a quota reaches 80% or 100%, with IFSTAT_INTERFACE, IFSTAT_THRESHOLD, IFSTAT_USED_BYTES and IFSTAT_LIMIT_BYTES set
+    #[arg(long, value_name = "COMMAND", global = true)]
+    pub quota_command: Option<String>,
+
+    /// Source of the statistics
+    #[arg(long, value_enum, default_value_t = ProviderKind::System, conflicts_with = "replay")]
+    pub provider: ProviderKind,
+
+    /// File in /proc/net/dev format to read with --provider proc, or directory in /sys/class/net layout to read with --provider sysfs, e.g. test fixtures
+    #[arg(long, value_name = "PATH", conflicts_with = "replay")]
+    pub provider_path: Option<PathBuf>,
+
+    /// Record every raw snapshot to this file (replaces it if it exists)
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Replay snapshots from a file written by --record instead of reading live stats
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Replay speed factor, e.g. 10 replays ten times faster than recorded (must be > 0)
+    #[arg(long, default_value = "1", value_parser = parse_positive_f64, requires = "replay")]
+    pub speed: f64,
+
     /// Delay before the first measurement in seconds (must be >= 0 if set)
     #[arg(long, value_parser = parse_non_negative_f64)]
     pub first_measurement: Option<f64>,
 
+    // Negations of the boolean options, e.g. to turn off what the configuration file turns on.
+    // Of an option and its negation, the last one given wins.
+    #[arg(long, overrides_with = "monitor_all", hide = true, global = true)]
+    pub no_all: bool,
+
+    #[arg(long, overrides_with = "monitor_loopback", hide = true, global = true)]
+    pub no_loopback: bool,
+
+    #[arg(long, overrides_with = "group", hide = true)]
+    pub no_group: bool,
+
+    #[arg(
+        long,
+        overrides_with = "hide_zero_counters",
+        hide = true,
+        global = true
+    )]
+    pub no_hide_zero_counters: bool,
+
+    #[arg(long, overrides_with = "show_raw", hide = true, global = true)]
+    pub no_show_raw: bool,
+
+    #[arg(long, overrides_with = "peaks", hide = true, global = true)]
+    pub no_peaks: bool,
+
+    #[arg(long, overrides_with = "no_header_repeat", hide = true, global = true)]
+    pub header_repeat: bool,
+
+    #[arg(long, overrides_with = "header_on_resize", hide = true, global = true)]
+    pub no_header_on_resize: bool,
+
+    #[arg(long, overrides_with = "single_line", hide = true, global = true)]
+    pub no_single_line: bool,
+
+    #[arg(long, overrides_with = "proto", hide = true)]
+    pub no_proto: bool,
+
+    #[arg(long, overrides_with = "softnet", hide = true)]
+    pub no_softnet: bool,
+
+    #[arg(long, overrides_with = "wireless", hide = true)]
+    pub no_wireless: bool,
+
+    #[arg(long, overrides_with = "qdisc", hide = true)]
+    pub no_qdisc: bool,
+
+    #[arg(long, overrides_with = "bonds", hide = true)]
+    pub no_bonds: bool,
+
     /// Delay between updates in seconds (must be > 0)
     #[arg(default_value = "1", value_parser = parse_positive_f64)]
     pub delay: f64,
@@ -76,6 +268,98 @@ pub struct Opts {
     pub count: Option<u64>,
 }
 
+#[derive(Subcommand)]
+pub enum Command {
+    /// Analyze saved `/proc/net/dev` dumps instead of live statistics
+    Analyze(AnalyzeOpts),
+    /// Account the traffic of the selected interfaces in a database, until stopped
+    Daemon(DaemonOpts),
+    /// Print the traffic accounted by the daemon
+    Query(QueryOpts),
+}
+
+#[derive(Args)]
+pub struct AnalyzeOpts {
+    /// Files with one or more `/proc/net/dev` dumps, optionally separated by timestamp lines
+    #[arg(required = true, value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// Seconds between consecutive snapshots, overrides timestamps found in the files (must be > 0)
+    #[arg(long, value_parser = parse_positive_f64)]
+    pub interval: Option<f64>,
+}
+
+#[derive(Args)]
+pub struct DaemonOpts {
+    /// Accounting database [default: $XDG_DATA_HOME/ifstat-rs/accounting.json]
+    #[arg(long, value_name = "FILE")]
+    pub db: Option<PathBuf>,
+
+    /// Seconds between updates of the database (must be > 0)
+    #[arg(long, default_value = "60", value_parser = parse_positive_f64)]
+    pub interval: f64,
+}
+
+#[derive(Args)]
+pub struct QueryOpts {
+    /// Accounting database [default: $XDG_DATA_HOME/ifstat-rs/accounting.json]
+    #[arg(long, value_name = "FILE")]
+    pub db: Option<PathBuf>,
+
+    /// What to show
+    #[arg(long, value_enum, default_value_t = AccountingPeriod::Summary)]
+    pub period: AccountingPeriod,
+
+    /// Print the used and remaining traffic of the quotas given with --quota instead
+    #[arg(long = "quotas")]
+    pub show_quotas: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AccountingPeriod {
+    /// This hour, today, this month and the total
+    Summary,
+    /// Traffic per hour of the last 48 hours (UTC)
+    Hours,
+    /// Traffic per day of the last 62 days (UTC)
+    Days,
+    /// Traffic per month of the last 24 months (UTC)
+    Months,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human-readable table in KB/s
+    Table,
+    /// One JSON object per line, rates in bytes/s
+    Json,
+    /// CSV with one row per interface and update, rates in bytes/s
+    Csv,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphMode {
+    /// Two bars per interface for received and transmitted bytes, redrawn every update
+    Bars,
+    /// One line per update with a received and a transmitted bar per interface
+    Chart,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// The platform default (/proc/net/dev on Linux)
+    System,
+    /// /proc/net/dev
+    Proc,
+    /// /sys/class/net/*/statistics (Linux)
+    Sysfs,
+    /// rtnetlink RTM_GETLINK with 64 bit counters (Linux)
+    Netlink,
+}
+
 fn parse_positive_f64(src: &str) -> Result<f64, String> {
     let val: f64 = src
         .parse()
@@ -108,3 +392,23 @@ fn parse_positive_u64(src: &str) -> Result<u64, String> {
         Ok(val)
     }
 }
+
+fn parse_alpha(src: &str) -> Result<f64, String> {
+    let val: f64 = src
+        .parse()
+        .map_err(|_| format!("`{}` is not a valid number", src))?;
+    if val <= 0.0 || val > 1.0 {
+        Err(format!("`{}` must be greater than 0 and at most 1", src))
+    } else {
+        Ok(val)
+    }
+}
+
+fn parse_label(src: &str) -> Result<(String, String), String> {
+    match src.split_once('=') {
+        Some((name, label)) if !name.is_empty() && !label.is_empty() => {
+            Ok((name.to_string(), label.to_string()))
+        }
+        _ => Err(format!("`{}` is not of the form NAME=LABEL", src)),
+    }
+}
@@ -1,8 +1,17 @@
 // This file contains standalone-useful functions.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use indexmap::IndexMap;
 
-use crate::net_stats::get_device_string_to_name_map;
+use crate::accounting::{civil_from_days, Traffic};
+use crate::bond::BondBreakdown;
+use crate::graph::{ascii_bar, block_bar, Graph};
+use crate::hierarchy::group_interfaces;
+use crate::net_stats::{InterfaceInfo, SoftnetRates, TcRates, WirelessRates};
+use crate::peaks::{InterfacePeaks, Peak};
+use crate::quota::{QuotaPeriod, QuotaStatus};
 
 #[macro_export]
 macro_rules! test_debug {
@@ -51,19 +60,262 @@ pub fn shorten_name(name: &str) -> String {
 
         // If the name doesn't match the expected pattern or prefix_len check fails
         if name.len() > 13 {
-            return format!("{}...", &name[..13]);
+            return format!("{}...", name.chars().take(13).collect::<String>());
         }
     }
     // If the name length is 16 or less, or all other conditions fail
     name.to_string()
 }
 
+/// A group of additional table columns shown after the interfaces, e.g. the TCP rates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnGroup {
+    /// Name centered above the group.
+    pub name: String,
+    /// Label of every column, at most 8 characters.
+    pub labels: Vec<&'static str>,
+}
+
+/// Table columns for `--proto`: group name and (column label, counter) pairs, covering
+/// `PROTO_RATE_COUNTERS`.
+const PROTO_GROUPS: [(&str, &[(&str, &str)]); 4] = [
+    (
+        "IP",
+        &[("in/s", "Ip.InReceives"), ("out/s", "Ip.OutRequests")],
+    ),
+    (
+        "TCP",
+        &[
+            ("in/s", "Tcp.InSegs"),
+            ("out/s", "Tcp.OutSegs"),
+            ("retr/s", "Tcp.RetransSegs"),
+            ("ldrop/s", "TcpExt.ListenDrops"),
+        ],
+    ),
+    (
+        "UDP",
+        &[
+            ("in/s", "Udp.InDatagrams"),
+            ("out/s", "Udp.OutDatagrams"),
+            ("rbuf/s", "Udp.RcvbufErrors"),
+        ],
+    ),
+    (
+        "ICMP",
+        &[("in/s", "Icmp.InMsgs"), ("out/s", "Icmp.OutMsgs")],
+    ),
+];
+
+/// Returns the table columns of the protocol rates.
+pub fn proto_column_groups() -> Vec<ColumnGroup> {
+    PROTO_GROUPS
+        .iter()
+        .map(|(name, columns)| ColumnGroup {
+            name: name.to_string(),
+            labels: columns.iter().map(|(label, _)| *label).collect(),
+        })
+        .collect()
+}
+
+/// Returns the values for the columns of `proto_column_groups`. Missing rates are 0.
+pub fn proto_column_values(rates: &IndexMap<String, f64>) -> Vec<f64> {
+    PROTO_GROUPS
+        .iter()
+        .flat_map(|(_, columns)| columns.iter())
+        .map(|(_, counter)| rates.get(*counter).copied().unwrap_or(0.0))
+        .collect()
+}
+
+/// Returns the table columns of the softnet rates of the given CPUs.
+pub fn softnet_column_groups(cpus: &[u32]) -> Vec<ColumnGroup> {
+    cpus.iter()
+        .map(|cpu| ColumnGroup {
+            name: format!("cpu{}", cpu),
+            labels: vec!["proc/s", "drop/s", "sqz/s"],
+        })
+        .collect()
+}
+
+/// Returns the values for the columns of `softnet_column_groups`. Missing CPUs are 0.
+pub fn softnet_column_values(cpus: &[u32], rates: &IndexMap<u32, SoftnetRates>) -> Vec<f64> {
+    cpus.iter()
+        .flat_map(|cpu| {
+            let r = rates.get(cpu).copied().unwrap_or_default();
+            [r.processed_per_s, r.dropped_per_s, r.time_squeeze_per_s]
+        })
+        .collect()
+}
+
+/// Returns the table columns of the link quality of the given wireless interfaces.
+pub fn wireless_column_groups(interfaces: &[String]) -> Vec<ColumnGroup> {
+    interfaces
+        .iter()
+        .map(|iface| ColumnGroup {
+            name: format!("{} wireless", shorten_name(iface)),
+            labels: vec!["link", "level", "noise", "disc/s", "beacon/s"],
+        })
+        .collect()
+}
+
+/// Returns the values for the columns of `wireless_column_groups`. Missing interfaces are 0.
+pub fn wireless_column_values(
+    interfaces: &[String],
+    rates: &IndexMap<String, WirelessRates>,
+) -> Vec<f64> {
+    interfaces
+        .iter()
+        .flat_map(|iface| {
+            let r = rates.get(iface).copied().unwrap_or_default();
+            [
+                r.link,
+                r.level,
+                r.noise,
+                r.discarded_per_s,
+                r.missed_beacon_per_s,
+            ]
+        })
+        .collect()
+}
+
+/// Returns the table columns of the unsmoothed rates of the given interfaces.
+pub fn raw_column_groups(interfaces: &[String]) -> Vec<ColumnGroup> {
+    interfaces
+        .iter()
+        .map(|iface| ColumnGroup {
+            name: format!("{} raw", shorten_name(iface)),
+            labels: vec!["KB/s in", "KB/s out"],
+        })
+        .collect()
+}
+
+/// Returns the values (in KB/s) for the columns of `raw_column_groups`. Missing interfaces are 0.
+pub fn raw_column_values(interfaces: &[String], rates: &IndexMap<String, (f64, f64)>) -> Vec<f64> {
+    interfaces
+        .iter()
+        .flat_map(|iface| {
+            let (rx, tx) = rates.get(iface).copied().unwrap_or_default();
+            [rx / 1024.0, tx / 1024.0]
+        })
+        .collect()
+}
+
+/// Returns the table columns of the peak rates of the given interfaces, with packet rates if
+/// `packets` is set.
+pub fn peak_column_groups(interfaces: &[String], packets: bool) -> Vec<ColumnGroup> {
+    let labels = match packets {
+        true => vec!["KB/s in", "KB/s out", "pk/s in", "pk/s out"],
+        false => vec!["KB/s in", "KB/s out"],
+    };
+    interfaces
+        .iter()
+        .map(|iface| ColumnGroup {
+            name: format!("{} peak", shorten_name(iface)),
+            labels: labels.clone(),
+        })
+        .collect()
+}
+
+/// Returns the values (bytes in KB/s) for the columns of `peak_column_groups`. Missing
+/// interfaces are 0.
+pub fn peak_column_values(
+    interfaces: &[String],
+    packets: bool,
+    peaks: &IndexMap<String, InterfacePeaks>,
+) -> Vec<f64> {
+    interfaces
+        .iter()
+        .flat_map(|iface| {
+            let p = peaks.get(iface).copied().unwrap_or_default();
+            let mut values = vec![
+                p.rx_bytes_per_s.value / 1024.0,
+                p.tx_bytes_per_s.value / 1024.0,
+            ];
+            if packets {
+                let value = |peak: Option<Peak>| peak.map_or(0.0, |peak| peak.value);
+                values.extend([value(p.rx_packets_per_s), value(p.tx_packets_per_s)]);
+            }
+            values
+        })
+        .collect()
+}
+
+/// Prints the peaks of the interfaces with the time each occurred (in seconds, like the `time`
+/// of the JSON output), e.g. before they are reset. Packet rates are shown if available.
+pub fn print_peak_table(
+    peaks: &IndexMap<String, InterfacePeaks>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let packets = peaks.values().any(|p| p.rx_packets_per_s.is_some());
+    let mut header = format!(
+        "  {:<16} {:>10} {:>12}  {:>10} {:>12}",
+        "peaks", "KB/s in", "at", "KB/s out", "at"
+    );
+    if packets {
+        header.push_str(&format!(
+            "  {:>10} {:>12}  {:>10} {:>12}",
+            "pk/s in", "at", "pk/s out", "at"
+        ));
+    }
+    writeln!(writer, "{}", header)?;
+    for (iface, p) in peaks {
+        let mut line = format!(
+            "  {:<16} {:>10.2} {:>12.1}  {:>10.2} {:>12.1}",
+            shorten_name(iface),
+            p.rx_bytes_per_s.value / 1024.0,
+            p.rx_bytes_per_s.at,
+            p.tx_bytes_per_s.value / 1024.0,
+            p.tx_bytes_per_s.at
+        );
+        for peak in [p.rx_packets_per_s, p.tx_packets_per_s]
+            .into_iter()
+            .flatten()
+        {
+            line.push_str(&format!("  {:>10.2} {:>12.1}", peak.value, peak.at));
+        }
+        writeln!(writer, "{}", line)?;
+    }
+    Ok(())
+}
+
 /// Prints headers for the network interface statistics table.
 pub fn print_headers(
     interfaces: &[String],
     writer: &mut dyn std::io::Write,
     hide_zero_counters: bool,
     stats: &IndexMap<String, (u64, u64)>,
+) -> std::io::Result<()> {
+    print_headers_with_columns(interfaces, writer, hide_zero_counters, stats, &[])
+}
+
+/// Prints headers for the network interface statistics table, followed by the `extra` column
+/// groups.
+pub fn print_headers_with_columns(
+    interfaces: &[String],
+    writer: &mut dyn std::io::Write,
+    hide_zero_counters: bool,
+    stats: &IndexMap<String, (u64, u64)>,
+    extra: &[ColumnGroup],
+) -> std::io::Result<()> {
+    let labels = IndexMap::new();
+    print_headers_with_labels(
+        interfaces,
+        &labels,
+        writer,
+        hide_zero_counters,
+        stats,
+        extra,
+    )
+}
+
+/// Prints headers like `print_headers_with_columns`, showing the interfaces in `labels` with
+/// their label instead of their name.
+pub fn print_headers_with_labels(
+    interfaces: &[String],
+    labels: &IndexMap<String, String>,
+    writer: &mut dyn std::io::Write,
+    hide_zero_counters: bool,
+    stats: &IndexMap<String, (u64, u64)>,
+    extra: &[ColumnGroup],
 ) -> std::io::Result<()> {
     let interfaces = if hide_zero_counters {
         filter_zero_counters(stats, interfaces)
@@ -71,30 +323,120 @@ pub fn print_headers(
         interfaces.to_vec()
     };
 
-    if interfaces.is_empty() {
+    if interfaces.is_empty() && extra.is_empty() {
         return Ok(());
     }
 
     let width = 18; // Width for each interface field including space for in/out
-    for (i, interface) in interfaces.iter().enumerate() {
-        let short_interface = shorten_name(interface);
-        let padded_name = format!("{:^width$}", short_interface, width = width);
-        write!(writer, "{}", padded_name)?;
-        if i < interfaces.len() - 1 {
-            write!(writer, "  ")?; // Additional spaces between columns
+    let mut names: Vec<String> = interfaces
+        .iter()
+        .map(|interface| {
+            let label = match labels.get(interface) {
+                Some(label) => label.clone(),
+                None => shorten_name(interface),
+            };
+            format!("{:^width$}", label, width = width)
+        })
+        .collect();
+    let mut units: Vec<String> = interfaces
+        .iter()
+        .map(|_| format!("{:>8}  {:>8}", "KB/s in", "KB/s out"))
+        .collect();
+    for group in extra {
+        let width = group.labels.len() * 10 - 2;
+        names.push(format!("{:^width$}", group.name, width = width));
+        let labels: Vec<String> = group
+            .labels
+            .iter()
+            .map(|label| format!("{:>8}", label))
+            .collect();
+        units.push(labels.join("  "));
+    }
+
+    // Additional spaces between columns
+    writeln!(writer, "{}", names.join("  "))?;
+    writeln!(writer, "{}", units.join("  "))?;
+
+    Ok(())
+}
+
+/// Decides when the table headers are repeated: every `interval` lines, and when a resize flag
+/// is set.
+#[derive(Debug, Default)]
+pub struct HeaderRepeat {
+    /// Lines between headers, `None` to never repeat them.
+    interval: Option<usize>,
+    lines_since_last_header: usize,
+    resized: Option<Arc<AtomicBool>>,
+}
+
+impl HeaderRepeat {
+    /// Repeats the headers every `interval` lines, or never if `None`.
+    pub fn new(interval: Option<usize>) -> HeaderRepeat {
+        HeaderRepeat {
+            interval,
+            ..Default::default()
         }
     }
-    writeln!(writer)?;
 
-    for (i, _) in interfaces.iter().enumerate() {
-        write!(writer, "{:>8}  {:>8}", "KB/s in", "KB/s out")?;
-        if i < interfaces.len() - 1 {
-            write!(writer, "  ")?; // Additional spaces between columns
+    /// Also repeats the headers when `resized` is set, e.g. on SIGWINCH. The flag is cleared
+    /// when the headers are printed.
+    pub fn with_resize_flag(mut self, resized: Arc<AtomicBool>) -> HeaderRepeat {
+        self.resized = Some(resized);
+        self
+    }
+
+    /// Returns true if the headers are due before the next line, starting a new count if so.
+    pub fn due(&mut self) -> bool {
+        let resized = self
+            .resized
+            .as_ref()
+            .is_some_and(|resized| resized.swap(false, Ordering::Relaxed));
+        let due = resized
+            || self
+                .interval
+                .is_some_and(|interval| self.lines_since_last_header >= interval);
+        if due {
+            self.lines_since_last_header = 0;
         }
+        due
     }
-    writeln!(writer)?;
 
-    Ok(())
+    /// Counts a printed table line.
+    pub fn line_printed(&mut self) {
+        self.lines_since_last_header += 1;
+    }
+
+    /// Makes the headers due before the next line, e.g. below a sub-table, unless they are
+    /// never repeated.
+    pub fn force(&mut self) {
+        if let Some(interval) = self.interval {
+            self.lines_since_last_header = interval;
+        }
+    }
+}
+
+/// Computes receive and transmit rates in bytes per second for the given interfaces.
+///
+/// Interfaces missing from either snapshot are skipped. Counters that went backwards (e.g. after
+/// a driver reload) yield a rate of zero.
+pub fn compute_rates(
+    previous: &IndexMap<String, (u64, u64)>,
+    current: &IndexMap<String, (u64, u64)>,
+    interfaces: &[String],
+    interval_secs: f64,
+) -> IndexMap<String, (f64, f64)> {
+    let mut rates = IndexMap::new();
+    for interface in interfaces {
+        if let (Some(&(prev_rx, prev_tx)), Some(&(cur_rx, cur_tx))) =
+            (previous.get(interface), current.get(interface))
+        {
+            let rx_rate = cur_rx.saturating_sub(prev_rx) as f64 / interval_secs;
+            let tx_rate = cur_tx.saturating_sub(prev_tx) as f64 / interval_secs;
+            rates.insert(interface.clone(), (rx_rate, tx_rate));
+        }
+    }
+    rates
 }
 
 /// Prints the network interface statistics.
@@ -111,28 +453,435 @@ pub fn print_stats(
         interfaces.to_vec()
     };
 
-    for (i, interface) in interfaces.iter().enumerate() {
-        if let (Some(&(prev_rx, prev_tx)), Some(&(cur_rx, cur_tx))) =
-            (previous.get(interface), current.get(interface))
-        {
-            let rx_kbps = (cur_rx.saturating_sub(prev_rx)) as f64 / 1024.0;
-            let tx_kbps = (cur_tx.saturating_sub(prev_tx)) as f64 / 1024.0;
-            write!(writer, "{:>8.2}  {:>8.2}", rx_kbps, tx_kbps)?;
-            if i < interfaces.len() - 1 {
-                write!(writer, "  ")?; // Additional spaces between columns
+    print_rates(&compute_rates(previous, current, &interfaces, 1.0), writer)
+}
+
+/// Prints one table line of rates (in bytes per second) as KB/s.
+pub fn print_rates(
+    rates: &IndexMap<String, (f64, f64)>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    print_rates_with_columns(rates, &[], writer)
+}
+
+/// Prints one table line of rates like `print_rates`, followed by the values of the extra
+/// columns of `print_headers_with_columns`.
+pub fn print_rates_with_columns(
+    rates: &IndexMap<String, (f64, f64)>,
+    extra: &[f64],
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let mut columns: Vec<String> = rates
+        .values()
+        .map(|(rx_rate, tx_rate)| format!("{:>8.2}  {:>8.2}", rx_rate / 1024.0, tx_rate / 1024.0))
+        .collect();
+    columns.extend(extra.iter().map(|value| format!("{:>8.2}", value)));
+    // Additional spaces between columns
+    writeln!(writer, "{}", columns.join("  "))
+}
+
+/// Returns the label of the interface in `labels`, else its shortened name.
+fn display_name(iface: &str, labels: &IndexMap<String, String>) -> String {
+    match labels.get(iface) {
+        Some(label) => label.clone(),
+        None => shorten_name(iface),
+    }
+}
+
+/// Prints the rates (in bytes per second) of `--graph bars` as a bar per direction and
+/// interface, scaled by `graph` to fit `width` characters, followed by an empty line.
+pub fn print_graph_bars(
+    rates: &IndexMap<String, (f64, f64)>,
+    labels: &IndexMap<String, String>,
+    graph: &Graph,
+    width: usize,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    // Name, direction, brackets and rate take 40 characters
+    let bar_width = width.saturating_sub(40).max(10);
+    for (iface, (rx_rate, tx_rate)) in rates {
+        let scale = graph.scale(iface);
+        let name = display_name(iface, labels);
+        for (name, direction, rate) in [(name.as_str(), "in", rx_rate), ("", "out", tx_rate)] {
+            writeln!(
+                writer,
+                "{:<16} {:<3} [{:<bar_width$}] {:>10.2} KB/s",
+                name,
+                direction,
+                ascii_bar(rate / scale, bar_width),
+                rate / 1024.0,
+                bar_width = bar_width
+            )?;
+        }
+    }
+    writeln!(writer)
+}
+
+/// Returns the width of the chart column of each of `count` interfaces and of its received
+/// half, so the columns fit `width` characters.
+fn chart_column_widths(count: usize, width: usize) -> (usize, usize) {
+    let separators = 2 * count.saturating_sub(1);
+    let column = (width.saturating_sub(separators) / count.max(1)).max(7);
+    (column, (column - 1) / 2)
+}
+
+/// Prints the header of `--graph chart`: the name and full scale of each interface over its
+/// column, with the received (`in`) and transmitted (`out`) halves.
+pub fn print_chart_header(
+    interfaces: &[String],
+    labels: &IndexMap<String, String>,
+    graph: &Graph,
+    width: usize,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let (column, rx_width) = chart_column_widths(interfaces.len(), width);
+    let tx_width = column - 1 - rx_width;
+    let names: Vec<String> = interfaces
+        .iter()
+        .map(|iface| {
+            let name = format!(
+                "{} {:.0} KB/s",
+                display_name(iface, labels),
+                graph.scale(iface) / 1024.0
+            );
+            let name: String = name.chars().take(column).collect();
+            format!("{:^column$}", name, column = column)
+        })
+        .collect();
+    let units: Vec<String> = interfaces
+        .iter()
+        .map(|_| format!("{:<rx_width$}|{:<tx_width$}", "in", "out"))
+        .collect();
+    writeln!(writer, "{}", names.join("  ").trim_end())?;
+    writeln!(writer, "{}", units.join("  ").trim_end())
+}
+
+/// Prints one line of `--graph chart` with a received and a transmitted bar per interface,
+/// scaled by `graph`, in the columns of `print_chart_header`.
+pub fn print_chart_line(
+    rates: &IndexMap<String, (f64, f64)>,
+    graph: &Graph,
+    width: usize,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let (column, rx_width) = chart_column_widths(rates.len(), width);
+    let columns: Vec<String> = rates
+        .iter()
+        .map(|(iface, (rx_rate, tx_rate))| {
+            let scale = graph.scale(iface);
+            format!(
+                "{}|{}",
+                block_bar(rx_rate / scale, rx_width),
+                block_bar(tx_rate / scale, column - 1 - rx_width)
+            )
+        })
+        .collect();
+    writeln!(writer, "{}", columns.join("  ").trim_end())
+}
+
+/// Prints rates (in bytes per second) as one JSON object per line.
+///
+/// `time` is the time of the measurement in seconds, if known.
+pub fn print_rates_json(
+    time: Option<f64>,
+    rates: &IndexMap<String, (f64, f64)>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    print_rates_json_with_extra(
+        time,
+        rates,
+        &IndexMap::new(),
+        serde_json::Map::new(),
+        writer,
+    )
+}
+
+/// Prints rates like `print_rates_json`, adding a `label` to the interfaces in `labels` and the
+/// `extra` members (e.g. `protocols`) to the object.
+pub fn print_rates_json_with_extra(
+    time: Option<f64>,
+    rates: &IndexMap<String, (f64, f64)>,
+    labels: &IndexMap<String, String>,
+    extra: serde_json::Map<String, serde_json::Value>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let interfaces: serde_json::Map<String, serde_json::Value> = rates
+        .iter()
+        .map(|(iface, (rx_rate, tx_rate))| {
+            let mut rates =
+                serde_json::json!({ "rx_bytes_per_s": rx_rate, "tx_bytes_per_s": tx_rate });
+            if let Some(label) = labels.get(iface) {
+                rates["label"] = serde_json::json!(label);
             }
+            (iface.clone(), rates)
+        })
+        .collect();
+    let mut line = serde_json::json!({ "time": time, "interfaces": interfaces });
+    line.as_object_mut().unwrap().extend(extra);
+    writeln!(writer, "{}", line)
+}
+
+/// Prints qdisc and class rates as a sub-table below a table line, grouped by interface.
+///
+/// Backlog is in bytes, qlen in packets.
+pub fn print_tc_table(rates: &[TcRates], writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "  {:<16} {:<5} {:<10} {:>7} {:>7}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}  {:>6}",
+        "interface",
+        "type",
+        "kind",
+        "handle",
+        "parent",
+        "KB/s",
+        "pkts/s",
+        "drops/s",
+        "ovrl/s",
+        "requ/s",
+        "backlog",
+        "qlen"
+    )?;
+    let mut previous_interface = None;
+    for r in rates {
+        // Show the interface name only on the first line of its group
+        let interface = if previous_interface == Some(&r.interface) {
+            String::new()
+        } else {
+            shorten_name(&r.interface)
+        };
+        previous_interface = Some(&r.interface);
+        writeln!(
+            writer,
+            "  {:<16} {:<5} {:<10} {:>7} {:>7}  {:>8.2}  {:>8.2}  {:>8.2}  {:>8.2}  {:>8.2}  {:>8}  {:>6}",
+            interface,
+            if r.class { "class" } else { "qdisc" },
+            r.kind,
+            r.handle,
+            r.parent,
+            r.bytes_per_s / 1024.0,
+            r.packets_per_s,
+            r.drops_per_s,
+            r.overlimits_per_s,
+            r.requeues_per_s,
+            r.backlog,
+            r.qlen
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints the traffic of bonds as a sub-table below a table line, each bond followed by its
+/// members. Members with an uneven share are marked.
+pub fn print_bond_table(
+    bonds: &[BondBreakdown],
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    for bond in bonds {
+        let name = match &bond.mode {
+            Some(mode) => format!("{} ({})", shorten_name(&bond.name), mode),
+            None => shorten_name(&bond.name),
+        };
+        writeln!(
+            writer,
+            "  {:<30} {:>8.2}  {:>8.2}  {:>6}",
+            name,
+            bond.rx_bytes_per_s / 1024.0,
+            bond.tx_bytes_per_s / 1024.0,
+            "share"
+        )?;
+        for member in &bond.members {
+            let line = format!(
+                "    {:<28} {:>8.2}  {:>8.2}  {:>5.1}%  {}",
+                shorten_name(&member.name),
+                member.rx_bytes_per_s / 1024.0,
+                member.tx_bytes_per_s / 1024.0,
+                member.share * 100.0,
+                if member.diverging { "uneven" } else { "" }
+            );
+            writeln!(writer, "{}", line.trim_end())?;
         }
     }
-    writeln!(writer)?;
+    Ok(())
+}
 
+/// Formats a byte count with binary units, e.g. `1.2 TiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
+/// Prints the accounted traffic of every interface, one row per period as returned by
+/// `InterfaceAccount::rows`.
+pub fn print_accounting_table(
+    rows: &IndexMap<String, IndexMap<String, Traffic>>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    for (iface, periods) in rows {
+        writeln!(writer, "{}", iface)?;
+        for (period, traffic) in periods {
+            let period = match period.as_str() {
+                "hour" => "this hour",
+                "day" => "today",
+                "month" => "this month",
+                period => period,
+            };
+            writeln!(
+                writer,
+                "  {:<14} {:>10} in / {:>10} out",
+                format!("{}:", period),
+                format_bytes(traffic.rx_bytes),
+                format_bytes(traffic.tx_bytes)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Formats seconds since the Unix epoch as UTC date and time, e.g. `2024-08-01 12:00`.
+pub fn format_utc(epoch_secs: u64) -> String {
+    let (year, month, day) = civil_from_days((epoch_secs / 86400) as i64);
+    let minutes = epoch_secs % 86400 / 60;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// Formats the usage of a quota, e.g. `wwan0 monthly quota: 41.2 GiB of 50.0 GiB used (82.4%),
+/// 8.8 GiB left until 2024-09-15 00:00 UTC, used up around 2024-09-10 14:00 at the current
+/// rate`.
+pub fn format_quota_status(status: &QuotaStatus) -> String {
+    let period = match status.period {
+        QuotaPeriod::Day => "daily",
+        QuotaPeriod::Month => "monthly",
+    };
+    let mut text = format!(
+        "{} {} quota: {} of {} used ({:.1}%), {} left until {} UTC",
+        status.interface,
+        period,
+        format_bytes(status.used_bytes),
+        format_bytes(status.limit_bytes),
+        status.percent,
+        format_bytes(status.remaining_bytes),
+        format_utc(status.period_end)
+    );
+    if let Some(at) = status.exhausted_at {
+        text.push_str(&format!(
+            ", used up around {} at the current rate",
+            format_utc(at)
+        ));
+    }
+    text
+}
+
+/// Prints the usage of quotas as CSV, times in seconds since the Unix epoch.
+pub fn print_quotas_csv(
+    statuses: &[QuotaStatus],
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "interface,period,limit_bytes,used_bytes,remaining_bytes,period_start,period_end,exhausted_at"
+    )?;
+    for s in statuses {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{}",
+            s.interface,
+            s.period.name(),
+            s.limit_bytes,
+            s.used_bytes,
+            s.remaining_bytes,
+            s.period_start,
+            s.period_end,
+            s.exhausted_at.map(|at| at.to_string()).unwrap_or_default()
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints the accounted traffic of every interface as CSV, one row per interface and period.
+pub fn print_accounting_csv(
+    rows: &IndexMap<String, IndexMap<String, Traffic>>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    writeln!(writer, "interface,period,rx_bytes,tx_bytes")?;
+    for (iface, periods) in rows {
+        for (period, traffic) in periods {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                iface, period, traffic.rx_bytes, traffic.tx_bytes
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the CSV header matching `print_rates_csv`.
+pub fn print_csv_header(writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+    writeln!(writer, "time,interface,rx_bytes_per_s,tx_bytes_per_s,label")
+}
+
+/// Prints rates (in bytes per second) as CSV, one row per interface. The label of interfaces not
+/// in `labels` is empty.
+pub fn print_rates_csv(
+    time: Option<f64>,
+    rates: &IndexMap<String, (f64, f64)>,
+    labels: &IndexMap<String, String>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let time = time.map(|t| format!("{:.3}", t)).unwrap_or_default();
+    for (iface, (rx_rate, tx_rate)) in rates {
+        writeln!(
+            writer,
+            "{},{},{:.2},{:.2},{}",
+            time,
+            csv_field(iface),
+            rx_rate,
+            tx_rate,
+            csv_field(labels.get(iface).map(String::as_str).unwrap_or_default())
+        )?;
+    }
     Ok(())
 }
 
-// Prints the names of network devices.
-pub fn print_net_devices(stats: &IndexMap<String, (u64, u64)>) {
-    // Get the map of device strings to human-readable names.
-    let adapter_name_map = get_device_string_to_name_map();
-    if adapter_name_map.len() > 0 {
+/// Quotes a CSV field if necessary.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Prints the names of network devices, with their metadata where available. With `group`, ports
+// and stacked interfaces are indented below their upper device.
+pub fn print_net_devices(
+    stats: &IndexMap<String, (u64, u64)>,
+    infos: &IndexMap<String, InterfaceInfo>,
+    group: bool,
+) {
+    // Get the map of device strings to human-readable names, i.e. of the adapter GUIDs. Elsewhere
+    // the friendly names are part of the metadata table.
+    #[cfg(target_os = "windows")]
+    let adapter_name_map = crate::net_stats::get_device_string_to_name_map();
+    #[cfg(not(target_os = "windows"))]
+    let adapter_name_map: IndexMap<String, String> = IndexMap::new();
+    if !adapter_name_map.is_empty() {
         println!("{} adapters:", adapter_name_map.len());
         for guid in adapter_name_map.keys() {
             if let Some(friendly_name) = adapter_name_map.get(guid) {
@@ -144,6 +893,16 @@ pub fn print_net_devices(stats: &IndexMap<String, (u64, u64)>) {
     // Print the number of interfaces.
     println!("{} interfaces:", stats.len());
 
+    if !infos.is_empty() {
+        let interfaces: Vec<String> = stats.keys().cloned().collect();
+        let grouped = match group {
+            true => group_interfaces(&interfaces, infos),
+            false => interfaces.into_iter().map(|iface| (iface, 0)).collect(),
+        };
+        print_interface_tree(&grouped, infos, &mut std::io::stdout()).unwrap();
+        return;
+    }
+
     // Iterate over the keys (interface names) in the stats HashMap.
     for iface in stats.keys() {
         // Try to get the human-readable name from the adapter_name_map.
@@ -153,3 +912,64 @@ pub fn print_net_devices(stats: &IndexMap<String, (u64, u64)>) {
         }
     }
 }
+
+/// Prints a table with the metadata of the interfaces in `stats`. Unknown values are shown as `-`.
+pub fn print_interface_info(
+    stats: &IndexMap<String, (u64, u64)>,
+    infos: &IndexMap<String, InterfaceInfo>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let grouped: Vec<(String, usize)> = stats.keys().map(|iface| (iface.clone(), 0)).collect();
+    print_interface_tree(&grouped, infos, writer)
+}
+
+/// Prints the metadata table of interfaces with their depth (see `group_interfaces`), indenting
+/// the names by two spaces per level.
+pub fn print_interface_tree(
+    grouped: &[(String, usize)],
+    infos: &IndexMap<String, InterfaceInfo>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    writeln!(
+        writer,
+        "{:<16} {:>5}  {:<8} {:<17} {:>6} {:>6}  {:<10} {:<9} {:<16} ADDRESSES",
+        "NAME", "INDEX", "STATE", "MAC", "MTU", "SPEED", "DRIVER", "KIND", "ALIAS"
+    )?;
+    for (iface, depth) in grouped {
+        let info = infos.get(iface).cloned().unwrap_or_default();
+        let line = format!(
+            "{:<16} {:>5}  {:<8} {:<17} {:>6} {:>6}  {:<10} {:<9} {:<16} {}",
+            format!("{}{}", "  ".repeat(*depth), iface),
+            or_dash((info.ifindex > 0).then(|| info.ifindex.to_string())),
+            or_dash(Some(info.operstate).filter(|s| !s.is_empty())),
+            or_dash(info.mac),
+            or_dash(info.mtu.map(|mtu| mtu.to_string())),
+            or_dash(info.speed_mbps.map(|speed| speed.to_string())),
+            or_dash(info.driver),
+            or_dash(Some(info.kind).filter(|s| !s.is_empty())),
+            or_dash(info.alias),
+            info.addresses.join(" ")
+        );
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Prints the interfaces in `stats` with their metadata as a JSON array.
+///
+/// Interfaces without metadata (e.g. on platforms other than Linux) only have a name.
+pub fn print_interface_info_json(
+    stats: &IndexMap<String, (u64, u64)>,
+    infos: &IndexMap<String, InterfaceInfo>,
+    writer: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    let interfaces: Vec<serde_json::Value> = stats
+        .keys()
+        .map(|iface| match infos.get(iface) {
+            Some(info) => serde_json::json!(info),
+            None => serde_json::json!({ "name": iface }),
+        })
+        .collect();
+    writeln!(writer, "{}", serde_json::Value::Array(interfaces))
+}
@@ -0,0 +1,134 @@
+// This module keeps the high-water marks of `--peaks`: the highest byte and packet rates of every
+// monitored interface and when they occurred, until they are reset.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::output::compute_rates;
+use crate::sampler::Sample;
+
+/// The highest value of a rate and the time of the sample it occurred in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct Peak {
+    pub value: f64,
+    /// Time of the sample in seconds, like the `time` of the JSON output.
+    pub at: f64,
+}
+
+impl Peak {
+    /// Raises the peak to `value` if it is higher.
+    pub fn update(&mut self, value: f64, at: f64) {
+        if value > self.value {
+            *self = Peak { value, at };
+        }
+    }
+}
+
+/// Peaks of the rates of one interface. The packet rates are `None` if packet counters are not
+/// available, e.g. when replaying a recording.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct InterfacePeaks {
+    pub rx_bytes_per_s: Peak,
+    pub tx_bytes_per_s: Peak,
+    pub rx_packets_per_s: Option<Peak>,
+    pub tx_packets_per_s: Option<Peak>,
+}
+
+/// Keeps the running maximum of the rates of a set of interfaces.
+#[derive(Debug, Clone)]
+pub struct PeakTracker {
+    interfaces: Vec<String>,
+    /// Previous packet counters (received, transmitted) per interface, if packet rates are
+    /// tracked.
+    packets: Option<IndexMap<String, (u64, u64)>>,
+    peaks: IndexMap<String, InterfacePeaks>,
+}
+
+impl PeakTracker {
+    /// Tracks the byte rates of `interfaces`.
+    pub fn new(interfaces: &[String]) -> PeakTracker {
+        let peaks = interfaces
+            .iter()
+            .map(|iface| (iface.clone(), InterfacePeaks::default()))
+            .collect();
+        PeakTracker {
+            interfaces: interfaces.to_vec(),
+            packets: None,
+            peaks,
+        }
+    }
+
+    /// Also tracks the packet rates, starting from the packet counters `baseline`.
+    pub fn with_packets(mut self, baseline: IndexMap<String, (u64, u64)>) -> PeakTracker {
+        for peaks in self.peaks.values_mut() {
+            peaks.rx_packets_per_s = Some(Peak::default());
+            peaks.tx_packets_per_s = Some(Peak::default());
+        }
+        self.packets = Some(baseline);
+        self
+    }
+
+    /// Returns true if packet rates are tracked.
+    pub fn has_packets(&self) -> bool {
+        self.packets.is_some()
+    }
+
+    /// Returns the monitored interfaces.
+    pub fn interfaces(&self) -> &[String] {
+        &self.interfaces
+    }
+
+    /// Raises the peaks to the rates of `sample` and, if packet rates are tracked, to the packet
+    /// rates since the previous `packets` counters.
+    pub fn update(&mut self, sample: &Sample, packets: Option<IndexMap<String, (u64, u64)>>) {
+        let at = sample.at.as_secs_f64();
+        for (iface, (rx, tx)) in sample.rates(&self.interfaces) {
+            if let Some(peaks) = self.peaks.get_mut(&iface) {
+                peaks.rx_bytes_per_s.update(rx, at);
+                peaks.tx_bytes_per_s.update(tx, at);
+            }
+        }
+
+        let (Some(previous), Some(current)) = (self.packets.as_mut(), packets) else {
+            return;
+        };
+        let interval_secs = sample.interval.as_secs_f64();
+        if interval_secs > 0.0 {
+            let rates = compute_rates(previous, &current, &self.interfaces, interval_secs);
+            for (iface, (rx, tx)) in rates {
+                if let Some(peaks) = self.peaks.get_mut(&iface) {
+                    if let Some(peak) = peaks.rx_packets_per_s.as_mut() {
+                        peak.update(rx, at);
+                    }
+                    if let Some(peak) = peaks.tx_packets_per_s.as_mut() {
+                        peak.update(tx, at);
+                    }
+                }
+            }
+        }
+        *previous = current;
+    }
+
+    /// Returns the current peaks per interface.
+    pub fn peaks(&self) -> &IndexMap<String, InterfacePeaks> {
+        &self.peaks
+    }
+
+    /// Resets all peaks to zero and returns the previous ones.
+    pub fn reset(&mut self) -> IndexMap<String, InterfacePeaks> {
+        let reset = self
+            .peaks
+            .iter()
+            .map(|(iface, peaks)| {
+                let zero = |peak: Option<Peak>| peak.map(|_| Peak::default());
+                let peaks = InterfacePeaks {
+                    rx_packets_per_s: zero(peaks.rx_packets_per_s),
+                    tx_packets_per_s: zero(peaks.tx_packets_per_s),
+                    ..Default::default()
+                };
+                (iface.clone(), peaks)
+            })
+            .collect();
+        std::mem::replace(&mut self.peaks, reset)
+    }
+}
@@ -0,0 +1,192 @@
+// This module provides the `ifstat_rs` Python extension module, built with the `python` feature.
+//
+// Counters and samples are returned as plain dicts so they can go straight into e.g. pandas.
+
+// The code generated by `#[pyfunction]` converts PyErr into PyErr
+#![allow(clippy::useless_conversion)]
+
+use std::io::Cursor;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use pyo3::exceptions::{PyOSError, PyStopIteration, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::error::IfstatError;
+use crate::net_stats::{self, NetDevCounters};
+use crate::sampler::{Sample, Sampler as RustSampler};
+
+impl From<IfstatError> for PyErr {
+    fn from(e: IfstatError) -> PyErr {
+        match e {
+            IfstatError::Io(e) => e.into(),
+            IfstatError::Exhausted => PyStopIteration::new_err(()),
+            IfstatError::Unsupported(_) => PyOSError::new_err(e.to_string()),
+            _ => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+fn counters_to_dict<'py>(py: Python<'py>, c: &NetDevCounters) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("rx_bytes", c.rx_bytes)?;
+    dict.set_item("rx_packets", c.rx_packets)?;
+    dict.set_item("rx_errors", c.rx_errors)?;
+    dict.set_item("rx_dropped", c.rx_dropped)?;
+    dict.set_item("rx_fifo", c.rx_fifo)?;
+    dict.set_item("rx_frame", c.rx_frame)?;
+    dict.set_item("rx_compressed", c.rx_compressed)?;
+    dict.set_item("rx_multicast", c.rx_multicast)?;
+    dict.set_item("tx_bytes", c.tx_bytes)?;
+    dict.set_item("tx_packets", c.tx_packets)?;
+    dict.set_item("tx_errors", c.tx_errors)?;
+    dict.set_item("tx_dropped", c.tx_dropped)?;
+    dict.set_item("tx_fifo", c.tx_fifo)?;
+    dict.set_item("tx_collisions", c.tx_collisions)?;
+    dict.set_item("tx_carrier", c.tx_carrier)?;
+    dict.set_item("tx_compressed", c.tx_compressed)?;
+    Ok(dict)
+}
+
+fn all_counters_to_dict<'py>(
+    py: Python<'py>,
+    counters: &IndexMap<String, NetDevCounters>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for (iface, c) in counters {
+        dict.set_item(iface, counters_to_dict(py, c)?)?;
+    }
+    Ok(dict)
+}
+
+fn sample_to_dict<'py>(
+    py: Python<'py>,
+    sample: &Sample,
+    interfaces: &Option<Vec<String>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let per_iface = PyDict::new_bound(py);
+    for (iface, s) in &sample.per_iface {
+        if interfaces
+            .as_ref()
+            .is_some_and(|names| !names.contains(iface))
+        {
+            continue;
+        }
+        let dict = PyDict::new_bound(py);
+        dict.set_item("rx_bytes", s.rx_bytes)?;
+        dict.set_item("tx_bytes", s.tx_bytes)?;
+        dict.set_item("rx_bytes_per_s", s.rx_bytes_per_s)?;
+        dict.set_item("tx_bytes_per_s", s.tx_bytes_per_s)?;
+        per_iface.set_item(iface, dict)?;
+    }
+
+    let dict = PyDict::new_bound(py);
+    dict.set_item("time", sample.at.as_secs_f64())?;
+    dict.set_item("interval", sample.interval.as_secs_f64())?;
+    dict.set_item("interfaces", per_iface)?;
+    Ok(dict)
+}
+
+fn byte_stats_to_dict<'py>(
+    py: Python<'py>,
+    stats: &IndexMap<String, (u64, u64)>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for (iface, bytes) in stats {
+        dict.set_item(iface, *bytes)?;
+    }
+    Ok(dict)
+}
+
+/// Returns {interface: (rx_bytes, tx_bytes)} of the system.
+#[pyfunction]
+fn get_net_dev_stats(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    byte_stats_to_dict(py, &net_stats::get_net_dev_stats()?)
+}
+
+/// Returns {interface: {counter: value}} with all counters of the system.
+#[pyfunction]
+fn get_net_dev_counters(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    all_counters_to_dict(py, &net_stats::get_net_dev_counters()?)
+}
+
+/// Parses the text of a `/proc/net/dev` dump into {interface: (rx_bytes, tx_bytes)}.
+#[pyfunction]
+fn parse_net_dev_stats<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyDict>> {
+    byte_stats_to_dict(py, &net_stats::parse_net_dev_stats(Cursor::new(text))?)
+}
+
+/// Parses the text of a `/proc/net/dev` dump into {interface: {counter: value}}.
+#[pyfunction]
+fn parse_net_dev_counters<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyDict>> {
+    all_counters_to_dict(py, &net_stats::parse_net_dev_counters(Cursor::new(text))?)
+}
+
+/// Iterator yielding one sample dict per `interval` seconds, `count` times or forever.
+///
+/// Each sample is {"time": s, "interval": s, "interfaces": {name: {"rx_bytes", "tx_bytes",
+/// "rx_bytes_per_s", "tx_bytes_per_s"}}}, optionally restricted to `interfaces`.
+#[pyclass(name = "Sampler")]
+struct PySampler {
+    sampler: RustSampler,
+    interfaces: Option<Vec<String>>,
+    remaining: Option<u64>,
+}
+
+#[pymethods]
+impl PySampler {
+    #[new]
+    #[pyo3(signature = (interval=1.0, count=None, interfaces=None))]
+    fn new(interval: f64, count: Option<u64>, interfaces: Option<Vec<String>>) -> PyResult<Self> {
+        let interval = Duration::try_from_secs_f64(interval)
+            .ok()
+            .filter(|d| !d.is_zero())
+            .ok_or_else(|| PyValueError::new_err("interval must be a positive number"))?;
+        Ok(PySampler {
+            sampler: RustSampler::new(interval)?,
+            interfaces,
+            remaining: count,
+        })
+    }
+
+    /// Takes a sample right away, with the rates since the previous sample.
+    fn sample<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let sample = self
+            .sampler
+            .sample_now()
+            .unwrap_or(Err(IfstatError::Exhausted))?;
+        sample_to_dict(py, &sample, &self.interfaces)
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        if self.remaining == Some(0) {
+            return Ok(None);
+        }
+        // Let other Python threads run while waiting for the next deadline
+        let sampler = &mut self.sampler;
+        let sample = match py.allow_threads(|| sampler.next()) {
+            Some(sample) => sample?,
+            None => return Ok(None),
+        };
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        sample_to_dict(py, &sample, &self.interfaces).map(Some)
+    }
+}
+
+#[pymodule]
+fn ifstat_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_function(wrap_pyfunction!(get_net_dev_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(get_net_dev_counters, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_net_dev_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_net_dev_counters, m)?)?;
+    m.add_class::<PySampler>()?;
+    Ok(())
+}
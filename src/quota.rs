@@ -0,0 +1,233 @@
+// This module tracks the data quotas of `--quota` on top of the traffic accounting, e.g.
+// `wwan0=50GiB/month,reset-day=15`: the traffic (received and transmitted) used in the current
+// period, when the quota runs out at the current rate, and the thresholds to alert on.
+//
+// Periods follow the daily buckets of the accounting database, so they start at midnight UTC.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::accounting::{civil_from_days, days_from_civil, BucketKeys, Database, InterfaceAccount};
+
+/// Percentages of a quota at which the daemon alerts, once per period each.
+pub const THRESHOLDS: [u32; 2] = [80, 100];
+
+/// How often a quota resets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaPeriod {
+    Day,
+    Month,
+}
+
+impl QuotaPeriod {
+    /// Returns the name of the period as used in `--quota`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuotaPeriod::Day => "day",
+            QuotaPeriod::Month => "month",
+        }
+    }
+}
+
+/// A data quota of one interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quota {
+    pub interface: String,
+    pub limit_bytes: u64,
+    pub period: QuotaPeriod,
+    /// Day of the month a monthly quota resets on (1-31), the last day in shorter months.
+    pub reset_day: u32,
+}
+
+/// The highest threshold reported for a quota and the start of the period it was reached in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaAlert {
+    pub period_start: u64,
+    pub threshold: u32,
+}
+
+/// Usage of a quota in its current period. Times are seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotaStatus {
+    pub interface: String,
+    pub period: QuotaPeriod,
+    pub limit_bytes: u64,
+    pub used_bytes: u64,
+    pub remaining_bytes: u64,
+    pub percent: f64,
+    pub period_start: u64,
+    pub period_end: u64,
+    /// When the quota will be used up at the average rate of the period so far, if before the
+    /// end of the period.
+    pub exhausted_at: Option<u64>,
+}
+
+/// Parses a size in bytes with an optional unit: `B`, decimal `KB` to `TB` or binary `KiB` to
+/// `TiB` (case-insensitive), e.g. `50GiB` or `1.5 TB`.
+pub fn parse_size(src: &str) -> Option<u64> {
+    let src = src.trim();
+    let split = src
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(src.len());
+    let value: f64 = src[..split].parse().ok()?;
+    let multiplier: u64 = match src[split..].trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return None,
+    };
+    Some((value * multiplier as f64).round() as u64)
+}
+
+/// Parses a quota of the form `IFACE=SIZE[/day|/month][,reset-day=N]`, monthly by default.
+pub fn parse_quota(src: &str) -> Result<Quota, String> {
+    let invalid = || {
+        format!(
+            "`{}` is not of the form IFACE=SIZE/PERIOD[,reset-day=N]",
+            src
+        )
+    };
+    let mut parts = src.split(',');
+    let (interface, amount) = parts
+        .next()
+        .and_then(|spec| spec.split_once('='))
+        .filter(|(interface, _)| !interface.is_empty())
+        .ok_or_else(invalid)?;
+    let (size, period) = amount.split_once('/').unwrap_or((amount, "month"));
+    let limit_bytes = parse_size(size)
+        .filter(|limit| *limit > 0)
+        .ok_or_else(|| format!("`{}` is not a valid size > 0, e.g. 50GiB", size))?;
+    let period = match period {
+        "day" | "daily" => QuotaPeriod::Day,
+        "month" | "monthly" => QuotaPeriod::Month,
+        _ => return Err(format!("`{}` is not a quota period: day or month", period)),
+    };
+
+    let mut reset_day = 1;
+    for option in parts {
+        match option.split_once('=') {
+            Some(("reset-day", day)) if period == QuotaPeriod::Month => {
+                reset_day = day
+                    .parse()
+                    .ok()
+                    .filter(|day| (1..=31).contains(day))
+                    .ok_or_else(|| format!("`{}` is not a day of the month", day))?;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(Quota {
+        interface: interface.to_string(),
+        limit_bytes,
+        period,
+        reset_day,
+    })
+}
+
+/// Returns the month after `month` (1-12) of `year`.
+fn next_month(year: i64, month: u32) -> (i64, u32) {
+    match month {
+        12 => (year + 1, 1),
+        month => (year, month + 1),
+    }
+}
+
+/// Returns the month before `month` (1-12) of `year`.
+fn previous_month(year: i64, month: u32) -> (i64, u32) {
+    match month {
+        1 => (year - 1, 12),
+        month => (year, month - 1),
+    }
+}
+
+impl Quota {
+    /// Returns the key of the quota in `Database::quota_alerts`, e.g. `wwan0/month`.
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.interface, self.period.name())
+    }
+
+    /// Returns the day the quota resets on in the given month, in days since the Unix epoch.
+    fn reset_in(&self, year: i64, month: u32) -> i64 {
+        let (next_year, next) = next_month(year, month);
+        let days_in_month = days_from_civil(next_year, next, 1) - days_from_civil(year, month, 1);
+        days_from_civil(year, month, self.reset_day.min(days_in_month as u32))
+    }
+
+    /// Returns the start and the end of the period containing `now`.
+    pub fn period_bounds(&self, now: u64) -> (u64, u64) {
+        let today = (now / 86400) as i64;
+        let (start, end) = match self.period {
+            QuotaPeriod::Day => (today, today + 1),
+            QuotaPeriod::Month => {
+                let (year, month, _) = civil_from_days(today);
+                let (year, month) = match today >= self.reset_in(year, month) {
+                    true => (year, month),
+                    false => previous_month(year, month),
+                };
+                let (next_year, next) = next_month(year, month);
+                (self.reset_in(year, month), self.reset_in(next_year, next))
+            }
+        };
+        (start as u64 * 86400, end as u64 * 86400)
+    }
+
+    /// Returns the usage of the quota at `now`, given the accounted traffic of its interface.
+    pub fn status(&self, account: Option<&InterfaceAccount>, now: u64) -> QuotaStatus {
+        let (period_start, period_end) = self.period_bounds(now);
+        let first_day = BucketKeys::at(period_start).day;
+        let empty = IndexMap::new();
+        let days = account.map_or(&empty, |account| &account.days);
+        let used_bytes = days
+            .iter()
+            .filter(|(day, _)| **day >= first_day)
+            .map(|(_, traffic)| traffic.rx_bytes + traffic.tx_bytes)
+            .sum();
+        let remaining_bytes = self.limit_bytes.saturating_sub(used_bytes);
+
+        let elapsed = now.saturating_sub(period_start).max(1) as f64;
+        let rate = used_bytes as f64 / elapsed;
+        let exhausted_at = (remaining_bytes > 0 && rate > 0.0)
+            .then(|| now + (remaining_bytes as f64 / rate) as u64)
+            .filter(|at| *at < period_end);
+        QuotaStatus {
+            interface: self.interface.clone(),
+            period: self.period,
+            limit_bytes: self.limit_bytes,
+            used_bytes,
+            remaining_bytes,
+            percent: used_bytes as f64 * 100.0 / self.limit_bytes as f64,
+            period_start,
+            period_end,
+            exhausted_at,
+        }
+    }
+
+    /// Returns the usage of the quota at `now` and the threshold it reached, if that threshold
+    /// was not reported yet in the current period. The reported threshold is kept in `database`.
+    pub fn check(&self, database: &mut Database, now: u64) -> Option<(QuotaStatus, u32)> {
+        let status = self.status(database.interfaces.get(&self.interface), now);
+        let threshold = THRESHOLDS
+            .into_iter()
+            .rev()
+            .find(|threshold| status.percent >= f64::from(*threshold))?;
+        let reported = database.quota_alerts.get(&self.key()).is_some_and(|alert| {
+            alert.period_start == status.period_start && alert.threshold >= threshold
+        });
+        if reported {
+            return None;
+        }
+        let alert = QuotaAlert {
+            period_start: status.period_start,
+            threshold,
+        };
+        database.quota_alerts.insert(self.key(), alert);
+        Some((status, threshold))
+    }
+}
@@ -0,0 +1,114 @@
+// This module records raw network device snapshots to a file and replays them later.
+//
+// The format is JSON Lines: every line is one self-contained snapshot, so a recording can be
+// appended to while it is being written and still be read back if the recorder was killed.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::error::IfstatError;
+
+/// A single raw snapshot as returned by `get_net_dev_stats`, stamped with a monotonic time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Microseconds since the start of the recording.
+    #[serde(rename = "t")]
+    pub elapsed_us: u64,
+    /// Interface name to (received bytes, transmitted bytes).
+    #[serde(rename = "s")]
+    pub stats: IndexMap<String, (u64, u64)>,
+}
+
+impl Snapshot {
+    /// Returns the time since the start of the recording.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_micros(self.elapsed_us)
+    }
+}
+
+/// Writes snapshots to a recording file.
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates a recorder writing to the file at `path`, replacing an existing recording.
+    ///
+    /// Timestamps start at 0 for every recorder, so appending to an earlier recording would make
+    /// them go backwards and break the rates of a replay.
+    pub fn create<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a recorder writing to `writer`. Timestamps are relative to this call.
+    pub fn new(writer: W) -> Self {
+        Recorder {
+            writer,
+            start: Instant::now(),
+        }
+    }
+
+    /// Writes one snapshot stamped with the time elapsed since the recorder was created.
+    pub fn record(&mut self, stats: &IndexMap<String, (u64, u64)>) -> std::io::Result<()> {
+        let elapsed_us = self.start.elapsed().as_micros() as u64;
+        self.write_snapshot(&Snapshot {
+            elapsed_us,
+            stats: stats.clone(),
+        })
+    }
+
+    /// Writes an already stamped snapshot.
+    pub fn write_snapshot(&mut self, snapshot: &Snapshot) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.writer, snapshot)?;
+        writeln!(self.writer)?;
+        // Flush every line so a recording survives the process being killed.
+        self.writer.flush()
+    }
+}
+
+/// Reads all snapshots from a recording file.
+pub fn read_recording<P: AsRef<Path>>(path: P) -> Result<Vec<Snapshot>, IfstatError> {
+    let path = path.as_ref();
+    File::open(path)
+        .map_err(IfstatError::from)
+        .and_then(|file| parse_recording(BufReader::new(file)))
+        .map_err(|e| e.context(path.display()))
+}
+
+/// Parses snapshots from a given reader, one JSON object per line.
+///
+/// Empty lines are ignored. A truncated last line (e.g. from a recorder that was killed while
+/// writing) is skipped, any other malformed line is an error.
+pub fn parse_recording<R: BufRead>(reader: R) -> Result<Vec<Snapshot>, IfstatError> {
+    let lines: Vec<_> = reader.lines().collect::<Result<_, _>>()?;
+    let mut snapshots = Vec::with_capacity(lines.len());
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Snapshot>(line) {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(e) if e.is_eof() && index == lines.len() - 1 => {
+                log::warn!("Ignoring truncated last line {} of recording", index + 1);
+            }
+            Err(e) => {
+                return Err(IfstatError::InvalidData(format!(
+                    "Invalid recording line {}: {}",
+                    index + 1,
+                    e
+                )));
+            }
+        }
+    }
+    Ok(snapshots)
+}
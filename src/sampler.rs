@@ -0,0 +1,307 @@
+// This module provides a reusable sampling loop on top of any `StatsProvider`.
+//
+// A `Sampler` keeps the previous snapshot, waits for the configured delay and turns each new
+// snapshot into per-interface counters and rates. It can be consumed as a blocking iterator or
+// as a tokio `Stream` via `Sampler::into_stream`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use indexmap::IndexMap;
+
+use crate::error::IfstatError;
+use crate::net_stats::{NextSnapshot, ReplayProvider, StatsProvider, SystemProvider};
+use crate::output::compute_rates;
+use crate::record::Snapshot;
+
+/// Counters and rates of one interface in a `Sample`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterfaceSample {
+    /// Received bytes counter at the time of the sample.
+    pub rx_bytes: u64,
+    /// Transmitted bytes counter at the time of the sample.
+    pub tx_bytes: u64,
+    /// Received bytes per second since the previous sample.
+    pub rx_bytes_per_s: f64,
+    /// Transmitted bytes per second since the previous sample.
+    pub tx_bytes_per_s: f64,
+}
+
+/// One measurement of all interfaces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// Time of the measurement since the sampler started (or since the start of a recording).
+    pub at: Duration,
+    /// Time since the previous measurement, used to compute the rates.
+    pub interval: Duration,
+    /// Counters and rates for every interface present in the current snapshot.
+    pub per_iface: IndexMap<String, InterfaceSample>,
+}
+
+impl Sample {
+    /// Builds a sample from two consecutive snapshots.
+    ///
+    /// Interfaces that are new in `current` get a rate of zero, as does everything if `interval`
+    /// is zero.
+    pub fn between(
+        previous: &IndexMap<String, (u64, u64)>,
+        current: &IndexMap<String, (u64, u64)>,
+        at: Duration,
+        interval: Duration,
+    ) -> Sample {
+        let interfaces: Vec<String> = current.keys().cloned().collect();
+        let rates = if interval.is_zero() {
+            IndexMap::new()
+        } else {
+            compute_rates(previous, current, &interfaces, interval.as_secs_f64())
+        };
+
+        let per_iface = current
+            .iter()
+            .map(|(iface, &(rx_bytes, tx_bytes))| {
+                let (rx_bytes_per_s, tx_bytes_per_s) =
+                    rates.get(iface).copied().unwrap_or((0.0, 0.0));
+                let sample = InterfaceSample {
+                    rx_bytes,
+                    tx_bytes,
+                    rx_bytes_per_s,
+                    tx_bytes_per_s,
+                };
+                (iface.clone(), sample)
+            })
+            .collect();
+
+        Sample {
+            at,
+            interval,
+            per_iface,
+        }
+    }
+
+    /// Returns the raw counters in the format of `get_net_dev_stats`.
+    pub fn counters(&self) -> IndexMap<String, (u64, u64)> {
+        self.per_iface
+            .iter()
+            .map(|(iface, s)| (iface.clone(), (s.rx_bytes, s.tx_bytes)))
+            .collect()
+    }
+
+    /// Returns the rates (received, transmitted bytes per second) of the given interfaces.
+    ///
+    /// Interfaces not present in the sample are skipped.
+    pub fn rates(&self, interfaces: &[String]) -> IndexMap<String, (f64, f64)> {
+        interfaces
+            .iter()
+            .filter_map(|iface| {
+                self.per_iface
+                    .get(iface)
+                    .map(|s| (iface.clone(), (s.rx_bytes_per_s, s.tx_bytes_per_s)))
+            })
+            .collect()
+    }
+}
+
+/// Periodically samples network device statistics from a `StatsProvider` and computes rates.
+pub struct Sampler {
+    provider: Box<dyn StatsProvider>,
+    delay: Duration,
+    speed: f64,
+    start: Instant,
+    baseline: IndexMap<String, (u64, u64)>,
+    previous_at: Duration,
+    next_deadline: Option<Instant>,
+}
+
+impl Sampler {
+    /// Creates a sampler reading live statistics of the system every `delay`.
+    ///
+    /// The baseline snapshot is taken immediately, the first sample follows after `delay`
+    /// (see `with_first_delay`). A zero delay is raised to 1 ms.
+    pub fn new(delay: Duration) -> Result<Sampler, IfstatError> {
+        Self::with_provider(SystemProvider, delay)
+    }
+
+    /// Creates a sampler that gets its snapshots from `fetch` instead of the system.
+    pub fn from_fn<F>(fetch: F, delay: Duration) -> Result<Sampler, IfstatError>
+    where
+        F: FnMut() -> Result<IndexMap<String, (u64, u64)>, IfstatError> + Send + 'static,
+    {
+        Self::with_provider(fetch, delay)
+    }
+
+    /// Creates a sampler that replays recorded snapshots.
+    ///
+    /// The first snapshot is the baseline. The gaps between snapshots are divided by `speed`,
+    /// so 2.0 replays twice as fast as recorded and `f64::INFINITY` replays without waiting.
+    pub fn replay(snapshots: Vec<Snapshot>, speed: f64) -> Result<Sampler, IfstatError> {
+        Ok(Self::with_provider(ReplayProvider::new(snapshots), Duration::ZERO)?.with_speed(speed))
+    }
+
+    /// Creates a sampler for any provider.
+    ///
+    /// Live providers are sampled every `delay`, recorded ones at their recorded pace.
+    pub fn with_provider<P>(provider: P, delay: Duration) -> Result<Sampler, IfstatError>
+    where
+        P: StatsProvider + 'static,
+    {
+        let mut provider: Box<dyn StatsProvider> = Box::new(provider);
+        let previous_at = match provider.next_snapshot() {
+            NextSnapshot::Live => Duration::ZERO,
+            NextSnapshot::Recorded(at) => at,
+            NextSnapshot::Exhausted => return Err(IfstatError::Exhausted),
+        };
+        let baseline = provider.get_net_dev_stats()?;
+        let start = Instant::now();
+
+        let mut sampler = Sampler {
+            provider,
+            delay: delay.max(Duration::from_millis(1)),
+            speed: 1.0,
+            start,
+            baseline,
+            previous_at,
+            next_deadline: None,
+        };
+        sampler.schedule(start);
+        Ok(sampler)
+    }
+
+    /// Sets the delay before the first sample. Only affects live providers.
+    pub fn with_first_delay(mut self, first_delay: Duration) -> Sampler {
+        if self.provider.next_snapshot() == NextSnapshot::Live {
+            self.next_deadline = Some(self.start + first_delay);
+        }
+        self
+    }
+
+    /// Sets the replay speed factor. Only affects recorded providers.
+    pub fn with_speed(mut self, speed: f64) -> Sampler {
+        self.speed = speed;
+        if let NextSnapshot::Recorded(_) = self.provider.next_snapshot() {
+            self.schedule(self.start);
+        }
+        self
+    }
+
+    /// Returns the most recent snapshot, which is the baseline before the first sample.
+    pub fn baseline(&self) -> &IndexMap<String, (u64, u64)> {
+        &self.baseline
+    }
+
+    /// Returns when the next sample is due, or `None` if the provider is exhausted.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.next_deadline
+    }
+
+    /// Takes the next sample right away, without waiting for its deadline.
+    ///
+    /// Returns `None` if the provider is exhausted. On a read error the previous snapshot is
+    /// kept, so the next sample covers the failed interval as well.
+    pub fn sample_now(&mut self) -> Option<Result<Sample, IfstatError>> {
+        let recorded_at = match self.provider.next_snapshot() {
+            NextSnapshot::Live => None,
+            NextSnapshot::Recorded(at) => Some(at),
+            NextSnapshot::Exhausted => {
+                self.next_deadline = None;
+                return None;
+            }
+        };
+        let result = self.provider.get_net_dev_stats();
+        let now = Instant::now();
+        let at = recorded_at.unwrap_or(now - self.start);
+
+        let current = match result {
+            Ok(current) => current,
+            Err(e) => {
+                self.schedule(now);
+                return Some(Err(e));
+            }
+        };
+
+        let interval = at.saturating_sub(self.previous_at);
+        let sample = Sample::between(&self.baseline, &current, at, interval);
+        self.baseline = current;
+        self.previous_at = at;
+        self.schedule(now);
+        Some(Ok(sample))
+    }
+
+    /// Turns the sampler into a tokio `Stream` of samples.
+    pub fn into_stream(self) -> SampleStream {
+        let deadline = self.next_deadline.unwrap_or(self.start);
+        SampleStream {
+            sleep: Box::pin(tokio::time::sleep_until(deadline.into())),
+            sampler: self,
+        }
+    }
+
+    /// Determines the deadline of the next sample after one was taken at `now`.
+    fn schedule(&mut self, now: Instant) {
+        self.next_deadline = match self.provider.next_snapshot() {
+            // Keep a fixed cadence, but don't try to catch up on missed ticks
+            NextSnapshot::Live => Some(match self.next_deadline {
+                Some(deadline) => (deadline + self.delay).max(now),
+                None => now + self.delay,
+            }),
+            NextSnapshot::Recorded(at) => {
+                let gap = at.saturating_sub(self.previous_at);
+                Some(now + gap.div_f64(self.speed))
+            }
+            NextSnapshot::Exhausted => None,
+        };
+    }
+}
+
+impl Iterator for Sampler {
+    type Item = Result<Sample, IfstatError>;
+
+    /// Blocks until the next sample is due and returns it.
+    fn next(&mut self) -> Option<Self::Item> {
+        let deadline = self.next_deadline()?;
+        let now = Instant::now();
+        if deadline > now {
+            std::thread::sleep(deadline - now);
+        }
+        self.sample_now()
+    }
+}
+
+/// Asynchronous stream of samples, created by `Sampler::into_stream`.
+pub struct SampleStream {
+    sampler: Sampler,
+    sleep: Pin<Box<tokio::time::Sleep>>,
+}
+
+impl SampleStream {
+    /// Returns the underlying sampler, e.g. to access the baseline.
+    pub fn sampler(&self) -> &Sampler {
+        &self.sampler
+    }
+
+    /// Waits for the next sample. Equivalent to `StreamExt::next`.
+    pub async fn next(&mut self) -> Option<Result<Sample, IfstatError>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for SampleStream {
+    type Item = Result<Sample, IfstatError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.sampler.next_deadline().is_none() {
+            return Poll::Ready(None);
+        }
+        if self.sleep.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        let item = self.sampler.sample_now();
+        if let Some(deadline) = self.sampler.next_deadline() {
+            self.sleep.as_mut().reset(deadline.into());
+        }
+        Poll::Ready(item)
+    }
+}
@@ -0,0 +1,113 @@
+// This module decides which interfaces to monitor: by name (`-i`), all (`-a`), with or without
+// loopback (`-l`), and by the type, state or driver from the interface metadata.
+
+use indexmap::IndexMap;
+
+use crate::error::IfstatError;
+use crate::net_stats::InterfaceInfo;
+use crate::opts::Opts;
+
+/// Selects interfaces by name and metadata.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InterfaceSelector {
+    /// Explicit interface names, in the order to show them.
+    pub names: Option<Vec<String>>,
+    /// Include loopback interfaces if no names are given.
+    pub include_loopback: bool,
+    /// Only interfaces of these kinds (see `InterfaceInfo::kind`).
+    pub types: Vec<String>,
+    /// No interfaces of these kinds.
+    pub exclude_types: Vec<String>,
+    /// Only interfaces in these operational states.
+    pub states: Vec<String>,
+    /// Only interfaces using these drivers.
+    pub drivers: Vec<String>,
+}
+
+impl InterfaceSelector {
+    /// Creates the selector for the command-line options.
+    pub fn from_opts(opts: &Opts) -> InterfaceSelector {
+        InterfaceSelector {
+            // -a takes precedence over -i
+            names: opts
+                .interfaces
+                .as_ref()
+                .filter(|_| !opts.monitor_all)
+                .map(|interfaces| {
+                    interfaces
+                        .split(',')
+                        .map(|s| s.trim().to_string())
+                        .collect()
+                }),
+            include_loopback: opts.monitor_all || opts.monitor_loopback,
+            types: opts.types.clone(),
+            exclude_types: opts.exclude_types.clone(),
+            states: opts.states.clone(),
+            drivers: opts.drivers.clone(),
+        }
+    }
+
+    /// Returns true if the selector filters by metadata and can't work without it.
+    pub fn needs_metadata(&self) -> bool {
+        !self.types.is_empty()
+            || !self.exclude_types.is_empty()
+            || !self.states.is_empty()
+            || !self.drivers.is_empty()
+    }
+
+    /// Returns true if the interface passes the type, state and driver filters.
+    pub fn matches(&self, info: &InterfaceInfo) -> bool {
+        let listed = |list: &[String], value: &str| list.iter().any(|v| v == value);
+        (self.types.is_empty() || listed(&self.types, &info.kind))
+            && !listed(&self.exclude_types, &info.kind)
+            && (self.states.is_empty() || listed(&self.states, &info.operstate))
+            && (self.drivers.is_empty()
+                || info
+                    .driver
+                    .as_ref()
+                    .is_some_and(|driver| listed(&self.drivers, driver)))
+    }
+
+    /// Selects interfaces among `stats`, using the metadata in `infos` if available.
+    ///
+    /// Without metadata, interfaces starting with `lo` are taken as loopback interfaces and
+    /// filtering by type, state or driver is an error.
+    pub fn select(
+        &self,
+        stats: &IndexMap<String, (u64, u64)>,
+        infos: Option<&IndexMap<String, InterfaceInfo>>,
+    ) -> Result<Vec<String>, IfstatError> {
+        if infos.is_none() && self.needs_metadata() {
+            return Err(IfstatError::Unsupported(
+                "Selecting interfaces by type, state or driver needs interface metadata, which is only available for live statistics on Linux",
+            ));
+        }
+
+        let is_loopback = |iface: &str| match infos.and_then(|infos| infos.get(iface)) {
+            Some(info) => info.kind == "loopback",
+            None => iface.starts_with("lo"),
+        };
+        // Interfaces without metadata (e.g. gone since the snapshot) don't match any filter
+        let matches = |iface: &str| match infos.and_then(|infos| infos.get(iface)) {
+            Some(info) => self.matches(info),
+            None => !self.needs_metadata(),
+        };
+
+        let candidates: Vec<String> = match &self.names {
+            Some(names) => names.clone(),
+            // Asking for loopback by type includes it
+            None if self.include_loopback || self.types.iter().any(|t| t == "loopback") => {
+                stats.keys().cloned().collect()
+            }
+            None => stats
+                .keys()
+                .filter(|iface| !is_loopback(iface))
+                .cloned()
+                .collect(),
+        };
+        Ok(candidates
+            .into_iter()
+            .filter(|iface| matches(iface))
+            .collect())
+    }
+}
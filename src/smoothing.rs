@@ -0,0 +1,77 @@
+// This module smooths the rates of consecutive samples for `--average` and `--ewma`, so short
+// intervals stay readable. A `Smoother` keeps the per-interface history across samples.
+
+use std::collections::VecDeque;
+
+use indexmap::IndexMap;
+
+use crate::sampler::Sample;
+
+/// How rates are smoothed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    /// Mean of the rates of the last `n` samples.
+    MovingAverage(usize),
+    /// Exponentially weighted moving average, weighting the newest rate with `alpha` (0 < alpha
+    /// <= 1).
+    Ewma(f64),
+}
+
+/// Smooths the rates of consecutive samples per interface.
+#[derive(Debug, Clone)]
+pub struct Smoother {
+    smoothing: Smoothing,
+    /// Last rates (received, transmitted) per interface, newest last. Only the previous average
+    /// is kept for EWMA.
+    history: IndexMap<String, VecDeque<(f64, f64)>>,
+}
+
+impl Smoother {
+    pub fn new(smoothing: Smoothing) -> Smoother {
+        Smoother {
+            smoothing,
+            history: IndexMap::new(),
+        }
+    }
+
+    /// Returns `sample` with the rates replaced by the smoothed rates, and adds its rates to the
+    /// history.
+    ///
+    /// The first sample of an interface is returned as is. Interfaces missing from a sample keep
+    /// their history.
+    pub fn smooth(&mut self, sample: &Sample) -> Sample {
+        let mut smoothed = sample.clone();
+        for (iface, s) in smoothed.per_iface.iter_mut() {
+            let rates = (s.rx_bytes_per_s, s.tx_bytes_per_s);
+            let history = self.history.entry(iface.clone()).or_default();
+            let (rx, tx) = match self.smoothing {
+                Smoothing::MovingAverage(n) => {
+                    history.push_back(rates);
+                    while history.len() > n.max(1) {
+                        history.pop_front();
+                    }
+                    let len = history.len() as f64;
+                    let (rx, tx) = history
+                        .iter()
+                        .fold((0.0, 0.0), |(rx, tx), (r, t)| (rx + r, tx + t));
+                    (rx / len, tx / len)
+                }
+                Smoothing::Ewma(alpha) => {
+                    let average = match history.back() {
+                        Some(&(rx, tx)) => (
+                            alpha * rates.0 + (1.0 - alpha) * rx,
+                            alpha * rates.1 + (1.0 - alpha) * tx,
+                        ),
+                        None => rates,
+                    };
+                    history.clear();
+                    history.push_back(average);
+                    average
+                }
+            };
+            s.rx_bytes_per_s = rx;
+            s.tx_bytes_per_s = tx;
+        }
+        smoothed
+    }
+}
@@ -0,0 +1,132 @@
+mod accounting_tests {
+    use assert_cmd::prelude::*;
+    use ifstat_rs::accounting::{civil_from_days, BucketKeys, Database, Traffic, HOURS_KEPT};
+    use ifstat_rs::opts::AccountingPeriod;
+    use ifstat_rs::output::{format_bytes, print_accounting_table};
+    use indexmap::IndexMap;
+    use std::process::Command;
+
+    // 2024-08-01T12:00:00Z
+    const NOON: u64 = 1722513600;
+
+    fn counters(rx: u64, tx: u64) -> IndexMap<String, (u64, u64)> {
+        [("wwan0".to_string(), (rx, tx))].into_iter().collect()
+    }
+
+    fn traffic(rx_bytes: u64, tx_bytes: u64) -> Traffic {
+        Traffic { rx_bytes, tx_bytes }
+    }
+
+    #[test]
+    fn test_bucket_keys() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19936), (2024, 8, 1));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(
+            BucketKeys::at(NOON + 3599),
+            BucketKeys {
+                hour: "2024-08-01T12".to_string(),
+                day: "2024-08-01".to_string(),
+                month: "2024-08".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_update_counts_deltas_and_resets() {
+        let mut database = Database::default();
+        // The first update only records the counters
+        database.update(&counters(1000, 500), NOON, Some("boot-a"));
+        assert_eq!(database.interfaces["wwan0"].total, traffic(0, 0));
+
+        database.update(&counters(3000, 800), NOON + 60, Some("boot-a"));
+        // Counter reset, e.g. the modem reconnected
+        database.update(&counters(100, 1800), NOON + 3600, Some("boot-a"));
+        // Reboot with counters already higher than before
+        database.update(&counters(5000, 5000), NOON + 86400, Some("boot-b"));
+
+        let wwan0 = &database.interfaces["wwan0"];
+        assert_eq!(wwan0.total, traffic(2000 + 100 + 5000, 300 + 1000 + 5000));
+        assert_eq!(wwan0.hours["2024-08-01T12"], traffic(2000, 300));
+        assert_eq!(wwan0.hours["2024-08-01T13"], traffic(100, 1000));
+        assert_eq!(wwan0.days["2024-08-02"], traffic(5000, 5000));
+        assert_eq!(wwan0.months["2024-08"], wwan0.total);
+        assert_eq!(database.boot_id.as_deref(), Some("boot-b"));
+        assert_eq!(database.updated, Some(NOON + 86400));
+    }
+
+    #[test]
+    fn test_old_buckets_are_dropped() {
+        let mut database = Database::default();
+        for hour in 0..60 {
+            database.update(&counters(hour * 10, 0), NOON + hour * 3600, None);
+        }
+        let hours = &database.interfaces["wwan0"].hours;
+        assert_eq!(hours.len(), HOURS_KEPT);
+        assert_eq!(hours.keys().last().unwrap(), "2024-08-03T23");
+        assert_eq!(database.interfaces["wwan0"].days.len(), 3);
+    }
+
+    #[test]
+    fn test_summary_and_table() {
+        let mut database = Database::default();
+        database.update(&counters(0, 0), NOON, None);
+        database.update(&counters(1536, 5 << 30), NOON + 60, None);
+
+        let rows: IndexMap<String, IndexMap<String, Traffic>> = database
+            .interfaces
+            .iter()
+            .map(|(iface, account)| {
+                let rows = account.rows(AccountingPeriod::Summary, NOON + 86400);
+                (iface.clone(), rows)
+            })
+            .collect();
+        // The next day, only the month and the total have traffic
+        assert_eq!(rows["wwan0"]["day"], traffic(0, 0));
+        assert_eq!(rows["wwan0"]["month"], traffic(1536, 5 << 30));
+
+        let mut output = Vec::new();
+        print_accounting_table(&rows, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "wwan0");
+        assert_eq!(
+            lines[3].split_whitespace().collect::<Vec<_>>(),
+            ["this", "month:", "1.5", "KiB", "in", "/", "5.0", "GiB", "out"]
+        );
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1_319_413_953_331), "1.2 TiB");
+    }
+
+    #[test]
+    fn test_write_read_and_query_cli() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ifstat-rs").join("accounting.json");
+        assert_eq!(Database::read(&path).unwrap(), Database::default());
+
+        let mut database = Database::default();
+        database.update(&counters(0, 0), NOON, None);
+        database.update(&counters(2048, 1024), NOON + 60, None);
+        database.write(&path).unwrap();
+        assert_eq!(Database::read(&path).unwrap(), database);
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["query", "--period", "days", "--format", "json", "--db"])
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "wwan0": { "2024-08-01": { "rx_bytes": 2048, "tx_bytes": 1024 } } })
+        );
+    }
+}
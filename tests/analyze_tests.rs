@@ -0,0 +1,106 @@
+mod analyze_tests {
+    use assert_cmd::prelude::*;
+    use ifstat_rs::analyze::{parse_snapshot_dump, parse_timestamp, snapshot_intervals};
+    use std::io::{Cursor, Write};
+    use std::process::Command;
+
+    const HEADER: &str = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+";
+
+    fn dump(timestamp: &str, eth0_rx: u64, eth0_tx: u64) -> String {
+        format!(
+            "{}\n{}    lo:  104013    1264    0    0    0     0          0         0   204386    1571    0    0    0     0       0          0
+  eth0:  {}    1264    0    0    0     0          0         0   {}    1571    0    0    0     0       0          0
+",
+            timestamp, HEADER, eth0_rx, eth0_tx
+        )
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1722513600"), Some(1722513600.0));
+        assert_eq!(parse_timestamp("1722513600.5"), Some(1722513600.5));
+        assert_eq!(parse_timestamp("2024-08-01T12:00:00Z"), Some(1722513600.0));
+        assert_eq!(parse_timestamp("2024-08-01 12:00:00"), Some(1722513600.0));
+        assert_eq!(
+            parse_timestamp("2024-08-01T14:00:00.25+02:00"),
+            Some(1722513600.25)
+        );
+        assert_eq!(
+            parse_timestamp("Thu Aug  1 12:00:00 UTC 2024"),
+            Some(1722513600.0)
+        );
+        assert_eq!(parse_timestamp("  eth0: 1 2 3"), None);
+        assert_eq!(parse_timestamp(""), None);
+    }
+
+    #[test]
+    fn test_split_snapshots() {
+        let data = format!(
+            "{}{}",
+            dump("2024-08-01T12:00:00Z", 1000, 2000),
+            dump("2024-08-01T12:00:10Z", 11240, 22480)
+        );
+        let snapshots = parse_snapshot_dump(Cursor::new(data)).unwrap();
+
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].timestamp, Some(1722513600.0));
+        assert_eq!(snapshots[1].timestamp, Some(1722513610.0));
+        assert_eq!(snapshots[0].stats["eth0"], (1000, 2000));
+        assert_eq!(snapshots[1].stats["eth0"], (11240, 22480));
+        assert_eq!(snapshots[1].stats["lo"], (104013, 204386));
+
+        let intervals = snapshot_intervals(&snapshots, None).unwrap();
+        assert_eq!(intervals, vec![10.0]);
+    }
+
+    #[test]
+    fn test_missing_timestamp_needs_interval() {
+        let data = format!("{}{}", HEADER, HEADER);
+        let snapshots = parse_snapshot_dump(Cursor::new(data)).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert!(snapshot_intervals(&snapshots, None).is_err());
+        assert_eq!(
+            snapshot_intervals(&snapshots, Some(5.0)).unwrap(),
+            vec![5.0]
+        );
+    }
+
+    #[test]
+    fn test_invalid_snapshot_is_error() {
+        let data = format!("{}  eth0:  invalid_data\n", HEADER);
+        assert!(parse_snapshot_dump(Cursor::new(data)).is_err());
+    }
+
+    #[test]
+    fn test_analyze_two_files_csv() {
+        let mut before = tempfile::NamedTempFile::new().unwrap();
+        let mut after = tempfile::NamedTempFile::new().unwrap();
+        write!(before, "{}", dump("", 1000, 2000)).unwrap();
+        write!(after, "{}", dump("", 21480, 2000)).unwrap();
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args([
+                "analyze",
+                "--interval",
+                "10",
+                "--format",
+                "csv",
+                "-i",
+                "eth0",
+            ])
+            .arg(before.path())
+            .arg(after.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(
+            stdout,
+            "time,interface,rx_bytes_per_s,tx_bytes_per_s,label\n10.000,eth0,2048.00,0.00,\n"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+mod common;
+
+mod bond_tests {
+    use crate::common::{info, infos, InfoExt};
+    use ifstat_rs::bond::{find_bonds, BondGroup};
+    use ifstat_rs::output::print_bond_table;
+    use indexmap::IndexMap;
+    use std::path::Path;
+
+    fn bond(mode: &str) -> BondGroup {
+        BondGroup {
+            name: "bond0".to_string(),
+            mode: Some(mode.to_string()),
+            members: vec!["eno1".to_string(), "eno2".to_string()],
+        }
+    }
+
+    fn rates(entries: &[(&str, f64, f64)]) -> IndexMap<String, (f64, f64)> {
+        entries
+            .iter()
+            .map(|(name, rx, tx)| (name.to_string(), (*rx, *tx)))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_bonds_from_metadata() {
+        let infos = infos([
+            info("eno1", "physical").with_master("bond0"),
+            info("eno2", "physical").with_master("bond0"),
+            info("eno3", "physical"),
+            info("bond0", "bond"),
+            info("team0", "team"),
+        ]);
+        let interfaces: Vec<String> = ["bond0", "team0", "eno3"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let bonds = find_bonds(&interfaces, &infos, Path::new("/nonexistent"));
+        assert_eq!(bonds.len(), 2);
+        assert_eq!(bonds[0].name, "bond0");
+        assert_eq!(bonds[0].members, ["eno1", "eno2"]);
+        assert_eq!(bonds[0].mode, None);
+        assert!(bonds[1].members.is_empty());
+    }
+
+    #[test]
+    fn test_find_bonds_from_sysfs() {
+        let dir = tempfile::tempdir().unwrap();
+        let bonding = dir.path().join("bond0").join("bonding");
+        std::fs::create_dir_all(&bonding).unwrap();
+        std::fs::write(bonding.join("mode"), "802.3ad 4\n").unwrap();
+        std::fs::write(bonding.join("slaves"), "eno1 eno2\n").unwrap();
+
+        let infos = infos([info("bond0", "bond")]);
+        let bonds = find_bonds(&["bond0".to_string()], &infos, dir.path());
+        assert_eq!(bonds, [bond("802.3ad")]);
+    }
+
+    #[test]
+    fn test_breakdown_flags_uneven_members() {
+        let rates = rates(&[
+            ("bond0", 100_000.0, 50_000.0),
+            ("eno1", 100_000.0, 50_000.0),
+            ("eno2", 0.0, 0.0),
+        ]);
+
+        let breakdown = bond("802.3ad").breakdown(&rates);
+        assert_eq!(breakdown.rx_bytes_per_s, 100_000.0);
+        assert_eq!(breakdown.members[0].share, 1.0);
+        assert!(breakdown.members[0].diverging);
+        assert!(breakdown.members[1].diverging);
+
+        // Active-backup bonds use one leg by design
+        let breakdown = bond("active-backup").breakdown(&rates);
+        assert!(breakdown.members.iter().all(|m| !m.diverging));
+    }
+
+    #[test]
+    fn test_breakdown_even_and_idle() {
+        let even = rates(&[("eno1", 60_000.0, 0.0), ("eno2", 40_000.0, 0.0)]);
+        let breakdown = bond("balance-rr").breakdown(&even);
+        assert_eq!(breakdown.members[0].share, 0.6);
+        assert!(breakdown.members.iter().all(|m| !m.diverging));
+
+        // Too little traffic to judge
+        let idle = rates(&[("eno1", 100.0, 0.0)]);
+        let breakdown = bond("balance-rr").breakdown(&idle);
+        assert!(breakdown.members.iter().all(|m| !m.diverging));
+    }
+
+    #[test]
+    fn test_print_bond_table() {
+        let rates = rates(&[
+            ("bond0", 20480.0, 0.0),
+            ("eno1", 20480.0, 0.0),
+            ("eno2", 0.0, 0.0),
+        ]);
+        let mut output = Vec::new();
+        print_bond_table(&[bond("802.3ad").breakdown(&rates)], &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("bond0 (802.3ad)"));
+        assert!(lines[0].contains("20.00"));
+        assert!(lines[1].starts_with("    eno1"));
+        assert!(lines[1].contains("100.0%"));
+        assert!(lines[1].ends_with("uneven"));
+        assert!(lines[2].contains("0.0%"));
+    }
+}
@@ -0,0 +1,78 @@
+/* Exercises the exported C API of libifstat_rs end to end, see tests/c_api_tests.rs. */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "ifstat_rs.h"
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+                    __LINE__, #cond);                                      \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+static int test_legacy(void) {
+    char *stats = GetNetDevStats();
+    CHECK(stats != NULL);
+    CHECK(strchr(stats, ',') != NULL);
+    FreeCString(stats);
+    FreeCString(NULL);
+    return 0;
+}
+
+static int test_interfaces(const char **first_name) {
+    size_t len = 0;
+    int32_t error = -1;
+    IfstatInterfaceV1 *ifaces = ifstat_get_interfaces_v1(&len, &error);
+    CHECK(error == IFSTAT_OK);
+    CHECK(ifaces != NULL && len > 0);
+    for (size_t i = 0; i < len; i++) {
+        CHECK(ifaces[i].name != NULL && ifaces[i].name[0] != '\0');
+    }
+    *first_name = strdup(ifaces[0].name);
+    ifstat_free_interfaces_v1(ifaces, len);
+
+    CHECK(ifstat_get_interfaces_v1(NULL, &error) == NULL);
+    CHECK(error == IFSTAT_ERR_INVALID_ARGUMENT);
+    return 0;
+}
+
+static int test_sampler(const char *name) {
+    int32_t error = -1;
+    IfstatSampler *sampler = ifstat_sampler_create(&error);
+    CHECK(error == IFSTAT_OK && sampler != NULL);
+
+    const char *names[] = {name};
+    CHECK(ifstat_sampler_set_filter(sampler, names, 1) == IFSTAT_OK);
+
+    size_t len = 0;
+    IfstatRateV1 *rates = ifstat_sampler_poll_v1(sampler, &len, &error);
+    CHECK(error == IFSTAT_OK);
+    CHECK(len == 1 && strcmp(rates[0].name, name) == 0);
+    CHECK(rates[0].rx_bytes_per_s >= 0.0 && rates[0].tx_bytes_per_s >= 0.0);
+    ifstat_free_rates_v1(rates, len);
+
+    CHECK(ifstat_sampler_set_filter(sampler, NULL, 0) == IFSTAT_OK);
+    rates = ifstat_sampler_poll_v1(sampler, &len, &error);
+    CHECK(error == IFSTAT_OK && len >= 1);
+    ifstat_free_rates_v1(rates, len);
+
+    ifstat_sampler_destroy(sampler);
+    ifstat_sampler_destroy(NULL);
+    return 0;
+}
+
+int main(void) {
+    const char *name = NULL;
+    CHECK(ifstat_abi_version() == IFSTAT_ABI_VERSION);
+    if (test_legacy() || test_interfaces(&name) || test_sampler(name)) {
+        return 1;
+    }
+    free((void *)name);
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,78 @@
+#[cfg(target_os = "linux")]
+mod c_api_tests {
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    use tempfile::tempdir;
+
+    /// Returns the directory the build script writes the header and the pkg-config file to.
+    fn out_dir() -> PathBuf {
+        PathBuf::from(env!("OUT_DIR"))
+    }
+
+    /// Returns the directory `cargo test` builds the cdylib of this run into.
+    fn lib_dir() -> PathBuf {
+        // OUT_DIR is target/<profile>/build/<pkg>-<hash>/out
+        out_dir().ancestors().nth(3).unwrap().join("deps")
+    }
+
+    #[test]
+    fn test_generated_files() {
+        let dir = out_dir();
+        let header = std::fs::read_to_string(dir.join("ifstat_rs.h")).unwrap();
+        for symbol in [
+            "GetNetDevStats",
+            "FreeCString",
+            "ifstat_get_interfaces_v1",
+            "ifstat_sampler_poll_v1",
+            "typedef struct IfstatSampler IfstatSampler;",
+        ] {
+            assert!(header.contains(symbol), "{} missing in header", symbol);
+        }
+        // Constants of other modules must not leak into the C API
+        for line in header.lines().filter(|line| line.starts_with("#define ")) {
+            assert!(line.starts_with("#define IFSTAT_"), "unexpected {}", line);
+        }
+
+        let pc = std::fs::read_to_string(dir.join("ifstat_rs.pc")).unwrap();
+        assert!(pc.contains(&format!("Version: {}", env!("CARGO_PKG_VERSION"))));
+        assert!(pc.contains("Libs: -L${libdir} -lifstat_rs"));
+    }
+
+    #[test]
+    #[cfg_attr(
+        feature = "python",
+        ignore = "the Python extension module leaves the Python symbols to the interpreter"
+    )]
+    fn test_c_program() {
+        let lib_dir = lib_dir();
+        let library = lib_dir.join("libifstat_rs.so");
+        assert!(library.exists(), "{} not built", library.display());
+
+        let build = tempdir().unwrap();
+        let program = build.path().join("ffi_test");
+        let compiled = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/c/ffi_test.c"))
+            .arg("-Wall")
+            .arg("-Werror")
+            .arg(format!("-I{}", out_dir().display()))
+            .arg(format!("-L{}", lib_dir.display()))
+            .arg("-lifstat_rs")
+            .arg("-o")
+            .arg(&program)
+            .status()
+            .expect("running the C compiler failed, set CC if it isn't cc");
+        assert!(compiled.success(), "compiling the C test program failed");
+
+        let output = Command::new(&program)
+            .env("LD_LIBRARY_PATH", &lib_dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "C test program failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+    }
+}
@@ -0,0 +1,88 @@
+// Helpers shared by the integration tests, included with `mod common;`.
+
+// Each test crate only uses some of the helpers
+#![allow(dead_code)]
+
+use ifstat_rs::net_stats::InterfaceInfo;
+use indexmap::IndexMap;
+
+/// Returns (rx_bytes, tx_bytes) of a host with `eth0` and `lo`, in this order.
+pub fn stats(eth0: (u64, u64), lo: (u64, u64)) -> IndexMap<String, (u64, u64)> {
+    vec![("eth0".to_string(), eth0), ("lo".to_string(), lo)]
+        .into_iter()
+        .collect()
+}
+
+/// Returns the metadata of an interface with only its name and kind set.
+pub fn info(name: &str, kind: &str) -> InterfaceInfo {
+    InterfaceInfo {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Sets the fields of the `InterfaceInfo` returned by `info` that tests vary.
+pub trait InfoExt {
+    fn with_master(self, master: &str) -> Self;
+    fn with_link(self, link: &str) -> Self;
+    fn with_operstate(self, operstate: &str) -> Self;
+    fn with_driver(self, driver: &str) -> Self;
+}
+
+impl InfoExt for InterfaceInfo {
+    fn with_master(self, master: &str) -> Self {
+        InterfaceInfo {
+            master: Some(master.to_string()),
+            ..self
+        }
+    }
+
+    fn with_link(self, link: &str) -> Self {
+        InterfaceInfo {
+            link: Some(link.to_string()),
+            ..self
+        }
+    }
+
+    fn with_operstate(self, operstate: &str) -> Self {
+        InterfaceInfo {
+            operstate: operstate.to_string(),
+            ..self
+        }
+    }
+
+    fn with_driver(self, driver: &str) -> Self {
+        InterfaceInfo {
+            driver: Some(driver.to_string()),
+            ..self
+        }
+    }
+}
+
+/// Collects interface metadata into a map by interface name, keeping the order.
+pub fn infos(infos: impl IntoIterator<Item = InterfaceInfo>) -> IndexMap<String, InterfaceInfo> {
+    infos
+        .into_iter()
+        .map(|info| (info.name.clone(), info))
+        .collect()
+}
+
+/// Runs ifstat-rs with `args`, sends it SIGINT after `after` and returns its output.
+#[cfg(unix)]
+pub fn run_interrupted(args: &[&str], after: std::time::Duration) -> std::process::Output {
+    use assert_cmd::prelude::*;
+    use std::process::{Command, Stdio};
+
+    let child = Command::cargo_bin("ifstat-rs")
+        .unwrap()
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(after);
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+    child.wait_with_output().unwrap()
+}
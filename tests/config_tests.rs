@@ -0,0 +1,247 @@
+mod config_tests {
+    use assert_cmd::prelude::*;
+    use clap::{CommandFactory, FromArgMatches};
+    use ifstat_rs::config::{Config, Profile};
+    use ifstat_rs::opts::{Opts, OutputFormat};
+    use std::process::Command;
+
+    const CONFIG: &str = r#"
+[default]
+hide-zero-counters = true
+format = "json"
+labels = { eth0 = "uplink" }
+
+[profiles.uplinks]
+interfaces = ["eth0", "eth1"]
+labels = { eth1 = "backup" }
+delay = 5
+count = 3
+
+[profiles.csv]
+format = "csv"
+"#;
+
+    fn parse_args(args: &[&str]) -> (Opts, clap::ArgMatches) {
+        let matches = Opts::command()
+            .try_get_matches_from(std::iter::once("ifstat-rs").chain(args.iter().copied()))
+            .unwrap();
+        (Opts::from_arg_matches(&matches).unwrap(), matches)
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(CONFIG).unwrap();
+        assert_eq!(config.default.format, Some(OutputFormat::Json));
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.profiles["uplinks"].delay, Some(5.0));
+
+        assert!(Config::parse("[default]\nunknown = 1\n").is_err());
+        assert!(Config::parse("[default]\nformat = \"xml\"\n").is_err());
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_keys_named_after_long_options() {
+        let config = Config::parse(
+            r#"
+[default]
+all = true
+loopback = true
+type = ["physical"]
+exclude-type = ["veth"]
+state = ["up"]
+driver = ["ixgbe"]
+label = { eth0 = "uplink" }
+quota = ["wwan0=50GiB/month"]
+
+[profiles.plural]
+types = ["bond"]
+exclude-types = ["tun"]
+states = ["down"]
+drivers = ["e1000e"]
+labels = { eth1 = "backup" }
+quotas = ["eth1=1TiB/month"]
+"#,
+        )
+        .unwrap();
+        let default = &config.default;
+        assert_eq!(default.all, Some(true));
+        assert_eq!(default.loopback, Some(true));
+        assert_eq!(
+            default.types.as_deref(),
+            Some(&["physical".to_string()][..])
+        );
+        assert_eq!(
+            default.exclude_types.as_deref(),
+            Some(&["veth".to_string()][..])
+        );
+        assert_eq!(default.states.as_deref(), Some(&["up".to_string()][..]));
+        assert_eq!(default.drivers.as_deref(), Some(&["ixgbe".to_string()][..]));
+        assert_eq!(default.labels.as_ref().unwrap()["eth0"], "uplink");
+        assert_eq!(default.quotas.as_ref().unwrap().len(), 1);
+
+        let plural = &config.profiles["plural"];
+        assert_eq!(plural.types.as_deref(), Some(&["bond".to_string()][..]));
+        assert_eq!(
+            plural.exclude_types.as_deref(),
+            Some(&["tun".to_string()][..])
+        );
+        assert_eq!(plural.states.as_deref(), Some(&["down".to_string()][..]));
+        assert_eq!(plural.drivers.as_deref(), Some(&["e1000e".to_string()][..]));
+        assert_eq!(plural.labels.as_ref().unwrap()["eth1"], "backup");
+        assert_eq!(plural.quotas.as_ref().unwrap().len(), 1);
+
+        // The boolean keys are the long options, which can also be given on the command line
+        let (opts, _) = parse_args(&[
+            "--all",
+            "--loopback",
+            "--hide-zero-counters",
+            "--no-header-repeat",
+        ]);
+        assert!(opts.monitor_all && opts.monitor_loopback);
+        assert!(opts.hide_zero_counters && opts.no_header_repeat);
+    }
+
+    #[test]
+    fn test_profile_on_top_of_default() {
+        let config = Config::parse(CONFIG).unwrap();
+        let profile = config.profile(Some("uplinks")).unwrap();
+        assert_eq!(profile.hide_zero_counters, Some(true));
+        assert_eq!(profile.count, Some(3));
+        let labels: Vec<(&str, &str)> = profile
+            .labels
+            .iter()
+            .flatten()
+            .map(|(name, label)| (name.as_str(), label.as_str()))
+            .collect();
+        assert_eq!(labels, [("eth0", "uplink"), ("eth1", "backup")]);
+
+        assert_eq!(config.profile(None).unwrap(), config.default);
+        assert!(config.profile(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_command_line_overrides_profile() {
+        let profile = Config::parse(CONFIG)
+            .unwrap()
+            .profile(Some("uplinks"))
+            .unwrap();
+
+        let (mut opts, matches) = parse_args(&[]);
+        profile.apply(&mut opts, &matches).unwrap();
+        assert_eq!(opts.interfaces.as_deref(), Some("eth0,eth1"));
+        assert_eq!(opts.format, OutputFormat::Json);
+        assert!(opts.hide_zero_counters);
+        assert_eq!(opts.delay, 5.0);
+        assert_eq!(opts.count, Some(3));
+
+        let (mut opts, matches) = parse_args(&[
+            "-i",
+            "eth2",
+            "--format",
+            "table",
+            "--label",
+            "eth0=core",
+            "1",
+        ]);
+        profile.apply(&mut opts, &matches).unwrap();
+        assert_eq!(opts.interfaces.as_deref(), Some("eth2"));
+        assert_eq!(opts.format, OutputFormat::Table);
+        assert_eq!(opts.delay, 1.0);
+        assert_eq!(opts.count, Some(3));
+        // Labels given on the command line come last and win
+        assert_eq!(opts.labels.last().unwrap(), &("eth0".into(), "core".into()));
+    }
+
+    #[test]
+    fn test_interfaces_override_profile_all() {
+        let profile = Profile {
+            all: Some(true),
+            ..Default::default()
+        };
+        let (mut opts, matches) = parse_args(&["-i", "lo"]);
+        profile.apply(&mut opts, &matches).unwrap();
+        assert!(!opts.monitor_all);
+        assert_eq!(opts.interfaces.as_deref(), Some("lo"));
+
+        let (mut opts, matches) = parse_args(&[]);
+        profile.apply(&mut opts, &matches).unwrap();
+        assert!(opts.monitor_all);
+    }
+
+    #[test]
+    fn test_negations_override_profile() {
+        let profile = Profile {
+            all: Some(true),
+            peaks: Some(true),
+            no_header_repeat: Some(true),
+            ..Default::default()
+        };
+        let (mut opts, matches) = parse_args(&["--no-all", "--no-peaks", "--header-repeat"]);
+        profile.apply(&mut opts, &matches).unwrap();
+        assert!(!opts.monitor_all);
+        assert!(!opts.peaks);
+        assert!(!opts.no_header_repeat);
+
+        // The last of an option and its negation wins
+        let (opts, _) = parse_args(&["--peaks", "--no-peaks"]);
+        assert!(!opts.peaks);
+        let (opts, _) = parse_args(&["--no-peaks", "--peaks"]);
+        assert!(opts.peaks);
+    }
+
+    #[test]
+    fn test_conflicting_options_override_profile() {
+        let profile = Profile {
+            single_line: Some(true),
+            no_header_repeat: Some(true),
+            ..Default::default()
+        };
+        let (mut opts, matches) = parse_args(&["--graph", "bars", "--header-interval", "5"]);
+        profile.apply(&mut opts, &matches).unwrap();
+        assert!(!opts.single_line);
+        assert!(!opts.no_header_repeat);
+        assert_eq!(opts.header_interval, 5);
+
+        let (mut opts, matches) = parse_args(&["--format", "json"]);
+        profile.apply(&mut opts, &matches).unwrap();
+        assert!(!opts.single_line);
+    }
+
+    #[test]
+    fn test_invalid_profile_values() {
+        let (mut opts, matches) = parse_args(&[]);
+        let profile = Profile {
+            delay: Some(0.0),
+            ..Default::default()
+        };
+        assert!(profile.apply(&mut opts, &matches).is_err());
+    }
+
+    #[test]
+    fn test_profile_option() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("ifstat-rs")).unwrap();
+        std::fs::write(dir.path().join("ifstat-rs").join("config.toml"), CONFIG).unwrap();
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", dir.path())
+            .args(["-p", "csv", "-a", "0.1", "1"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.starts_with("time,interface,"), "{}", stdout);
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", dir.path())
+            .args(["-p", "missing", "0.1", "1"])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("No profile named `missing`"), "{}", stderr);
+    }
+}
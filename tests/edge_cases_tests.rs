@@ -1,6 +1,9 @@
 #[cfg(target_os = "linux")]
 mod edge_cases_tests {
-    use ifstat_rs::net_stats::parse_net_dev_stats;
+    use ifstat_rs::error::IfstatError;
+    use ifstat_rs::net_stats::{
+        parse_net_dev_counters, parse_net_dev_stats, parse_net_dev_stats_lenient,
+    };
     use std::io::Cursor;
 
     #[test]
@@ -15,4 +18,79 @@ Inter-|   Receive                                                |  Transmit
 
         assert!(result.is_err());
     }
+
+    const MIXED: &str = r#"Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+   lo:  104013    1264    0    0    0     0          0         0   204386    1571    0    0    0     0       0          0
+ odd0:  1 2 3
+ eth0:  104013    1264    0    0    0     0          0         0   204386    1571    0    0    0     0       0          0
+ bad0:  x    1264    0    0    0     0          0         0   204386    1571    0    0    0     0       0          0
+garbage
+"#;
+
+    #[test]
+    fn test_strict_error_details() {
+        let err = parse_net_dev_stats(Cursor::new(MIXED)).unwrap_err();
+        match &err {
+            IfstatError::TooFewFields {
+                line,
+                interface,
+                fields,
+                text,
+            } => {
+                assert_eq!(*line, 4);
+                assert_eq!(interface, "odd0");
+                assert_eq!(*fields, 3);
+                assert_eq!(text, " odd0:  1 2 3");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert_eq!(err.line(), Some(4));
+        assert_eq!(err.interface(), Some("odd0"));
+        assert!(err.to_string().contains("3 fields"));
+    }
+
+    #[test]
+    fn test_lenient_skips_malformed_lines() {
+        let parsed = parse_net_dev_stats_lenient(Cursor::new(MIXED)).unwrap();
+
+        assert_eq!(parsed.stats.len(), 2);
+        assert_eq!(parsed.stats["lo"], (104013, 204386));
+        assert_eq!(parsed.stats["eth0"], (104013, 204386));
+
+        assert_eq!(parsed.warnings.len(), 3);
+        assert!(matches!(
+            &parsed.warnings[0],
+            IfstatError::TooFewFields { line: 4, .. }
+        ));
+        assert!(matches!(
+            &parsed.warnings[1],
+            IfstatError::InvalidCounter { line: 6, counter: "RX bytes", interface, .. } if interface == "bad0"
+        ));
+        assert!(matches!(
+            &parsed.warnings[2],
+            IfstatError::MissingColon { line: 7, text } if text == "garbage"
+        ));
+    }
+
+    #[test]
+    fn test_unread_columns_are_not_validated() {
+        let data = r#"Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+ eth0:  104013    1264    0    0    0     0          0         0   204386    n/a    0    0    0     0       0          0
+"#;
+        // The byte statistics don't read the packet columns
+        let stats = parse_net_dev_stats(Cursor::new(data)).unwrap();
+        assert_eq!(stats["eth0"], (104013, 204386));
+
+        let err = parse_net_dev_counters(Cursor::new(data)).unwrap_err();
+        assert!(matches!(
+            err,
+            IfstatError::InvalidCounter {
+                line: 3,
+                counter: "TX packets",
+                ..
+            }
+        ));
+    }
 }
@@ -0,0 +1,242 @@
+mod common;
+
+mod ffi_tests {
+    use std::ffi::CStr;
+    use std::io::Cursor;
+    use std::ptr;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use ifstat_rs::ffi::{
+        ifstat_abi_version, ifstat_free_interfaces_v1, ifstat_free_rates_v1,
+        ifstat_get_interfaces_v1, ifstat_sampler_create, ifstat_sampler_destroy,
+        ifstat_sampler_poll_v1, ifstat_sampler_set_filter, interfaces_to_c, to_c_string,
+        IfstatSampler, IFSTAT_ABI_VERSION, IFSTAT_ERR_EXHAUSTED, IFSTAT_ERR_INVALID_ARGUMENT,
+        IFSTAT_OK,
+    };
+    use ifstat_rs::net_stats::{parse_net_dev_counters, MockProvider, NetDevCounters};
+    use ifstat_rs::sampler::Sampler;
+    use indexmap::IndexMap;
+
+    use crate::common::stats;
+
+    const DATA: &str = r#"Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+   lo:  104013    1264    1    2    3     4          5         6   204386    1571    7    8    9    10      11         12
+ eth0:  1 2 3 4 5 6 7 8 9
+"#;
+
+    #[test]
+    fn test_parse_all_counters() {
+        let counters = parse_net_dev_counters(Cursor::new(DATA)).unwrap();
+        let lo = counters["lo"];
+        assert_eq!(lo.rx_bytes, 104013);
+        assert_eq!(lo.rx_packets, 1264);
+        assert_eq!(lo.rx_multicast, 6);
+        assert_eq!(lo.tx_bytes, 204386);
+        assert_eq!(lo.tx_collisions, 10);
+        assert_eq!(lo.tx_compressed, 12);
+
+        // Missing trailing columns are reported as zero
+        let eth0 = counters["eth0"];
+        assert_eq!(eth0.tx_bytes, 9);
+        assert_eq!(eth0.tx_packets, 0);
+    }
+
+    #[test]
+    fn test_records_round_trip() {
+        let mut counters = IndexMap::new();
+        counters.insert(
+            "eth0".to_string(),
+            NetDevCounters {
+                rx_bytes: 10,
+                tx_dropped: 3,
+                ..Default::default()
+            },
+        );
+        counters.insert("bad\0name".to_string(), NetDevCounters::default());
+
+        let (records, len) = interfaces_to_c(&counters);
+        assert_eq!(len, 2);
+        let slice = unsafe { std::slice::from_raw_parts(records, len) };
+        let name = |i: usize| unsafe { CStr::from_ptr(slice[i].name) }.to_str().unwrap();
+        assert_eq!(name(0), "eth0");
+        assert_eq!(slice[0].rx_bytes, 10);
+        assert_eq!(slice[0].tx_dropped, 3);
+        assert_eq!(name(1), "bad\u{FFFD}name");
+        unsafe { ifstat_free_interfaces_v1(records, len) };
+    }
+
+    #[test]
+    fn test_no_records_are_null() {
+        let (records, len) = interfaces_to_c(&IndexMap::new());
+        assert!(records.is_null());
+        assert_eq!(len, 0);
+        unsafe { ifstat_free_interfaces_v1(records, len) };
+    }
+
+    #[test]
+    fn test_to_c_string_replaces_nul() {
+        assert_eq!(to_c_string("a\0b").to_str().unwrap(), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn test_get_interfaces_arguments() {
+        assert_eq!(ifstat_abi_version(), IFSTAT_ABI_VERSION);
+
+        let mut error = -1;
+        let records = unsafe { ifstat_get_interfaces_v1(ptr::null_mut(), &mut error) };
+        assert!(records.is_null());
+        assert_eq!(error, IFSTAT_ERR_INVALID_ARGUMENT);
+
+        // Freeing null is a no-op
+        unsafe { ifstat_free_interfaces_v1(ptr::null_mut(), 0) };
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_get_interfaces_system() {
+        let mut len = 0;
+        let mut error = -1;
+        let records = unsafe { ifstat_get_interfaces_v1(&mut len, &mut error) };
+        assert_eq!(error, IFSTAT_OK);
+        assert!(len > 0);
+        let slice = unsafe { std::slice::from_raw_parts(records, len) };
+        assert!(slice.iter().all(|r| !r.name.is_null()));
+        unsafe { ifstat_free_interfaces_v1(records, len) };
+    }
+
+    fn mock_sampler(snapshots: Vec<IndexMap<String, (u64, u64)>>) -> *mut IfstatSampler {
+        let provider = MockProvider::new(snapshots).with_interval(Duration::from_secs(2));
+        let sampler = Sampler::with_provider(provider, Duration::from_secs(1)).unwrap();
+        Box::into_raw(IfstatSampler::new(sampler))
+    }
+
+    /// Polls the sampler and returns (name, rx/s, tx/s) of every record.
+    fn poll(sampler: *mut IfstatSampler) -> Result<Vec<(String, f64, f64)>, i32> {
+        let mut len = 0;
+        let mut error = -1;
+        let records = unsafe { ifstat_sampler_poll_v1(sampler, &mut len, &mut error) };
+        if error != IFSTAT_OK {
+            assert!(records.is_null());
+            return Err(error);
+        }
+        let rates = unsafe { std::slice::from_raw_parts(records, len) }
+            .iter()
+            .map(|r| {
+                let name = unsafe { CStr::from_ptr(r.name) }.to_str().unwrap();
+                (name.to_string(), r.rx_bytes_per_s, r.tx_bytes_per_s)
+            })
+            .collect();
+        unsafe { ifstat_free_rates_v1(records, len) };
+        Ok(rates)
+    }
+
+    #[test]
+    fn test_sampler_rates_and_filter() {
+        let sampler = mock_sampler(vec![
+            stats((0, 0), (0, 0)),
+            stats((2000, 1000), (20, 0)),
+            stats((4000, 1000), (40, 0)),
+            stats((4000, 1000), (40, 0)),
+            stats((4000, 1000), (40, 0)),
+        ]);
+
+        assert_eq!(
+            poll(sampler),
+            Ok(vec![
+                ("eth0".to_string(), 1000.0, 500.0),
+                ("lo".to_string(), 10.0, 0.0)
+            ])
+        );
+
+        let lo = to_c_string("lo");
+        let names = [lo.as_ptr()];
+        assert_eq!(
+            unsafe { ifstat_sampler_set_filter(sampler, names.as_ptr(), 1) },
+            IFSTAT_OK
+        );
+        assert_eq!(poll(sampler), Ok(vec![("lo".to_string(), 10.0, 0.0)]));
+
+        // The filter order wins over the order of the provider
+        let eth0 = to_c_string("eth0");
+        let names = [lo.as_ptr(), eth0.as_ptr()];
+        assert_eq!(
+            unsafe { ifstat_sampler_set_filter(sampler, names.as_ptr(), 2) },
+            IFSTAT_OK
+        );
+        assert_eq!(
+            poll(sampler),
+            Ok(vec![
+                ("lo".to_string(), 0.0, 0.0),
+                ("eth0".to_string(), 0.0, 0.0)
+            ])
+        );
+
+        assert_eq!(
+            unsafe { ifstat_sampler_set_filter(sampler, ptr::null(), 0) },
+            IFSTAT_OK
+        );
+        assert_eq!(poll(sampler).unwrap().len(), 2);
+        assert_eq!(poll(sampler), Err(IFSTAT_ERR_EXHAUSTED));
+
+        unsafe { ifstat_sampler_destroy(sampler) };
+    }
+
+    #[test]
+    fn test_sampler_invalid_arguments() {
+        let mut len = 0;
+        let mut error = -1;
+        let records = unsafe { ifstat_sampler_poll_v1(ptr::null_mut(), &mut len, &mut error) };
+        assert!(records.is_null());
+        assert_eq!(error, IFSTAT_ERR_INVALID_ARGUMENT);
+        assert_eq!(
+            unsafe { ifstat_sampler_set_filter(ptr::null_mut(), ptr::null(), 0) },
+            IFSTAT_ERR_INVALID_ARGUMENT
+        );
+        unsafe { ifstat_sampler_destroy(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn test_sampler_shared_between_threads() {
+        let snapshots = (0..=40).map(|i| stats((i * 100, 0), (0, 0))).collect();
+        // Raw pointers aren't Send, pass the address like a C caller would
+        let sampler = mock_sampler(snapshots) as usize;
+
+        let polled = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let polled = Arc::clone(&polled);
+                thread::spawn(move || {
+                    for _ in 0..10 {
+                        let rates = poll(sampler as *mut IfstatSampler).unwrap();
+                        assert_eq!(rates[0], ("eth0".to_string(), 50.0, 0.0));
+                        polled.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(polled.load(std::sync::atomic::Ordering::SeqCst), 40);
+        assert_eq!(
+            poll(sampler as *mut IfstatSampler),
+            Err(IFSTAT_ERR_EXHAUSTED)
+        );
+        unsafe { ifstat_sampler_destroy(sampler as *mut IfstatSampler) };
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sampler_system() {
+        let mut error = -1;
+        let sampler = unsafe { ifstat_sampler_create(&mut error) };
+        assert_eq!(error, IFSTAT_OK);
+        assert!(!sampler.is_null());
+        thread::sleep(Duration::from_millis(10));
+        assert!(!poll(sampler).unwrap().is_empty());
+        unsafe { ifstat_sampler_destroy(sampler) };
+    }
+}
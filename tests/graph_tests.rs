@@ -0,0 +1,149 @@
+mod graph_tests {
+    use assert_cmd::prelude::*;
+    use ifstat_rs::graph::{ascii_bar, block_bar, Graph};
+    use ifstat_rs::net_stats::InterfaceInfo;
+    use ifstat_rs::opts::GraphMode;
+    use ifstat_rs::output::{print_chart_header, print_chart_line, print_graph_bars};
+    use indexmap::IndexMap;
+    use std::io::Write;
+    use std::process::Command;
+
+    fn rates(values: &[(&str, f64, f64)]) -> IndexMap<String, (f64, f64)> {
+        values
+            .iter()
+            .map(|(iface, rx, tx)| (iface.to_string(), (*rx, *tx)))
+            .collect()
+    }
+
+    #[test]
+    fn test_bars() {
+        assert_eq!(ascii_bar(0.5, 10), "#####");
+        assert_eq!(ascii_bar(0.0, 10), "");
+        // Any traffic shows, and too high rates are cut off
+        assert_eq!(ascii_bar(0.001, 10), "#");
+        assert_eq!(ascii_bar(2.0, 4), "####");
+
+        assert_eq!(block_bar(0.5, 4), "██  ");
+        assert_eq!(block_bar(0.3, 4), "█▎  ");
+        assert_eq!(block_bar(0.001, 2), "▏ ");
+        assert_eq!(block_bar(0.0, 2), "  ");
+    }
+
+    #[test]
+    fn test_scale() {
+        let infos: IndexMap<String, InterfaceInfo> = [(
+            "eth0".to_string(),
+            InterfaceInfo {
+                speed_mbps: Some(1000),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect();
+        let mut graph = Graph::new(GraphMode::Chart).with_link_speeds(&infos);
+        assert!(graph.take_header_due());
+        assert_eq!(graph.scale("eth0"), 125_000_000.0);
+        assert_eq!(graph.scale("wlan0"), 1024.0);
+
+        // Without a link speed, the scale follows the highest rate in powers of two
+        graph.update(&rates(&[("eth0", 5e6, 0.0), ("wlan0", 100.0, 300.0)]));
+        assert!(!graph.take_header_due());
+        graph.update(&rates(&[("wlan0", 3000.0, 0.0)]));
+        assert!(graph.take_header_due());
+        assert_eq!(graph.scale("wlan0"), 4096.0);
+        graph.update(&rates(&[("wlan0", 10.0, 0.0)]));
+        assert_eq!(graph.scale("wlan0"), 4096.0);
+        assert!(!graph.take_header_due());
+    }
+
+    #[test]
+    fn test_print_graph_bars() {
+        let graph = Graph::new(GraphMode::Bars);
+        let labels: IndexMap<String, String> = [("eth0".to_string(), "uplink".to_string())]
+            .into_iter()
+            .collect();
+        let mut output = Vec::new();
+        print_graph_bars(
+            &rates(&[("eth0", 512.0, 0.0)]),
+            &labels,
+            &graph,
+            50,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "uplink           in  [#####     ]       0.50 KB/s\n\
+             \x20                out [          ]       0.00 KB/s\n\n"
+        );
+    }
+
+    #[test]
+    fn test_print_chart() {
+        let graph = Graph::new(GraphMode::Chart);
+        let interfaces = vec!["eth0".to_string(), "eth1".to_string()];
+        let mut output = Vec::new();
+        print_chart_header(&interfaces, &IndexMap::new(), &graph, 32, &mut output).unwrap();
+        print_chart_line(
+            &rates(&[("eth0", 1024.0, 512.0), ("eth1", 0.0, 0.0)]),
+            &graph,
+            32,
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "  eth0 1 KB/s      eth1 1 KB/s");
+        assert_eq!(lines[1], "in     |out      in     |out");
+        assert_eq!(lines[2], "███████|███▌            |");
+    }
+
+    /// Runs `analyze` on three snapshots of one interface with the given options.
+    fn analyze(args: &[&str]) -> std::process::Output {
+        let header = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+";
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for rx in [0, 1024, 3072] {
+            writeln!(
+                file,
+                "{}  eth0:  {}    1264    0    0    0     0          0         0   0    1571    0    0    0     0       0          0",
+                header, rx
+            )
+            .unwrap();
+        }
+        Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .env("COLUMNS", "50")
+            .args(["analyze", "--interval", "1"])
+            .args(args)
+            .arg(file.path())
+            .output()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_graph_option() {
+        let output = analyze(&["--graph", "bars"]);
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[3],
+            "eth0             in  [##########]       2.00 KB/s"
+        );
+
+        // The chart header is repeated when the scale grows to 2 KB/s
+        let output = analyze(&["--graph", "chart", "-n"]);
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(stdout.matches("|out").count(), 2);
+        assert!(stdout.contains("eth0 2 KB/s"));
+
+        let output = analyze(&["--graph", "bars", "--format", "json"]);
+        assert!(!output.status.success());
+        let output = analyze(&["--graph", "chart", "-S"]);
+        assert!(!output.status.success());
+    }
+}
@@ -0,0 +1,114 @@
+mod common;
+
+mod header_repeat_tests {
+    #[cfg(unix)]
+    use crate::common::run_interrupted;
+    use assert_cmd::prelude::*;
+    use ifstat_rs::output::HeaderRepeat;
+    use std::io::Write;
+    use std::process::Command;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// Prints `lines` table lines and returns before which of them headers were due.
+    fn due_lines(headers: &mut HeaderRepeat, lines: usize) -> Vec<usize> {
+        (0..lines)
+            .filter(|_| {
+                let due = headers.due();
+                headers.line_printed();
+                due
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_repeat_interval() {
+        let mut headers = HeaderRepeat::new(Some(3));
+        assert_eq!(due_lines(&mut headers, 7), [3, 6]);
+
+        let mut never = HeaderRepeat::new(None);
+        assert!(due_lines(&mut never, 50).is_empty());
+        never.force();
+        assert!(!never.due());
+    }
+
+    #[test]
+    fn test_force_and_resize() {
+        let resized = Arc::new(AtomicBool::new(false));
+        let mut headers = HeaderRepeat::new(Some(20)).with_resize_flag(resized.clone());
+        assert!(!headers.due());
+        headers.line_printed();
+
+        headers.force();
+        assert!(headers.due());
+        assert!(!headers.due());
+
+        resized.store(true, Ordering::Relaxed);
+        assert!(headers.due());
+        assert!(!resized.load(Ordering::Relaxed));
+        assert!(!headers.due());
+    }
+
+    /// Runs `analyze` on three snapshots of one interface with the given options.
+    fn analyze(args: &[&str]) -> std::process::Output {
+        let header = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+";
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for rx in [0, 1024, 3072] {
+            writeln!(
+                file,
+                "{}  eth0:  {}    1264    0    0    0     0          0         0   0    1571    0    0    0     0       0          0",
+                header, rx
+            )
+            .unwrap();
+        }
+        Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["analyze", "--interval", "1"])
+            .args(args)
+            .arg(file.path())
+            .output()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_header_interval_option() {
+        let output = analyze(&["--header-interval", "1"]);
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(stdout.matches("KB/s in").count(), 2);
+
+        let output = analyze(&["-n", "--header-interval", "1"]);
+        assert!(!output.status.success());
+    }
+
+    #[test]
+    fn test_single_line_option() {
+        let output = analyze(&["-S"]);
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], "\r    1.00      0.00\r    2.00      0.00");
+
+        let output = analyze(&["-S", "--format", "csv"]);
+        assert!(!output.status.success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_single_line_ends_on_interrupt() {
+        let output = run_interrupted(
+            &["-i", "lo", "-S", "0.1"],
+            std::time::Duration::from_millis(500),
+        );
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            stdout.contains('\r') && stdout.ends_with('\n'),
+            "{:?}",
+            stdout
+        );
+    }
+}
@@ -0,0 +1,114 @@
+mod common;
+
+mod hierarchy_tests {
+    use crate::common::{info, infos, InfoExt};
+    use ifstat_rs::hierarchy::{group_interfaces, group_labels};
+    use ifstat_rs::net_stats::InterfaceInfo;
+    use ifstat_rs::output::{print_headers_with_labels, print_interface_tree};
+    use indexmap::IndexMap;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// A hypervisor: guests on a bridge over a VLAN of a bond.
+    fn hypervisor() -> IndexMap<String, InterfaceInfo> {
+        infos([
+            info("lo", "loopback"),
+            info("eno1", "physical").with_master("bond0"),
+            info("eno2", "physical").with_master("bond0"),
+            info("bond0", "bond"),
+            info("bond0.10", "vlan")
+                .with_master("br10")
+                .with_link("bond0"),
+            info("br10", "bridge"),
+            info("vnet0", "tun").with_master("br10"),
+            info("vnet1", "tun").with_master("br10"),
+            info("macvtap0", "macvtap").with_link("eno2"),
+        ])
+    }
+
+    #[test]
+    fn test_group_interfaces() {
+        let interfaces: Vec<String> = hypervisor().keys().cloned().collect();
+        let grouped = group_interfaces(&interfaces, &hypervisor());
+        let expected = [
+            ("lo", 0),
+            ("bond0", 0),
+            ("eno1", 1),
+            ("eno2", 1),
+            ("macvtap0", 2),
+            ("br10", 0),
+            ("bond0.10", 1),
+            ("vnet0", 1),
+            ("vnet1", 1),
+        ];
+        let expected: Vec<(String, usize)> = expected
+            .iter()
+            .map(|(name, depth)| (name.to_string(), *depth))
+            .collect();
+        assert_eq!(grouped, expected);
+    }
+
+    #[test]
+    fn test_group_interfaces_unmonitored_parent() {
+        // The bridge isn't monitored, so its ports stay roots
+        let grouped = group_interfaces(&names(&["vnet1", "eno1", "vnet0"]), &hypervisor());
+        let order: Vec<&str> = grouped.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(order, ["vnet1", "eno1", "vnet0"]);
+        assert!(grouped.iter().all(|(_, depth)| *depth == 0));
+    }
+
+    #[test]
+    fn test_group_interfaces_loop_and_unknown() {
+        let infos = infos([
+            info("a", "bridge").with_master("b"),
+            info("b", "bridge").with_master("a"),
+        ]);
+        let grouped = group_interfaces(&names(&["a", "x", "b"]), &infos);
+        let expected: Vec<(String, usize)> = vec![
+            ("x".to_string(), 0),
+            ("a".to_string(), 0),
+            ("b".to_string(), 0),
+        ];
+        assert_eq!(grouped, expected);
+    }
+
+    #[test]
+    fn test_group_labels() {
+        let grouped = vec![("br10".to_string(), 0), ("vnet0".to_string(), 1)];
+        let labels = group_labels(&grouped, &IndexMap::new());
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels["vnet0"], "└vnet0");
+
+        let stats: IndexMap<String, (u64, u64)> = IndexMap::new();
+        let mut output = Vec::new();
+        print_headers_with_labels(
+            &names(&["br10", "vnet0"]),
+            &labels,
+            &mut output,
+            false,
+            &stats,
+            &[],
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let header = output.lines().next().unwrap();
+        assert_eq!(header.trim(), "br10               └vnet0");
+    }
+
+    #[test]
+    fn test_print_interface_tree() {
+        let grouped = vec![
+            ("br10".to_string(), 0),
+            ("bond0.10".to_string(), 1),
+            ("vnet0".to_string(), 1),
+        ];
+        let mut output = Vec::new();
+        print_interface_tree(&grouped, &hypervisor(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.lines().nth(1).unwrap().starts_with("br10 "));
+        assert!(output.lines().nth(2).unwrap().starts_with("  bond0.10 "));
+        assert!(output.lines().nth(3).unwrap().starts_with("  vnet0 "));
+    }
+}
@@ -0,0 +1,149 @@
+mod interface_info_tests {
+    use ifstat_rs::net_stats::{format_mac, operstate_name, read_sysfs_link_info, InterfaceInfo};
+    use ifstat_rs::output::{print_interface_info, print_interface_info_json};
+    use indexmap::IndexMap;
+
+    #[test]
+    fn test_format_mac_and_operstate() {
+        assert_eq!(
+            format_mac(&[0x02, 0xfc, 0, 0, 0, 0x1a]),
+            "02:fc:00:00:00:1a"
+        );
+        assert_eq!(operstate_name(6), "up");
+        assert_eq!(operstate_name(2), "down");
+        assert_eq!(operstate_name(0), "unknown");
+        assert_eq!(operstate_name(42), "unknown");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_sysfs_link_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let eth0 = dir.path().join("eth0");
+        let drivers = dir.path().join("drivers").join("ixgbe");
+        std::fs::create_dir_all(eth0.join("device")).unwrap();
+        std::fs::create_dir_all(&drivers).unwrap();
+        std::os::unix::fs::symlink(&drivers, eth0.join("device").join("driver")).unwrap();
+        std::fs::write(eth0.join("speed"), "10000\n").unwrap();
+
+        let veth0 = dir.path().join("veth0");
+        std::fs::create_dir_all(&veth0).unwrap();
+        std::fs::write(veth0.join("speed"), "-1\n").unwrap();
+
+        assert_eq!(
+            read_sysfs_link_info(dir.path(), "eth0"),
+            (Some(10000), Some("ixgbe".to_string()), true)
+        );
+        assert_eq!(
+            read_sysfs_link_info(dir.path(), "veth0"),
+            (None, None, false)
+        );
+        assert_eq!(
+            read_sysfs_link_info(dir.path(), "missing"),
+            (None, None, false)
+        );
+    }
+
+    fn sample_infos() -> (
+        IndexMap<String, (u64, u64)>,
+        IndexMap<String, InterfaceInfo>,
+    ) {
+        let stats: IndexMap<String, (u64, u64)> =
+            [("eth0".to_string(), (0, 0)), ("tun0".to_string(), (0, 0))]
+                .into_iter()
+                .collect();
+        let eth0 = InterfaceInfo {
+            name: "eth0".to_string(),
+            ifindex: 2,
+            operstate: "up".to_string(),
+            mac: Some("02:fc:00:00:00:01".to_string()),
+            mtu: Some(1500),
+            speed_mbps: Some(1000),
+            driver: Some("e1000e".to_string()),
+            kind: "physical".to_string(),
+            addresses: vec!["192.0.2.2/24".to_string(), "fe80::1/64".to_string()],
+            master: None,
+            link: None,
+            alias: None,
+            altnames: Vec::new(),
+        };
+        let infos = [("eth0".to_string(), eth0)].into_iter().collect();
+        (stats, infos)
+    }
+
+    #[test]
+    fn test_print_interface_info() {
+        let (stats, infos) = sample_infos();
+        let mut output = Vec::new();
+        print_interface_info(&stats, &infos, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("NAME"));
+        let eth0: Vec<&str> = lines[1].split_whitespace().collect();
+        assert_eq!(
+            eth0,
+            [
+                "eth0",
+                "2",
+                "up",
+                "02:fc:00:00:00:01",
+                "1500",
+                "1000",
+                "e1000e",
+                "physical",
+                "-",
+                "192.0.2.2/24",
+                "fe80::1/64"
+            ]
+        );
+        // Interfaces without metadata show dashes
+        assert!(lines[2].starts_with("tun0"));
+        assert_eq!(lines[2].split_whitespace().filter(|v| *v == "-").count(), 8);
+    }
+
+    #[test]
+    fn test_print_interface_info_json() {
+        let (stats, infos) = sample_infos();
+        let mut output = Vec::new();
+        print_interface_info_json(&stats, &infos, &mut output).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(json[0]["name"], "eth0");
+        assert_eq!(json[0]["speed_mbps"], 1000);
+        assert_eq!(json[0]["addresses"][0], "192.0.2.2/24");
+        assert_eq!(json[1], serde_json::json!({ "name": "tun0" }));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_get_interface_info_loopback() {
+        let infos = ifstat_rs::net_stats::get_interface_info().unwrap();
+        let lo = &infos["lo"];
+        assert_eq!(lo.kind, "loopback");
+        assert!(lo.ifindex > 0);
+        assert!(lo.mtu.is_some());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_list_interfaces_json_cli() {
+        use assert_cmd::Command;
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["--list-interfaces", "--format", "json"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let lo = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|iface| iface["name"] == "lo")
+            .unwrap();
+        assert_eq!(lo["kind"], "loopback");
+    }
+}
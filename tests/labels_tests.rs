@@ -0,0 +1,130 @@
+mod labels_tests {
+    use assert_cmd::prelude::*;
+    use ifstat_rs::hierarchy::group_labels;
+    use ifstat_rs::net_stats::{read_ifalias, InterfaceInfo};
+    use ifstat_rs::output::{print_rates_csv, print_rates_json_with_extra};
+    use indexmap::IndexMap;
+    use std::io::Write;
+    use std::process::Command;
+
+    fn labels() -> IndexMap<String, String> {
+        [("enp3s0f0".to_string(), "uplink-a".to_string())]
+            .into_iter()
+            .collect()
+    }
+
+    fn rates() -> IndexMap<String, (f64, f64)> {
+        [
+            ("enp3s0f0".to_string(), (2048.0, 1024.0)),
+            ("lo".to_string(), (0.0, 0.0)),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_read_ifalias() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, alias) in [("eth0", "uplink to core\n"), ("eth1", "\n")] {
+            std::fs::create_dir(dir.path().join(name)).unwrap();
+            std::fs::write(dir.path().join(name).join("ifalias"), alias).unwrap();
+        }
+        assert_eq!(
+            read_ifalias(dir.path(), "eth0"),
+            Some("uplink to core".to_string())
+        );
+        assert_eq!(read_ifalias(dir.path(), "eth1"), None);
+        assert_eq!(read_ifalias(dir.path(), "eth2"), None);
+    }
+
+    #[test]
+    fn test_friendly_name() {
+        let mut info = InterfaceInfo {
+            altnames: vec!["enp3s0f0".to_string(), "ens3f0".to_string()],
+            ..Default::default()
+        };
+        // The predictable names udev adds as alternative names don't replace the kernel name
+        assert_eq!(info.friendly_name(), None);
+        info.alias = Some("uplink-a".to_string());
+        assert_eq!(info.friendly_name(), Some("uplink-a"));
+        assert_eq!(InterfaceInfo::default().friendly_name(), None);
+    }
+
+    #[test]
+    fn test_group_labels_with_aliases() {
+        let grouped = vec![
+            ("enp3s0f0".to_string(), 0),
+            ("vnet0".to_string(), 1),
+            ("lo".to_string(), 0),
+        ];
+        let mut aliases = labels();
+        aliases.insert("vnet0".to_string(), "guest-with-a-long-name".to_string());
+
+        let headers = group_labels(&grouped, &aliases);
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["enp3s0f0"], "uplink-a");
+        assert_eq!(headers["vnet0"], "└guest-with-a-...");
+    }
+
+    #[test]
+    fn test_machine_output_carries_labels() {
+        let mut output = Vec::new();
+        print_rates_json_with_extra(
+            Some(1.0),
+            &rates(),
+            &labels(),
+            serde_json::Map::new(),
+            &mut output,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(json["interfaces"]["enp3s0f0"]["label"], "uplink-a");
+        assert_eq!(json["interfaces"]["enp3s0f0"]["rx_bytes_per_s"], 2048.0);
+        assert!(json["interfaces"]["lo"].get("label").is_none());
+
+        let mut output = Vec::new();
+        print_rates_csv(Some(1.0), &rates(), &labels(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1.000,enp3s0f0,2048.00,1024.00,uplink-a\n1.000,lo,0.00,0.00,\n"
+        );
+    }
+
+    #[test]
+    fn test_label_option() {
+        let header = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+";
+        let mut before = tempfile::NamedTempFile::new().unwrap();
+        let mut after = tempfile::NamedTempFile::new().unwrap();
+        for (file, rx) in [(&mut before, 1000), (&mut after, 11240)] {
+            writeln!(
+                file,
+                "{}  eth0:  {}    1264    0    0    0     0          0         0   2000    1571    0    0    0     0       0          0",
+                header, rx
+            )
+            .unwrap();
+        }
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["analyze", "--interval", "10", "--label", "eth0=uplink-a"])
+            .arg(before.path())
+            .arg(after.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(stdout.lines().next().unwrap().trim(), "uplink-a");
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["analyze", "--label", "eth0"])
+            .arg(before.path())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("NAME=LABEL"), "{}", stderr);
+    }
+}
@@ -0,0 +1,68 @@
+mod parse_wireless_stats_tests {
+    use ifstat_rs::net_stats::{compute_wireless_rates, parse_wireless_stats};
+    use ifstat_rs::output::{wireless_column_groups, wireless_column_values};
+    use std::io::Cursor;
+
+    const WIRELESS: &str = r#"Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+wlp2s0: 0000   54.  -56.  -256        0      0      0     12      3        7
+ wlan1: 0000   70   -40   -95        1      2      0      0      0        0
+"#;
+
+    #[test]
+    fn test_parse_wireless_stats() {
+        let stats = parse_wireless_stats(Cursor::new(WIRELESS)).unwrap();
+
+        assert_eq!(stats.len(), 2);
+        let wlp2s0 = stats["wlp2s0"];
+        assert_eq!(wlp2s0.link, 54.0);
+        assert_eq!(wlp2s0.level, -56.0);
+        assert_eq!(wlp2s0.noise, -256.0);
+        assert_eq!(wlp2s0.discarded_retry, 12);
+        assert_eq!(wlp2s0.discarded(), 15);
+        assert_eq!(wlp2s0.missed_beacon, 7);
+        assert_eq!(stats["wlan1"].discarded_crypt, 2);
+        assert_eq!(stats["wlan1"].noise, -95.0);
+    }
+
+    #[test]
+    fn test_parse_wireless_stats_header_only() {
+        let header: String = WIRELESS.lines().take(2).collect::<Vec<_>>().join("\n");
+        let stats = parse_wireless_stats(Cursor::new(header)).unwrap();
+        assert!(stats.is_empty());
+    }
+
+    #[test]
+    fn test_parse_wireless_stats_invalid() {
+        let too_few = "wlan0: 0000 54. -56. -256 0 0\n";
+        let err = parse_wireless_stats(Cursor::new(too_few)).unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+
+        let invalid = "wlan0: 0000 good -56. -256 0 0 0 0 0 0\n";
+        assert!(parse_wireless_stats(Cursor::new(invalid)).is_err());
+
+        let missing_colon = "wlan0 0000 54. -56. -256 0 0 0 0 0 0\n";
+        assert!(parse_wireless_stats(Cursor::new(missing_colon)).is_err());
+    }
+
+    #[test]
+    fn test_compute_wireless_rates() {
+        let previous = parse_wireless_stats(Cursor::new(WIRELESS)).unwrap();
+        let mut current = previous.clone();
+        current["wlp2s0"].discarded_retry += 20;
+        current["wlp2s0"].level = -60.0;
+        current.shift_remove("wlan1");
+
+        let rates = compute_wireless_rates(&previous, &current, 2.0);
+        assert_eq!(rates["wlp2s0"].discarded_per_s, 10.0);
+        assert_eq!(rates["wlp2s0"].level, -60.0);
+        assert!(!rates.contains_key("wlan1"));
+
+        let interfaces = vec!["wlp2s0".to_string(), "wlan1".to_string()];
+        assert_eq!(wireless_column_groups(&interfaces).len(), 2);
+        let values = wireless_column_values(&interfaces, &rates);
+        assert_eq!(values[..5], [54.0, -60.0, -256.0, 10.0, 0.0]);
+        // Disconnected interfaces show zeros
+        assert_eq!(values[5..], [0.0; 5]);
+    }
+}
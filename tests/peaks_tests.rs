@@ -0,0 +1,213 @@
+mod common;
+
+mod peaks_tests {
+    #[cfg(unix)]
+    use crate::common::run_interrupted;
+    use assert_cmd::prelude::*;
+    use ifstat_rs::output::{peak_column_values, print_peak_table};
+    use ifstat_rs::peaks::{Peak, PeakTracker};
+    use ifstat_rs::sampler::Sample;
+    use indexmap::IndexMap;
+    use std::io::Write;
+    use std::process::Command;
+    use std::time::Duration;
+
+    fn counters(values: &[(&str, u64, u64)]) -> IndexMap<String, (u64, u64)> {
+        values
+            .iter()
+            .map(|(iface, rx, tx)| (iface.to_string(), (*rx, *tx)))
+            .collect()
+    }
+
+    fn sample(at: u64, previous: &[(&str, u64, u64)], current: &[(&str, u64, u64)]) -> Sample {
+        Sample::between(
+            &counters(previous),
+            &counters(current),
+            Duration::from_secs(at),
+            Duration::from_secs(1),
+        )
+    }
+
+    #[test]
+    fn test_peaks_keep_maximum_and_time() {
+        let interfaces = vec!["eth0".to_string()];
+        let mut tracker = PeakTracker::new(&interfaces);
+        tracker.update(&sample(1, &[("eth0", 0, 0)], &[("eth0", 100, 500)]), None);
+        tracker.update(
+            &sample(2, &[("eth0", 100, 500)], &[("eth0", 400, 600)]),
+            None,
+        );
+        tracker.update(
+            &sample(3, &[("eth0", 400, 600)], &[("eth0", 500, 700)]),
+            None,
+        );
+
+        let eth0 = tracker.peaks()["eth0"];
+        assert_eq!(
+            eth0.rx_bytes_per_s,
+            Peak {
+                value: 300.0,
+                at: 2.0
+            }
+        );
+        assert_eq!(
+            eth0.tx_bytes_per_s,
+            Peak {
+                value: 500.0,
+                at: 1.0
+            }
+        );
+        assert_eq!(eth0.rx_packets_per_s, None);
+        assert!(!tracker.has_packets());
+    }
+
+    #[test]
+    fn test_packet_peaks_and_reset() {
+        let interfaces = vec!["eth0".to_string()];
+        let mut tracker = PeakTracker::new(&interfaces).with_packets(counters(&[("eth0", 0, 0)]));
+        tracker.update(
+            &sample(1, &[("eth0", 0, 0)], &[("eth0", 0, 0)]),
+            Some(counters(&[("eth0", 10, 4)])),
+        );
+        tracker.update(
+            &sample(2, &[("eth0", 0, 0)], &[("eth0", 0, 0)]),
+            Some(counters(&[("eth0", 15, 12)])),
+        );
+
+        let previous = tracker.reset();
+        assert_eq!(
+            previous["eth0"].rx_packets_per_s,
+            Some(Peak {
+                value: 10.0,
+                at: 1.0
+            })
+        );
+        assert_eq!(
+            previous["eth0"].tx_packets_per_s,
+            Some(Peak {
+                value: 8.0,
+                at: 2.0
+            })
+        );
+        // Reset peaks start from zero, packet rates still tracked
+        let eth0 = tracker.peaks()["eth0"];
+        assert_eq!(eth0.rx_packets_per_s, Some(Peak::default()));
+        assert_eq!(eth0.rx_bytes_per_s, Peak::default());
+
+        tracker.update(
+            &sample(3, &[("eth0", 0, 0)], &[("eth0", 2048, 0)]),
+            Some(counters(&[("eth0", 17, 12)])),
+        );
+        let eth0 = tracker.peaks()["eth0"];
+        assert_eq!(
+            eth0.rx_bytes_per_s,
+            Peak {
+                value: 2048.0,
+                at: 3.0
+            }
+        );
+        assert_eq!(
+            eth0.rx_packets_per_s,
+            Some(Peak {
+                value: 2.0,
+                at: 3.0
+            })
+        );
+        assert_eq!(
+            peak_column_values(&interfaces, true, tracker.peaks()),
+            vec![2.0, 0.0, 2.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_print_peak_table() {
+        let interfaces = vec!["eth0".to_string()];
+        let mut tracker = PeakTracker::new(&interfaces);
+        tracker.update(&sample(4, &[("eth0", 0, 0)], &[("eth0", 2048, 1024)]), None);
+
+        let mut output = Vec::new();
+        print_peak_table(tracker.peaks(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<Vec<&str>> = output
+            .lines()
+            .map(|line| line.split_whitespace().collect())
+            .collect();
+        assert_eq!(lines[0], ["peaks", "KB/s", "in", "at", "KB/s", "out", "at"]);
+        assert_eq!(lines[1], ["eth0", "2.00", "4.0", "1.00", "4.0"]);
+    }
+
+    const HEADER: &str = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+";
+
+    fn dump(eth0_rx: u64, eth0_tx: u64) -> String {
+        format!(
+            "{}  eth0:  {}    1264    0    0    0     0          0         0   {}    1571    0    0    0     0       0          0\n",
+            HEADER, eth0_rx, eth0_tx
+        )
+    }
+
+    #[test]
+    fn test_analyze_peaks_json() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for (rx, tx) in [(0, 0), (10240, 1024), (11264, 4096), (12288, 5120)] {
+            write!(file, "{}", dump(rx, tx)).unwrap();
+        }
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["analyze", "--interval", "1", "--format", "json", "--peaks"])
+            .arg(file.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let last: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+        let eth0 = &last["peaks"]["eth0"];
+        assert_eq!(last["interfaces"]["eth0"]["rx_bytes_per_s"], 1024.0);
+        assert_eq!(eth0["rx_bytes_per_s"]["value"], 10240.0);
+        assert_eq!(eth0["rx_bytes_per_s"]["at"], 1.0);
+        assert_eq!(eth0["tx_bytes_per_s"]["value"], 3072.0);
+        assert_eq!(eth0["tx_bytes_per_s"]["at"], 2.0);
+        // Packet counters are not available from dumps
+        assert!(eth0["rx_packets_per_s"].is_null());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_no_packet_peaks_from_other_providers() {
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["-i", "lo", "--peaks", "--provider", "sysfs"])
+            .args(["--format", "json", "0.05", "1"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("only tracks packet rates with --provider system"));
+
+        // The byte peaks come from sysfs, the packet counters of /proc/net/dev are not mixed in
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let last: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+        let lo = &last["peaks"]["lo"];
+        assert!(lo["rx_bytes_per_s"].is_object(), "{}", stdout);
+        assert!(lo["rx_packets_per_s"].is_null());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_peaks_printed_on_interrupt() {
+        let output = run_interrupted(&["-i", "lo", "--peaks", "0.1"], Duration::from_millis(500));
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let peaks = stdout.lines().rev().nth(1).unwrap();
+        assert!(peaks.trim_start().starts_with("peaks"), "{}", stdout);
+        assert!(stdout
+            .lines()
+            .last()
+            .unwrap()
+            .trim_start()
+            .starts_with("lo"));
+    }
+}
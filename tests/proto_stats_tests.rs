@@ -0,0 +1,150 @@
+mod proto_stats_tests {
+    use ifstat_rs::net_stats::{
+        compute_proto_rates, parse_proto_stats, read_proto_stats, PROTO_RATE_COUNTERS,
+    };
+    use ifstat_rs::output::{
+        print_headers_with_columns, print_rates_with_columns, proto_column_groups,
+        proto_column_values,
+    };
+    use indexmap::IndexMap;
+    use std::io::Cursor;
+
+    const SNMP: &str = "\
+Ip: Forwarding DefaultTTL InReceives OutRequests
+Ip: 2 64 8785 8647
+Tcp: RtoAlgorithm MaxConn InSegs OutSegs RetransSegs
+Tcp: 1 -1 8753 8629 2
+Udp: InDatagrams OutDatagrams RcvbufErrors
+Udp: 32 32 0
+";
+
+    const NETSTAT: &str = "\
+TcpExt: SyncookiesSent ListenOverflows ListenDrops
+TcpExt: 0 3 5
+";
+
+    #[test]
+    fn test_parse_proto_stats() {
+        let stats = parse_proto_stats(Cursor::new(SNMP)).unwrap();
+        assert_eq!(stats["Ip.InReceives"], 8785);
+        assert_eq!(stats["Tcp.RetransSegs"], 2);
+        assert_eq!(stats["Udp.RcvbufErrors"], 0);
+        // Negative values are settings, not counters
+        assert!(!stats.contains_key("Tcp.MaxConn"));
+        assert_eq!(stats.len(), 11);
+    }
+
+    #[test]
+    fn test_parse_proto_stats_errors() {
+        let mismatch = "Ip: A B\nIp: 1\n";
+        let err = parse_proto_stats(Cursor::new(mismatch)).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("1 values for 2 counters at line 2"));
+
+        let other_protocol = "Ip: A\nTcp: 1\n";
+        assert!(parse_proto_stats(Cursor::new(other_protocol)).is_err());
+
+        let missing_values = "Ip: A\n";
+        assert!(parse_proto_stats(Cursor::new(missing_values)).is_err());
+
+        let invalid = "Ip: A\nIp: x\n";
+        let err = parse_proto_stats(Cursor::new(invalid)).unwrap_err();
+        assert!(err.to_string().contains("Invalid Ip.A"));
+    }
+
+    #[test]
+    fn test_read_proto_stats_merges_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let snmp = dir.path().join("snmp");
+        let netstat = dir.path().join("netstat");
+        std::fs::write(&snmp, SNMP).unwrap();
+        std::fs::write(&netstat, NETSTAT).unwrap();
+
+        let stats = read_proto_stats(&snmp, Some(&netstat)).unwrap();
+        assert_eq!(stats["Tcp.InSegs"], 8753);
+        assert_eq!(stats["TcpExt.ListenDrops"], 5);
+
+        let missing = dir.path().join("missing");
+        let err = read_proto_stats(&missing, None).unwrap_err();
+        assert!(err.to_string().contains("missing"));
+    }
+
+    #[test]
+    fn test_compute_and_print_proto_rates() {
+        let previous: IndexMap<String, u64> = [
+            ("Tcp.InSegs".to_string(), 100),
+            ("Tcp.RetransSegs".to_string(), 10),
+        ]
+        .into_iter()
+        .collect();
+        let current: IndexMap<String, u64> = [
+            ("Tcp.InSegs".to_string(), 300),
+            ("Tcp.RetransSegs".to_string(), 5),
+        ]
+        .into_iter()
+        .collect();
+        let rates = compute_proto_rates(&previous, &current, &PROTO_RATE_COUNTERS, 2.0);
+        assert_eq!(rates["Tcp.InSegs"], 100.0);
+        // Counters going backwards yield zero
+        assert_eq!(rates["Tcp.RetransSegs"], 0.0);
+        assert!(!rates.contains_key("Udp.InDatagrams"));
+
+        let interfaces = vec!["eth0".to_string()];
+        let stats: IndexMap<String, (u64, u64)> =
+            [("eth0".to_string(), (0, 0))].into_iter().collect();
+        let mut output = Vec::new();
+        print_headers_with_columns(
+            &interfaces,
+            &mut output,
+            false,
+            &stats,
+            &proto_column_groups(),
+        )
+        .unwrap();
+        let iface_rates: IndexMap<String, (f64, f64)> = [("eth0".to_string(), (2048.0, 1024.0))]
+            .into_iter()
+            .collect();
+        print_rates_with_columns(&iface_rates, &proto_column_values(&rates), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for group in ["eth0", "IP", "TCP", "UDP", "ICMP"] {
+            assert!(
+                lines[0].contains(group),
+                "{} missing in {}",
+                group,
+                lines[0]
+            );
+        }
+        assert!(lines[1].contains("retr/s"));
+        let values: Vec<&str> = lines[2].split_whitespace().collect();
+        // eth0 in/out, IP in/out, then TCP in
+        assert_eq!(values.len(), 2 + PROTO_RATE_COUNTERS.len());
+        assert_eq!(&values[..5], ["2.00", "1.00", "0.00", "0.00", "100.00"]);
+        // Every rate column is aligned below its label
+        assert_eq!(lines[1].len(), lines[2].len());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_proto_cli() {
+        use assert_cmd::Command;
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["--proto", "--format", "json", "-i", "lo", "0.1", "1"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let line: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert!(line["protocols"]["Tcp.RetransSegs"].is_number());
+
+        Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["--proto", "--format", "csv", "0.1", "1"])
+            .assert()
+            .failure();
+    }
+}
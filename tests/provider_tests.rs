@@ -0,0 +1,226 @@
+mod common;
+
+mod provider_tests {
+    use crate::common::stats;
+    use assert_cmd::prelude::*;
+    use ifstat_rs::net_stats::{
+        MockProvider, NextSnapshot, ProcNetDevProvider, ReplayProvider, StatsProvider,
+        SysfsProvider,
+    };
+    use ifstat_rs::output::{print_headers, print_rates};
+    use ifstat_rs::record::Snapshot;
+    use ifstat_rs::sampler::Sampler;
+    use std::fs;
+    use std::process::Command;
+    use std::time::Duration;
+
+    #[test]
+    fn test_mock_provider_drives_sampler_and_output() {
+        let provider = MockProvider::new(vec![
+            stats((0, 0), (0, 0)),
+            stats((10240, 5120), (0, 0)),
+            stats((20480, 5120), (1024, 1024)),
+        ])
+        .with_interval(Duration::from_secs(1));
+        let sampler = Sampler::with_provider(provider, Duration::from_secs(1))
+            .unwrap()
+            .with_speed(f64::INFINITY);
+        let interfaces = vec!["eth0".to_string(), "lo".to_string()];
+
+        let mut output = Vec::new();
+        print_headers(&interfaces, &mut output, false, sampler.baseline()).unwrap();
+        let mut samples = 0;
+        for sample in sampler {
+            let sample = sample.unwrap();
+            assert_eq!(sample.interval, Duration::from_secs(1));
+            print_rates(&sample.rates(&interfaces), &mut output).unwrap();
+            samples += 1;
+        }
+        assert_eq!(samples, 2);
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("eth0") && lines[0].contains("lo"));
+        assert_eq!(
+            lines[2].split_whitespace().collect::<Vec<_>>(),
+            vec!["10.00", "5.00", "0.00", "0.00"]
+        );
+        assert_eq!(
+            lines[3].split_whitespace().collect::<Vec<_>>(),
+            vec!["10.00", "0.00", "1.00", "1.00"]
+        );
+    }
+
+    #[test]
+    fn test_mock_provider_exhausts() {
+        let mut provider = MockProvider::new(vec![stats((1, 2), (3, 4))]);
+        assert_eq!(provider.next_snapshot(), NextSnapshot::Live);
+        assert_eq!(provider.get_net_dev_stats().unwrap(), stats((1, 2), (3, 4)));
+        assert_eq!(provider.next_snapshot(), NextSnapshot::Exhausted);
+        assert!(provider.get_net_dev_stats().is_err());
+        assert!(Sampler::with_provider(provider, Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_replay_provider_timing() {
+        let mut provider = ReplayProvider::new(vec![
+            Snapshot {
+                elapsed_us: 500,
+                stats: stats((1, 2), (3, 4)),
+            },
+            Snapshot {
+                elapsed_us: 1500,
+                stats: stats((5, 6), (7, 8)),
+            },
+        ]);
+        assert_eq!(
+            provider.next_snapshot(),
+            NextSnapshot::Recorded(Duration::from_micros(500))
+        );
+        provider.get_net_dev_stats().unwrap();
+        assert_eq!(
+            provider.next_snapshot(),
+            NextSnapshot::Recorded(Duration::from_micros(1500))
+        );
+        assert_eq!(provider.get_net_dev_stats().unwrap(), stats((5, 6), (7, 8)));
+        assert_eq!(provider.next_snapshot(), NextSnapshot::Exhausted);
+    }
+
+    #[test]
+    fn test_closure_provider() {
+        let mut counter = 0;
+        let mut provider = move || {
+            counter += 100;
+            Ok(stats((counter, 0), (0, 0)))
+        };
+        assert_eq!(provider.get_net_dev_stats().unwrap()["eth0"], (100, 0));
+        assert_eq!(provider.get_net_dev_stats().unwrap()["eth0"], (200, 0));
+    }
+
+    #[test]
+    fn test_proc_net_dev_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dev");
+        fs::write(
+            &path,
+            "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+  eth0:  104013    1264    0    0    0     0          0         0   204386    1571    0    0    0     0       0          0
+",
+        )
+        .unwrap();
+
+        let mut provider = ProcNetDevProvider::new(&path);
+        assert_eq!(
+            provider.get_net_dev_stats().unwrap()["eth0"],
+            (104013, 204386)
+        );
+    }
+
+    #[test]
+    fn test_sysfs_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        for (iface, ifindex, rx, tx) in [("lo", 1, 10, 20), ("eth0", 2, 30, 40)] {
+            let statistics = dir.path().join(iface).join("statistics");
+            fs::create_dir_all(&statistics).unwrap();
+            fs::write(
+                dir.path().join(iface).join("ifindex"),
+                format!("{}\n", ifindex),
+            )
+            .unwrap();
+            fs::write(statistics.join("rx_bytes"), format!("{}\n", rx)).unwrap();
+            fs::write(statistics.join("tx_bytes"), format!("{}\n", tx)).unwrap();
+        }
+        // Files that aren't devices are ignored
+        fs::write(dir.path().join("bonding_masters"), "").unwrap();
+
+        let mut provider = SysfsProvider::new(dir.path());
+        let result = provider.get_net_dev_stats().unwrap();
+        assert_eq!(result.keys().collect::<Vec<_>>(), vec!["lo", "eth0"]);
+        assert_eq!(result["lo"], (10, 20));
+        assert_eq!(result["eth0"], (30, 40));
+    }
+
+    #[test]
+    fn test_provider_path_cli() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dev");
+        fs::write(
+            &path,
+            "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    5000      10    0    0    0     0          0         0     5000      10    0    0    0     0       0          0
+  eth9:  104013    1264    0    0    0     0          0         0   204386    1571    0    0    0     0       0          0
+ wlan9:    2048       2    0    0    0     0          0         0     1024       1    0    0    0     0       0          0
+",
+        )
+        .unwrap();
+        let run = |args: &[&str]| {
+            Command::cargo_bin("ifstat-rs")
+                .unwrap()
+                .args(args)
+                .arg(&path)
+                .args(["--format", "json", "0.05", "2"])
+                .output()
+                .unwrap()
+        };
+
+        // Only the interfaces of the fixture are shown, never those of the host
+        let output = run(&["--provider", "proc", "--provider-path"]);
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<serde_json::Value> = stdout
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        let interfaces = lines[1]["interfaces"].as_object().unwrap();
+        assert_eq!(interfaces.keys().collect::<Vec<_>>(), ["eth9", "wlan9"]);
+        assert_eq!(interfaces["eth9"]["rx_bytes_per_s"], 0.0);
+
+        let output = run(&["--provider", "netlink", "--provider-path"]);
+        assert!(!output.status.success());
+
+        // The metadata of --list-interfaces would be that of the host
+        let output = run(&["--list-interfaces", "--provider", "proc", "--provider-path"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("cannot be used with"), "{}", stderr);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_link_message() {
+        use ifstat_rs::net_stats::parse_link_message;
+
+        fn attr(attr_type: u16, payload: &[u8]) -> Vec<u8> {
+            let mut attr = Vec::new();
+            attr.extend_from_slice(&((4 + payload.len()) as u16).to_ne_bytes());
+            attr.extend_from_slice(&attr_type.to_ne_bytes());
+            attr.extend_from_slice(payload);
+            attr.resize((attr.len() + 3) & !3, 0);
+            attr
+        }
+
+        // rx_packets, tx_packets, rx_bytes, tx_bytes and the remaining counters
+        let stats64: Vec<u8> = [10u64, 20, 104013, 204386, 0, 0]
+            .iter()
+            .flat_map(|counter| counter.to_ne_bytes())
+            .collect();
+        let mut payload = vec![0u8; 16]; // struct ifinfomsg
+        payload.extend(attr(libc::IFLA_IFNAME, b"eth0\0"));
+        payload.extend(attr(libc::IFLA_STATS64, &stats64));
+
+        assert_eq!(
+            parse_link_message(&payload),
+            Some(("eth0".to_string(), (104013, 204386)))
+        );
+        // Links without 64 bit counters and truncated messages are skipped
+        let mut unnamed = vec![0u8; 16];
+        unnamed.extend(attr(libc::IFLA_STATS64, &stats64));
+        assert_eq!(parse_link_message(&unnamed), None);
+        assert_eq!(parse_link_message(&payload[..24]), None);
+        assert_eq!(parse_link_message(&payload[..8]), None);
+    }
+}
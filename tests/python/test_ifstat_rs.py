@@ -0,0 +1,71 @@
+# Tests of the `ifstat_rs` Python extension module (src/python.rs).
+#
+# Run by `cargo test --features python` (tests/python_tests.rs), or with pytest after
+# `maturin develop`.
+
+import ifstat_rs
+
+DUMP = """Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    1000      10    0    0    0     0          0         0     1000      10    0    0    0     0       0          0
+  eth0:    2000      20    1    2    0     0          0         3     4000      40    0    0    0     0       0          0
+"""
+
+
+def test_version():
+    assert ifstat_rs.__version__
+
+
+def test_parse_net_dev_stats():
+    assert ifstat_rs.parse_net_dev_stats(DUMP) == {"lo": (1000, 1000), "eth0": (2000, 4000)}
+
+
+def test_parse_net_dev_counters():
+    counters = ifstat_rs.parse_net_dev_counters(DUMP)
+    assert list(counters) == ["lo", "eth0"]
+    assert counters["eth0"]["rx_errors"] == 1
+    assert counters["eth0"]["rx_dropped"] == 2
+    assert counters["eth0"]["rx_multicast"] == 3
+    assert counters["eth0"]["tx_packets"] == 40
+
+
+def test_parse_error_raises_value_error():
+    try:
+        ifstat_rs.parse_net_dev_stats(DUMP + "garbage\n")
+    except ValueError:
+        return
+    raise AssertionError("no ValueError raised")
+
+
+def test_get_net_dev_stats():
+    stats = ifstat_rs.get_net_dev_stats()
+    assert "lo" in stats
+    assert ifstat_rs.get_net_dev_counters()["lo"]["rx_bytes"] >= stats["lo"][0]
+
+
+def test_sampler():
+    samples = list(ifstat_rs.Sampler(interval=0.01, count=2, interfaces=["lo"]))
+    assert len(samples) == 2
+    for sample in samples:
+        assert list(sample["interfaces"]) == ["lo"]
+        assert sample["interval"] > 0
+        assert sample["interfaces"]["lo"]["rx_bytes_per_s"] >= 0
+    assert samples[0]["time"] <= samples[1]["time"]
+
+    sample = ifstat_rs.Sampler(interval=0.01).sample()
+    assert "lo" in sample["interfaces"]
+
+
+def test_sampler_invalid_interval():
+    try:
+        ifstat_rs.Sampler(interval=0)
+    except ValueError:
+        return
+    raise AssertionError("no ValueError raised")
+
+
+if __name__ == "__main__":
+    for name, test in list(globals().items()):
+        if name.startswith("test_"):
+            test()
+    print("ok")
@@ -0,0 +1,43 @@
+#[cfg(all(feature = "python", target_os = "linux"))]
+mod python_tests {
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    use tempfile::tempdir;
+
+    /// Returns the directory `cargo test` builds the cdylib of this run into.
+    fn lib_dir() -> PathBuf {
+        // OUT_DIR is target/<profile>/build/<pkg>-<hash>/out
+        PathBuf::from(env!("OUT_DIR"))
+            .ancestors()
+            .nth(3)
+            .unwrap()
+            .join("deps")
+    }
+
+    #[test]
+    fn test_python_module() {
+        let library = lib_dir().join("libifstat_rs.so");
+        assert!(library.exists(), "{} not built", library.display());
+
+        // Python only imports extension modules named after the module
+        let module_dir = tempdir().unwrap();
+        std::fs::copy(&library, module_dir.path().join("ifstat_rs.so")).unwrap();
+
+        let output =
+            Command::new(std::env::var("PYTHON").unwrap_or_else(|_| "python3".to_string()))
+                .arg(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/tests/python/test_ifstat_rs.py"
+                ))
+                .env("PYTHONPATH", module_dir.path())
+                .output()
+                .expect("running python3 failed, set PYTHON if it isn't python3");
+        assert!(
+            output.status.success(),
+            "Python tests failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+    }
+}
@@ -0,0 +1,140 @@
+mod qdisc_tests {
+    use ifstat_rs::net_stats::{compute_tc_rates, format_tc_handle, TcStats};
+    use ifstat_rs::output::print_tc_table;
+
+    #[test]
+    fn test_format_tc_handle() {
+        assert_eq!(format_tc_handle(0xffff_ffff), "root");
+        assert_eq!(format_tc_handle(0), "0:");
+        assert_eq!(format_tc_handle(0x8001_0000), "8001:");
+        assert_eq!(format_tc_handle(0x0001_0010), "1:10");
+    }
+
+    fn htb(ifindex: u32, interface: &str, class: bool, bytes: u64, drops: u64) -> TcStats {
+        TcStats {
+            ifindex,
+            interface: interface.to_string(),
+            class,
+            kind: "htb".to_string(),
+            handle: if class { 0x0001_0010 } else { 0x0001_0000 },
+            parent: if class { 0x0001_0000 } else { 0xffff_ffff },
+            bytes,
+            packets: bytes / 1000,
+            drops,
+            backlog: 1500,
+            qlen: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compute_and_print_tc_rates() {
+        let previous = vec![htb(2, "eth0", false, 0, 1), htb(2, "eth0", true, 0, 0)];
+        let current = vec![
+            htb(2, "eth0", false, 20480, 5),
+            htb(2, "eth0", true, 10240, 0),
+            htb(3, "eth1", false, 1000, 0),
+        ];
+
+        let rates = compute_tc_rates(&previous, &current, 2.0);
+        assert_eq!(rates.len(), 3);
+        assert_eq!(rates[0].bytes_per_s, 10240.0);
+        assert_eq!(rates[0].drops_per_s, 2.0);
+        assert_eq!(rates[0].handle, "1:");
+        assert_eq!(rates[0].parent, "root");
+        assert_eq!(rates[1].handle, "1:10");
+        assert_eq!(rates[1].backlog, 1500);
+        // New qdiscs have no rates yet
+        assert_eq!(rates[2].bytes_per_s, 0.0);
+
+        let mut output = Vec::new();
+        print_tc_table(&rates, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("drops/s"));
+        assert!(lines[1].trim_start().starts_with("eth0"));
+        // The interface is only named on the first line of its group
+        assert!(lines[2].trim_start().starts_with("class"));
+        assert!(lines[3].trim_start().starts_with("eth1"));
+        assert!(lines[1].contains("10.00"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_tc_message() {
+        use ifstat_rs::net_stats::parse_tc_message;
+
+        fn attr(attr_type: u16, payload: &[u8]) -> Vec<u8> {
+            let mut attr = Vec::new();
+            attr.extend_from_slice(&((4 + payload.len()) as u16).to_ne_bytes());
+            attr.extend_from_slice(&attr_type.to_ne_bytes());
+            attr.extend_from_slice(payload);
+            attr.resize((attr.len() + 3) & !3, 0);
+            attr
+        }
+
+        let mut basic = Vec::new();
+        basic.extend_from_slice(&123456u64.to_ne_bytes());
+        basic.extend_from_slice(&789u32.to_ne_bytes());
+        basic.extend_from_slice(&0u32.to_ne_bytes());
+        let mut queue = Vec::new();
+        for value in [2u32, 3000, 4, 5, 6] {
+            queue.extend_from_slice(&value.to_ne_bytes());
+        }
+        let mut stats2 = attr(1, &basic);
+        stats2.extend(attr(3, &queue));
+
+        let mut payload = vec![0u8; 20];
+        payload[4..8].copy_from_slice(&7u32.to_ne_bytes());
+        payload[8..12].copy_from_slice(&0x0001_0000u32.to_ne_bytes());
+        payload[12..16].copy_from_slice(&0xffff_ffffu32.to_ne_bytes());
+        payload.extend(attr(libc::TCA_KIND, b"fq_codel\0"));
+        payload.extend(attr(libc::TCA_STATS2, &stats2));
+
+        let stats = parse_tc_message(libc::RTM_NEWQDISC, &payload).unwrap();
+        assert_eq!(stats.ifindex, 7);
+        assert!(!stats.class);
+        assert_eq!(stats.kind, "fq_codel");
+        assert_eq!(stats.handle, 0x0001_0000);
+        assert_eq!(stats.bytes, 123456);
+        assert_eq!(stats.packets, 789);
+        assert_eq!(stats.qlen, 2);
+        assert_eq!(stats.backlog, 3000);
+        assert_eq!(stats.drops, 4);
+        assert_eq!(stats.requeues, 5);
+        assert_eq!(stats.overlimits, 6);
+
+        assert!(
+            parse_tc_message(libc::RTM_NEWTCLASS, &payload)
+                .unwrap()
+                .class
+        );
+        assert!(parse_tc_message(libc::RTM_NEWLINK, &payload).is_none());
+        assert!(parse_tc_message(libc::RTM_NEWQDISC, &payload[..10]).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_get_tc_stats_loopback() {
+        use ifstat_rs::net_stats::get_tc_stats;
+
+        let stats = get_tc_stats(&["lo".to_string()]).unwrap();
+        assert!(stats.iter().all(|s| s.interface == "lo"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_qdisc_cli() {
+        use assert_cmd::Command;
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["--qdisc", "--format", "json", "-i", "lo", "0.1", "1"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let line: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert!(line["qdiscs"].is_array());
+    }
+}
@@ -0,0 +1,195 @@
+mod quota_tests {
+    use assert_cmd::prelude::*;
+    use clap::{CommandFactory, FromArgMatches};
+    use ifstat_rs::accounting::{days_from_civil, Database};
+    use ifstat_rs::config::{Config, Profile};
+    use ifstat_rs::opts::Opts;
+    use ifstat_rs::output::format_quota_status;
+    use ifstat_rs::quota::{parse_quota, parse_size, Quota, QuotaPeriod};
+    use indexmap::IndexMap;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const GIB: u64 = 1 << 30;
+
+    fn day(year: i64, month: u32, day: u32) -> u64 {
+        days_from_civil(year, month, day) as u64 * 86400
+    }
+
+    fn counters(bytes: u64) -> IndexMap<String, (u64, u64)> {
+        [("wwan0".to_string(), (bytes, 0))].into_iter().collect()
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("50GiB"), Some(50 * GIB));
+        assert_eq!(parse_size("1.5 TB"), Some(1_500_000_000_000));
+        assert_eq!(parse_size("2mib"), Some(2 << 20));
+        assert_eq!(parse_size("10 GiBs"), None);
+        assert_eq!(parse_size("GiB"), None);
+    }
+
+    #[test]
+    fn test_parse_quota() {
+        assert_eq!(
+            parse_quota("wwan0=50GiB/month,reset-day=15"),
+            Ok(Quota {
+                interface: "wwan0".to_string(),
+                limit_bytes: 50 * GIB,
+                period: QuotaPeriod::Month,
+                reset_day: 15,
+            })
+        );
+        let daily = parse_quota("wwan0=1GB/day").unwrap();
+        assert_eq!(daily.period, QuotaPeriod::Day);
+        assert_eq!(daily.limit_bytes, 1_000_000_000);
+        assert_eq!(parse_quota("wwan0=5GiB").unwrap().reset_day, 1);
+
+        assert!(parse_quota("wwan0").is_err());
+        assert!(parse_quota("=5GiB").is_err());
+        assert!(parse_quota("wwan0=0GiB").is_err());
+        assert!(parse_quota("wwan0=5GiB/week").is_err());
+        assert!(parse_quota("wwan0=5GiB/month,reset-day=32").is_err());
+        assert!(parse_quota("wwan0=5GiB/day,reset-day=15").is_err());
+        assert!(parse_quota("wwan0=5GiB,limit=1").is_err());
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        let quota = parse_quota("wwan0=50GiB/month,reset-day=15").unwrap();
+        assert_eq!(
+            quota.period_bounds(day(2024, 8, 1) + 3600),
+            (day(2024, 7, 15), day(2024, 8, 15))
+        );
+        assert_eq!(
+            quota.period_bounds(day(2024, 12, 20)),
+            (day(2024, 12, 15), day(2025, 1, 15))
+        );
+
+        // The last day in shorter months
+        let quota = parse_quota("wwan0=50GiB/month,reset-day=31").unwrap();
+        assert_eq!(
+            quota.period_bounds(day(2024, 2, 10)),
+            (day(2024, 1, 31), day(2024, 2, 29))
+        );
+        assert_eq!(
+            quota.period_bounds(day(2024, 2, 29)),
+            (day(2024, 2, 29), day(2024, 3, 31))
+        );
+
+        let quota = parse_quota("wwan0=1GiB/day").unwrap();
+        assert_eq!(
+            quota.period_bounds(day(2024, 8, 1) + 50000),
+            (day(2024, 8, 1), day(2024, 8, 2))
+        );
+    }
+
+    #[test]
+    fn test_status_and_projection() {
+        let quota = parse_quota("wwan0=40GiB/month,reset-day=15").unwrap();
+        let mut database = Database::default();
+        // Traffic before the period doesn't count
+        database.update(&counters(0), day(2024, 7, 10), None);
+        database.update(&counters(5 * GIB), day(2024, 7, 12), None);
+        database.update(&counters(5 * GIB), day(2024, 7, 15), None);
+        database.update(&counters(15 * GIB), day(2024, 7, 20), None);
+
+        // 10 GiB in 5 days, so the remaining 30 GiB last another 15 days
+        let status = quota.status(database.interfaces.get("wwan0"), day(2024, 7, 20));
+        assert_eq!(status.used_bytes, 10 * GIB);
+        assert_eq!(status.remaining_bytes, 30 * GIB);
+        assert_eq!(status.percent, 25.0);
+        assert_eq!(status.exhausted_at, Some(day(2024, 8, 4)));
+        assert_eq!(
+            format_quota_status(&status),
+            "wwan0 monthly quota: 10.0 GiB of 40.0 GiB used (25.0%), 30.0 GiB left until \
+             2024-08-15 00:00 UTC, used up around 2024-08-04 00:00 at the current rate"
+        );
+
+        // No projection if the quota lasts until the reset, or without an account
+        let status = quota.status(database.interfaces.get("wwan0"), day(2024, 8, 10));
+        assert_eq!(status.exhausted_at, None);
+        let status = quota.status(None, day(2024, 7, 20));
+        assert_eq!(status.used_bytes, 0);
+        assert_eq!(status.exhausted_at, None);
+    }
+
+    #[test]
+    fn test_thresholds_are_reported_once_per_period() {
+        let quota = parse_quota("wwan0=10GiB/day").unwrap();
+        let mut database = Database::default();
+        let noon = day(2024, 8, 1) + 43200;
+        database.update(&counters(0), noon, None);
+
+        database.update(&counters(7 * GIB), noon + 60, None);
+        assert_eq!(quota.check(&mut database, noon + 60), None);
+
+        database.update(&counters(8 * GIB), noon + 120, None);
+        let (status, threshold) = quota.check(&mut database, noon + 120).unwrap();
+        assert_eq!((status.used_bytes, threshold), (8 * GIB, 80));
+        assert_eq!(quota.check(&mut database, noon + 180), None);
+
+        database.update(&counters(11 * GIB), noon + 240, None);
+        assert_eq!(quota.check(&mut database, noon + 240).unwrap().1, 100);
+        assert_eq!(quota.check(&mut database, noon + 300), None);
+
+        // The next day starts over
+        database.update(&counters(20 * GIB), noon + 86400, None);
+        assert_eq!(quota.check(&mut database, noon + 86400).unwrap().1, 80);
+    }
+
+    #[test]
+    fn test_quotas_from_config() {
+        let config = Config::parse(
+            r#"
+[default]
+quotas = ["wwan0=50GiB/month,reset-day=15"]
+quota-command = "logger quota"
+"#,
+        )
+        .unwrap();
+        let matches = Opts::command()
+            .try_get_matches_from(["ifstat-rs", "daemon"])
+            .unwrap();
+        let mut opts = Opts::from_arg_matches(&matches).unwrap();
+        config.default.apply(&mut opts, &matches).unwrap();
+        assert_eq!(opts.quotas[0].reset_day, 15);
+        assert_eq!(opts.quota_command.as_deref(), Some("logger quota"));
+
+        let profile = Profile {
+            quotas: Some(vec!["wwan0=50GiB/year".to_string()]),
+            ..Default::default()
+        };
+        assert!(profile.apply(&mut opts, &matches).is_err());
+    }
+
+    #[test]
+    fn test_query_quotas_cli() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("accounting.json");
+        let mut database = Database::default();
+        database.update(&counters(0), now - 1, None);
+        database.update(&counters(GIB), now, None);
+        database.write(&path).unwrap();
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["query", "--quotas", "--format", "json", "--db"])
+            .arg(&path)
+            .args(["--quota", "wwan0=4GiB/day"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(json[0]["interface"], "wwan0");
+        assert_eq!(json[0]["period"], "day");
+        assert_eq!(json[0]["used_bytes"], GIB);
+        assert_eq!(json[0]["remaining_bytes"], 3 * GIB);
+        assert_eq!(json[0]["percent"], 25.0);
+    }
+}
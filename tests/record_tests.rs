@@ -0,0 +1,123 @@
+mod common;
+
+mod record_tests {
+    use crate::common::stats;
+    use assert_cmd::prelude::*;
+    use ifstat_rs::record::{parse_recording, read_recording, Recorder, Snapshot};
+    use std::io::{Cursor, Write};
+    use std::process::Command;
+
+    #[test]
+    fn test_record_roundtrip() {
+        let mut output = Vec::new();
+        {
+            let mut recorder = Recorder::new(&mut output);
+            recorder.record(&stats((1000, 2000), (10, 20))).unwrap();
+            recorder.record(&stats((3048, 4048), (10, 20))).unwrap();
+        }
+
+        let snapshots = parse_recording(Cursor::new(output)).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].stats, stats((1000, 2000), (10, 20)));
+        assert_eq!(snapshots[1].stats, stats((3048, 4048), (10, 20)));
+        assert!(snapshots[0].elapsed_us <= snapshots[1].elapsed_us);
+
+        // Interface order is preserved
+        let names: Vec<_> = snapshots[1].stats.keys().cloned().collect();
+        assert_eq!(names, vec!["eth0", "lo"]);
+    }
+
+    #[test]
+    fn test_create_replaces_recording() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        for _ in 0..2 {
+            let mut recorder = Recorder::create(file.path()).unwrap();
+            recorder.record(&stats((1000, 2000), (10, 20))).unwrap();
+        }
+
+        // Only the second session remains, so timestamps never go backwards
+        let snapshots = read_recording(file.path()).unwrap();
+        assert_eq!(snapshots.len(), 1);
+    }
+
+    #[test]
+    fn test_truncated_last_line_is_ignored() {
+        let data = "{\"t\":0,\"s\":{\"eth0\":[1,2]}}\n{\"t\":1000,\"s\":{\"eth0\":[3,";
+        let snapshots = parse_recording(Cursor::new(data)).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].stats["eth0"], (1, 2));
+    }
+
+    #[test]
+    fn test_invalid_line_is_error() {
+        let data = "not json\n{\"t\":0,\"s\":{\"eth0\":[1,2]}}\n";
+        let result = parse_recording(Cursor::new(data));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_replay_output() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        {
+            let mut recorder = Recorder::new(file.as_file_mut());
+            recorder
+                .write_snapshot(&Snapshot {
+                    elapsed_us: 0,
+                    stats: stats((1000, 2000), (10, 20)),
+                })
+                .unwrap();
+            recorder
+                .write_snapshot(&Snapshot {
+                    elapsed_us: 1_000_000,
+                    stats: stats((3048, 4048), (10, 20)),
+                })
+                .unwrap();
+        }
+        file.flush().unwrap();
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["-a", "--speed", "100", "--replay"])
+            .arg(file.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 3, "output: {}", stdout);
+        assert!(lines[0].contains("eth0") && lines[0].contains("lo"));
+        let values: Vec<&str> = lines[2].split_whitespace().collect();
+        assert_eq!(values, vec!["2.00", "2.00", "0.00", "0.00"]);
+    }
+
+    #[test]
+    fn test_replay_rates_are_per_second() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        {
+            let mut recorder = Recorder::new(file.as_file_mut());
+            for (elapsed_us, rx) in [(0, 0), (4_000_000, 8192)] {
+                recorder
+                    .write_snapshot(&Snapshot {
+                        elapsed_us,
+                        stats: stats((rx, 0), (0, 0)),
+                    })
+                    .unwrap();
+            }
+        }
+        file.flush().unwrap();
+
+        // 8 KB over 4 seconds is 2 KB/s, not the 8 KB of the whole interval
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["-i", "eth0", "--speed", "100", "--replay"])
+            .arg(file.path())
+            .arg("4")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let values: Vec<&str> = stdout.lines().nth(2).unwrap().split_whitespace().collect();
+        assert_eq!(values, vec!["2.00", "0.00"]);
+    }
+}
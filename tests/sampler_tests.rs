@@ -0,0 +1,101 @@
+mod sampler_tests {
+    use ifstat_rs::error::IfstatError;
+    use ifstat_rs::record::Snapshot;
+    use ifstat_rs::sampler::{Sample, Sampler};
+    use indexmap::IndexMap;
+    use std::time::Duration;
+
+    fn stats(rx: u64, tx: u64) -> IndexMap<String, (u64, u64)> {
+        vec![("eth0".to_string(), (rx, tx))].into_iter().collect()
+    }
+
+    /// Returns a fetch function yielding the given snapshots, repeating the last one.
+    fn scripted(
+        snapshots: Vec<IndexMap<String, (u64, u64)>>,
+    ) -> impl FnMut() -> Result<IndexMap<String, (u64, u64)>, IfstatError> + Send {
+        let mut index = 0;
+        move || {
+            let snapshot = snapshots[index.min(snapshots.len() - 1)].clone();
+            index += 1;
+            Ok(snapshot)
+        }
+    }
+
+    #[test]
+    fn test_sample_between() {
+        let sample = Sample::between(
+            &stats(1000, 2000),
+            &stats(3048, 2000),
+            Duration::from_secs(4),
+            Duration::from_secs(2),
+        );
+        let eth0 = sample.per_iface["eth0"];
+        assert_eq!((eth0.rx_bytes, eth0.tx_bytes), (3048, 2000));
+        assert_eq!((eth0.rx_bytes_per_s, eth0.tx_bytes_per_s), (1024.0, 0.0));
+        assert_eq!(sample.counters(), stats(3048, 2000));
+    }
+
+    #[test]
+    fn test_blocking_iterator() {
+        let fetch = scripted(vec![stats(0, 0), stats(100, 10), stats(300, 10)]);
+        let sampler = Sampler::from_fn(fetch, Duration::from_millis(10)).unwrap();
+        assert_eq!(sampler.baseline(), &stats(0, 0));
+
+        let samples: Vec<Sample> = sampler.take(2).map(|s| s.unwrap()).collect();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].per_iface["eth0"].rx_bytes, 100);
+        assert_eq!(samples[1].per_iface["eth0"].rx_bytes, 300);
+        assert!(samples[0].interval >= Duration::from_millis(10));
+        assert!(samples[1].at > samples[0].at);
+
+        // The rate is computed over the measured interval
+        let secs = samples[1].interval.as_secs_f64();
+        let expected = 200.0 / secs;
+        assert!((samples[1].per_iface["eth0"].rx_bytes_per_s - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_replay_stream() {
+        let snapshots = vec![
+            Snapshot {
+                elapsed_us: 0,
+                stats: stats(0, 0),
+            },
+            Snapshot {
+                elapsed_us: 2_000_000,
+                stats: stats(2048, 1024),
+            },
+            Snapshot {
+                elapsed_us: 3_000_000,
+                stats: stats(3072, 1024),
+            },
+        ];
+        let sampler = Sampler::replay(snapshots, f64::INFINITY).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        let samples = runtime.block_on(async {
+            let mut stream = sampler.into_stream();
+            let mut samples = Vec::new();
+            while let Some(sample) = stream.next().await {
+                samples.push(sample.unwrap());
+            }
+            samples
+        });
+
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].at, Duration::from_secs(2));
+        assert_eq!(samples[0].interval, Duration::from_secs(2));
+        assert_eq!(samples[0].per_iface["eth0"].rx_bytes_per_s, 1024.0);
+        assert_eq!(samples[0].per_iface["eth0"].tx_bytes_per_s, 512.0);
+        assert_eq!(samples[1].interval, Duration::from_secs(1));
+        assert_eq!(samples[1].per_iface["eth0"].rx_bytes_per_s, 1024.0);
+    }
+
+    #[test]
+    fn test_replay_without_snapshots_is_error() {
+        assert!(Sampler::replay(Vec::new(), 1.0).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+mod common;
+
+mod select_tests {
+    use crate::common::{info, infos, InfoExt};
+    use clap::Parser;
+    use ifstat_rs::net_stats::InterfaceInfo;
+    use ifstat_rs::opts::Opts;
+    use ifstat_rs::select::InterfaceSelector;
+    use indexmap::IndexMap;
+
+    fn docker_host() -> (
+        IndexMap<String, (u64, u64)>,
+        IndexMap<String, InterfaceInfo>,
+    ) {
+        let infos = infos([
+            info("lo", "loopback").with_operstate("unknown"),
+            info("eno1", "physical")
+                .with_operstate("up")
+                .with_driver("ixgbe"),
+            info("eno2", "physical")
+                .with_operstate("down")
+                .with_driver("ixgbe"),
+            info("wlp3s0", "physical")
+                .with_operstate("up")
+                .with_driver("iwlwifi"),
+            info("docker0", "bridge").with_operstate("up"),
+            info("veth1a2b", "veth").with_operstate("up"),
+        ]);
+        let stats = infos.keys().map(|name| (name.clone(), (0, 0))).collect();
+        (stats, infos)
+    }
+
+    fn select(args: &[&str]) -> Vec<String> {
+        let (stats, infos) = docker_host();
+        let opts = Opts::parse_from(std::iter::once("ifstat-rs").chain(args.iter().copied()));
+        InterfaceSelector::from_opts(&opts)
+            .select(&stats, Some(&infos))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_select_by_name_and_loopback() {
+        assert_eq!(
+            select(&[]),
+            ["eno1", "eno2", "wlp3s0", "docker0", "veth1a2b"]
+        );
+        assert_eq!(select(&["-l"]).len(), 6);
+        assert_eq!(select(&["-a", "-i", "eno1"]).len(), 6);
+        assert_eq!(select(&["-i", "veth1a2b,eno1"]), ["veth1a2b", "eno1"]);
+    }
+
+    #[test]
+    fn test_select_by_metadata() {
+        assert_eq!(
+            select(&["--type", "physical", "--state", "up"]),
+            ["eno1", "wlp3s0"]
+        );
+        assert_eq!(
+            select(&["--exclude-type", "veth,bridge"]),
+            ["eno1", "eno2", "wlp3s0"]
+        );
+        assert_eq!(select(&["--driver", "ixgbe"]), ["eno1", "eno2"]);
+        assert_eq!(select(&["--type", "loopback"]), ["lo"]);
+        assert_eq!(select(&["-i", "lo,eno1", "--state", "up"]), ["eno1"]);
+    }
+
+    #[test]
+    fn test_select_without_metadata() {
+        let (stats, _) = docker_host();
+        let selector = InterfaceSelector::default();
+        // Falls back to the name heuristic for loopback
+        assert_eq!(selector.select(&stats, None).unwrap().len(), 5);
+
+        let selector = InterfaceSelector {
+            types: vec!["physical".to_string()],
+            ..Default::default()
+        };
+        assert!(selector.select(&stats, None).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+mod smoothing_tests {
+    use assert_cmd::prelude::*;
+    use ifstat_rs::sampler::Sample;
+    use ifstat_rs::smoothing::{Smoother, Smoothing};
+    use indexmap::IndexMap;
+    use std::io::Write;
+    use std::process::Command;
+    use std::time::Duration;
+
+    /// Returns a sample with the given received bytes per second of `eth0`.
+    fn sample(rx_rate: u64) -> Sample {
+        let previous: IndexMap<String, (u64, u64)> = [("eth0".to_string(), (0, 0))].into();
+        let current: IndexMap<String, (u64, u64)> = [("eth0".to_string(), (rx_rate, 0))].into();
+        Sample::between(
+            &previous,
+            &current,
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        )
+    }
+
+    fn smoothed_rx(smoother: &mut Smoother, rates: &[u64]) -> Vec<f64> {
+        rates
+            .iter()
+            .map(|&rate| smoother.smooth(&sample(rate)).per_iface["eth0"].rx_bytes_per_s)
+            .collect()
+    }
+
+    #[test]
+    fn test_moving_average() {
+        let mut smoother = Smoother::new(Smoothing::MovingAverage(3));
+        assert_eq!(
+            smoothed_rx(&mut smoother, &[300, 0, 600, 0, 0, 0]),
+            [300.0, 150.0, 300.0, 200.0, 200.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_ewma() {
+        let mut smoother = Smoother::new(Smoothing::Ewma(0.5));
+        assert_eq!(
+            smoothed_rx(&mut smoother, &[400, 0, 0, 800]),
+            [400.0, 200.0, 100.0, 450.0]
+        );
+
+        // An alpha of 1 doesn't smooth at all
+        let mut smoother = Smoother::new(Smoothing::Ewma(1.0));
+        assert_eq!(smoothed_rx(&mut smoother, &[400, 0]), [400.0, 0.0]);
+    }
+
+    #[test]
+    fn test_missing_interface_keeps_history() {
+        let mut smoother = Smoother::new(Smoothing::MovingAverage(2));
+        smoother.smooth(&sample(1000));
+        let empty = Sample::between(
+            &IndexMap::new(),
+            &IndexMap::new(),
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+        );
+        assert!(smoother.smooth(&empty).per_iface.is_empty());
+        assert_eq!(smoothed_rx(&mut smoother, &[0]), [500.0]);
+    }
+
+    #[test]
+    fn test_average_option() {
+        let header = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+";
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for rx in [0, 2048, 2048] {
+            writeln!(
+                file,
+                "{}  eth0:  {}    1264    0    0    0     0          0         0   0    1571    0    0    0     0       0          0",
+                header, rx
+            )
+            .unwrap();
+        }
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["analyze", "--interval", "1", "--average", "2", "--show-raw"])
+            .arg(file.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert!(lines[0].contains("eth0 raw"), "{}", stdout);
+        assert_eq!(
+            lines[2].split_whitespace().collect::<Vec<_>>(),
+            ["2.00", "0.00", "2.00", "0.00"]
+        );
+        assert_eq!(
+            lines[3].split_whitespace().collect::<Vec<_>>(),
+            ["1.00", "0.00", "0.00", "0.00"]
+        );
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["analyze", "--interval", "1", "--show-raw"])
+            .arg(file.path())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["analyze", "--ewma", "1.5"])
+            .arg(file.path())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+    }
+}
@@ -0,0 +1,144 @@
+mod softnet_stat_tests {
+    use ifstat_rs::net_stats::{compute_softnet_rates, parse_softnet_stat, SoftnetStat};
+    use ifstat_rs::output::{
+        print_headers_with_columns, print_rates_with_columns, softnet_column_groups,
+        softnet_column_values,
+    };
+    use indexmap::IndexMap;
+    use std::io::Cursor;
+
+    // Linux 5.10+ with the CPU number in the 13th column, CPU 1 offline
+    const SOFTNET_STAT: &str = "\
+0001a2b3 00000000 00000003 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000
+0000ff00 00000002 00000010 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000002
+";
+
+    // Older kernels with 11 columns
+    const SOFTNET_STAT_OLD: &str = "\
+00000010 00000000 00000001 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000
+00000020 00000000 00000002 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000
+";
+
+    #[test]
+    fn test_parse_softnet_stat() {
+        let stats = parse_softnet_stat(Cursor::new(SOFTNET_STAT)).unwrap();
+        assert_eq!(stats.keys().copied().collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(
+            stats[&0],
+            SoftnetStat {
+                processed: 0x1a2b3,
+                dropped: 0,
+                time_squeeze: 3,
+            }
+        );
+        assert_eq!(stats[&2].dropped, 2);
+        assert_eq!(stats[&2].time_squeeze, 0x10);
+
+        let old = parse_softnet_stat(Cursor::new(SOFTNET_STAT_OLD)).unwrap();
+        assert_eq!(old.keys().copied().collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(old[&1].processed, 0x20);
+    }
+
+    #[test]
+    fn test_parse_softnet_stat_errors() {
+        let err = parse_softnet_stat(Cursor::new("0000000x 0 0\n")).unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+        assert!(parse_softnet_stat(Cursor::new("00000001 00000002\n")).is_err());
+        assert!(parse_softnet_stat(Cursor::new("")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compute_softnet_rates() {
+        let stat = |processed, dropped, time_squeeze| SoftnetStat {
+            processed,
+            dropped,
+            time_squeeze,
+        };
+        let previous: IndexMap<u32, SoftnetStat> =
+            [(0, stat(100, 0, 0)), (1, stat(0xffff_fff0, 0, 0))]
+                .into_iter()
+                .collect();
+        let current: IndexMap<u32, SoftnetStat> = [
+            (0, stat(300, 4, 2)),
+            (1, stat(0x10, 0, 0)),
+            (2, stat(5, 0, 0)),
+        ]
+        .into_iter()
+        .collect();
+
+        let rates = compute_softnet_rates(&previous, &current, 2.0);
+        assert_eq!(rates[&0].processed_per_s, 100.0);
+        assert_eq!(rates[&0].dropped_per_s, 2.0);
+        assert_eq!(rates[&0].time_squeeze_per_s, 1.0);
+        // The 32 bit kernel counter wrapped around
+        assert_eq!(rates[&1].processed_per_s, 16.0);
+        // CPUs without a previous snapshot are skipped
+        assert!(!rates.contains_key(&2));
+    }
+
+    #[test]
+    fn test_print_softnet_columns() {
+        let previous = parse_softnet_stat(Cursor::new(SOFTNET_STAT)).unwrap();
+        let mut current = previous.clone();
+        current[&2].processed += 1024;
+        let rates = compute_softnet_rates(&previous, &current, 1.0);
+        let cpus: Vec<u32> = current.keys().copied().collect();
+
+        let interfaces = vec!["eth0".to_string()];
+        let stats: IndexMap<String, (u64, u64)> =
+            [("eth0".to_string(), (0, 0))].into_iter().collect();
+        let iface_rates: IndexMap<String, (f64, f64)> =
+            [("eth0".to_string(), (1024.0, 0.0))].into_iter().collect();
+        let mut output = Vec::new();
+        print_headers_with_columns(
+            &interfaces,
+            &mut output,
+            false,
+            &stats,
+            &softnet_column_groups(&cpus),
+        )
+        .unwrap();
+        print_rates_with_columns(
+            &iface_rates,
+            &softnet_column_values(&cpus, &rates),
+            &mut output,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].contains("cpu0") && lines[0].contains("cpu2"));
+        assert!(lines[1].contains("sqz/s"));
+        let values: Vec<&str> = lines[2].split_whitespace().collect();
+        assert_eq!(
+            values,
+            ["1.00", "0.00", "0.00", "0.00", "0.00", "1024.00", "0.00", "0.00"]
+        );
+        assert_eq!(lines[1].len(), lines[2].len());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_softnet_cli() {
+        use assert_cmd::Command;
+
+        let output = Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["--softnet", "--format", "json", "-i", "lo", "0.1", "1"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let line: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let cpus = line["softnet"].as_object().unwrap();
+        assert!(!cpus.is_empty());
+        assert!(cpus
+            .values()
+            .all(|rates| rates["dropped_per_s"].is_number()));
+
+        Command::cargo_bin("ifstat-rs")
+            .unwrap()
+            .args(["--softnet", "--format", "csv", "0.1", "1"])
+            .assert()
+            .failure();
+    }
+}